@@ -0,0 +1,173 @@
+//! Compares decoding with a precompiled `DecodePlan` against walking the `MsgSpec` tree.
+#![allow(clippy::unwrap_used, clippy::missing_assert_message)] // Fine in benchmarks
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use rerun_ros::cdr::{decode, DecodePlan};
+use rerun_ros::ros_introspection::{primitive_layout, BuiltinType, Dialect, MsgSpec};
+
+const HEADER: &str = r#"
+================================================================================
+MSG: std_msgs/Header
+builtin_interfaces/Time stamp
+string frame_id
+================================================================================
+MSG: builtin_interfaces/Time
+int32 sec
+uint32 nanosec
+"#;
+
+const VECTOR3: &str = r#"
+================================================================================
+MSG: geometry_msgs/Vector3
+float64 x
+float64 y
+float64 z
+"#;
+
+const QUATERNION: &str = r#"
+================================================================================
+MSG: geometry_msgs/Quaternion
+float64 x
+float64 y
+float64 z
+float64 w
+"#;
+
+const IMU: &str = r#"
+std_msgs/Header header
+geometry_msgs/Quaternion orientation
+float64[9] orientation_covariance
+geometry_msgs/Vector3 angular_velocity
+float64[9] angular_velocity_covariance
+geometry_msgs/Vector3 linear_acceleration
+float64[9] linear_acceleration_covariance
+"#;
+
+const ODOMETRY: &str = r#"
+std_msgs/Header header
+string child_frame_id
+geometry_msgs/PoseWithCovariance pose
+geometry_msgs/TwistWithCovariance twist
+================================================================================
+MSG: geometry_msgs/PoseWithCovariance
+geometry_msgs/Pose pose
+float64[36] covariance
+================================================================================
+MSG: geometry_msgs/Pose
+geometry_msgs/Point position
+geometry_msgs/Quaternion orientation
+================================================================================
+MSG: geometry_msgs/Point
+float64 x
+float64 y
+float64 z
+================================================================================
+MSG: geometry_msgs/TwistWithCovariance
+geometry_msgs/Twist twist
+float64[36] covariance
+================================================================================
+MSG: geometry_msgs/Twist
+geometry_msgs/Vector3 linear
+geometry_msgs/Vector3 angular
+"#;
+
+const TF_MESSAGE: &str = r#"
+geometry_msgs/TransformStamped[] transforms
+================================================================================
+MSG: geometry_msgs/TransformStamped
+std_msgs/Header header
+string child_frame_id
+geometry_msgs/Transform transform
+================================================================================
+MSG: geometry_msgs/Transform
+geometry_msgs/Vector3 translation
+geometry_msgs/Quaternion rotation
+"#;
+
+/// Number of elements written for each sequence, e.g. the transforms of a `TFMessage`.
+const SEQUENCE_LENGTH: u32 = 32;
+
+/// Serializes a sample message of the given type, with every number set to zero.
+fn sample_message(spec: &MsgSpec) -> Vec<u8> {
+    let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+    write_message(spec, &mut buffer);
+    buffer
+}
+
+fn align(buffer: &mut Vec<u8>, align: usize) {
+    // Alignment is relative to the end of the encapsulation header
+    let padding = (buffer.len() - 4).next_multiple_of(align) - (buffer.len() - 4);
+    buffer.resize(buffer.len() + padding, 0);
+}
+
+fn write_message(spec: &MsgSpec, buffer: &mut Vec<u8>) {
+    for (field, child) in spec.serialized_fields() {
+        let count = match usize::try_from(field.array_size()) {
+            Ok(size) => size,
+            Err(_) if field.is_array() => {
+                align(buffer, 4);
+                buffer.extend(SEQUENCE_LENGTH.to_le_bytes());
+                SEQUENCE_LENGTH as usize
+            }
+            Err(_) => 1,
+        };
+
+        for _ in 0..count {
+            if let Some((size, alignment)) = primitive_layout(field.type_().id()) {
+                align(buffer, alignment);
+                buffer.resize(buffer.len() + size, 0);
+            } else if field.type_().id() == &BuiltinType::String {
+                align(buffer, 4);
+                buffer.extend(10_u32.to_le_bytes());
+                buffer.extend(b"base_link\0");
+            } else if let Some(child) = child {
+                write_message(child, buffer);
+            }
+        }
+    }
+}
+
+fn bench_decode(c: &mut Criterion) {
+    for (name, definition, dependencies) in [
+        (
+            "sensor_msgs/Imu",
+            IMU,
+            format!("{HEADER}{QUATERNION}{VECTOR3}"),
+        ),
+        (
+            "nav_msgs/Odometry",
+            ODOMETRY,
+            format!("{HEADER}{QUATERNION}{VECTOR3}"),
+        ),
+        (
+            "tf2_msgs/TFMessage",
+            TF_MESSAGE,
+            format!("{HEADER}{QUATERNION}{VECTOR3}"),
+        ),
+    ] {
+        let spec = MsgSpec::new_from_definitions(
+            name,
+            &format!("{definition}{dependencies}"),
+            Dialect::Ros2,
+        )
+        .unwrap();
+        let plan = DecodePlan::new(&spec).unwrap();
+        let buffer = sample_message(&spec);
+        assert_eq!(
+            plan.decode(&buffer).unwrap(),
+            decode(&spec, &buffer).unwrap()
+        );
+
+        let mut group = c.benchmark_group(name);
+        group.bench_function("tree", |b| {
+            b.iter(|| decode(black_box(&spec), black_box(&buffer)).unwrap());
+        });
+        group.bench_function("plan", |b| {
+            b.iter(|| plan.decode(black_box(&buffer)).unwrap());
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
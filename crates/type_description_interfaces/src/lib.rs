@@ -0,0 +1,9 @@
+//! Placeholder for the `type_description_interfaces` crate generated by `rosidl_generator_rs`.
+//!
+//! The generated crate is not published on crates.io. Building with colcon-ros-cargo patches it
+//! in from the sourced ROS 2 workspace, which takes precedence over this placeholder.
+
+compile_error!(
+    "the type-description feature needs the type_description_interfaces of a sourced ROS 2 \
+     Jazzy workspace, build it with colcon"
+);
@@ -0,0 +1,89 @@
+#!/usr/bin/env python3
+
+"""
+Generates the serialized messages of the CDR fixtures in `tests/fixtures/cdr`.
+
+Each fixture is made of three files sharing a name:
+- `<name>.json` holds the message type, the serializer that produced `<name>.cdr` along with
+  the ROS 2 distribution and the RMW implementation it ran with, and the expected decoded value,
+  in the format of `rerun_ros echo --format json`: arrays of `uint8`, `byte` and `char` are base64
+  strings, and `byte` and `char` fields are integers.
+- `<name>.msg` holds the full message definition, as stored by rosbag2: the definition of the
+  type, followed by those of its dependencies, each introduced by a `MSG: pkg/Name` line.
+- `<name>.cdr` holds the serialized message, including its encapsulation header.
+
+Messages are built and serialized with `rclpy`, which requires a sourced ROS 2 environment
+providing `test_msgs` and `common_interfaces`:
+
+    python3 scripts/generate_cdr_fixtures.py
+
+The distribution and the RMW implementation matter: Fast DDS writes 32-bit `wstring` characters
+up to Iron, and 16-bit ones from Jazzy on.
+"""
+
+from __future__ import annotations
+
+import argparse
+import base64
+import json
+import os
+import re
+from pathlib import Path
+from typing import Any
+
+FIXTURES = Path(__file__).resolve().parent.parent / "tests" / "fixtures" / "cdr"
+
+
+def rclpy_environment() -> dict[str, str]:
+    """Names the ROS 2 distribution and RMW implementation that `rclpy` serializes with."""
+    from rclpy.utilities import get_rmw_implementation_identifier
+
+    return {"distro": os.environ["ROS_DISTRO"], "rmw": get_rmw_implementation_identifier()}
+
+
+def with_rclpy(type_name: str, value: dict[str, Any]) -> bytes:
+    from rclpy.serialization import serialize_message
+    from rosidl_runtime_py.utilities import get_message
+
+    def element_class(field_type: str) -> Any:
+        package, name = field_type.split("/")
+        return get_message(f"{package}/msg/{name}")
+
+    def convert(field_type: str, value: Any) -> Any:
+        match = re.match(r"^sequence<([^,>]+)(, \d+)?>$", field_type) or re.match(r"^([^\[]+)\[\d+\]$", field_type)
+        if match is not None:
+            base = match[1]
+            if base in ("uint8", "octet", "char"):
+                value = list(base64.b64decode(value))
+            return [convert(base, element) for element in value]
+        if field_type == "octet":
+            return bytes([value])
+        if field_type == "char":
+            return chr(value)
+        if "/" in field_type:
+            return fill(element_class(field_type)(), value)
+        return value
+
+    def fill(msg: Any, value: dict[str, Any]) -> Any:
+        for name, field_type in msg.get_fields_and_field_types().items():
+            setattr(msg, name, convert(field_type, value[name]))
+        return msg
+
+    return serialize_message(fill(get_message(type_name)(), value))
+
+
+def main() -> None:
+    argparse.ArgumentParser(description="Generate the serialized messages of the CDR fixtures").parse_args()
+    environment = rclpy_environment()
+
+    for path in sorted(FIXTURES.glob("*.json")):
+        fixture = json.loads(path.read_text())
+        serialized = with_rclpy(fixture["type"], fixture["value"])
+        path.with_suffix(".cdr").write_bytes(serialized)
+        fixture = {"type": fixture["type"], "serializer": "rclpy", **environment, "value": fixture["value"]}
+        path.write_text(json.dumps(fixture, indent=2, ensure_ascii=False) + "\n")
+        print(f"{path.with_suffix('.cdr').name}: {len(serialized)} bytes ({environment['distro']}, {environment['rmw']})")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,544 @@
+use std::mem::size_of;
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::cdr::{locate, CdrReader, DecodeLimits};
+use crate::ros_introspection::{primitive_layout, BuiltinType, Field, MsgSpec, LENGTH_PREFIX_SIZE};
+use crate::value::Value;
+
+/// Decodes a CDR serialized message into a generic `Value`.
+///
+/// # Arguments
+///
+/// * `spec` - A reference to the `MsgSpec` of the message type.
+/// * `buffer` - The serialized message, starting with its encapsulation header.
+///
+/// # Returns
+///
+/// * `Result<Value, Error>` - A result containing the decoded message or an error.
+///
+/// # Errors
+///
+/// This function will return a [`DecodeError`](crate::cdr::DecodeError), naming the field
+/// being decoded and its offset, if the buffer does not hold a message of the given type.
+pub fn decode(spec: &MsgSpec, buffer: &[u8]) -> Result<Value, Error> {
+    decode_with_limits(spec, buffer, DecodeLimits::default())
+}
+
+/// Decodes a CDR serialized message into a generic `Value`, enforcing custom limits.
+///
+/// # Arguments
+///
+/// * `spec` - A reference to the `MsgSpec` of the message type.
+/// * `buffer` - The serialized message, starting with its encapsulation header.
+/// * `limits` - The limits to enforce while decoding the message.
+///
+/// # Returns
+///
+/// * `Result<Value, Error>` - A result containing the decoded message or an error.
+///
+/// # Errors
+///
+/// This function will return a [`DecodeError`](crate::cdr::DecodeError), naming the field
+/// being decoded and its offset, if the buffer does not hold a message of the given type or
+/// if decoding it would exceed the limits.
+pub fn decode_with_limits(
+    spec: &MsgSpec,
+    buffer: &[u8],
+    limits: DecodeLimits,
+) -> Result<Value, Error> {
+    let mut reader = CdrReader::with_limits(buffer, limits)?;
+    let position = reader.position();
+    decode_message(&mut reader, spec).map_err(|err| locate(err, "", position))
+}
+
+/// Decodes a message from the current position of a reader.
+///
+/// # Errors
+///
+/// This function will return a [`DecodeError`](crate::cdr::DecodeError), naming the field
+/// being decoded, if the reader does not hold a message of the given type.
+pub fn decode_message(reader: &mut CdrReader<'_>, spec: &MsgSpec) -> Result<Value, Error> {
+    reader.read_struct_header()?;
+    let mut fields = Vec::new();
+
+    for (field, child) in spec.serialized_fields() {
+        let position = reader.position();
+        let value = reader
+            .allocate(size_of::<(String, Value)>() + field.name().len())
+            .and_then(|()| decode_field(reader, field, child))
+            .map_err(|err| locate(err, field.name(), position))?;
+        fields.push((field.name().to_owned(), value));
+    }
+
+    if spec.is_empty_struct() {
+        reader.read_empty_struct()?;
+    }
+
+    Ok(Value::Message(fields))
+}
+
+/// Decodes a field, or all elements of an array field, from the current position of a reader.
+///
+/// # Errors
+///
+/// This function will return an error if the reader does not hold a value of the field's type,
+/// or if the value exceeds a bound of the field.
+pub fn decode_field(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+    child: Option<&MsgSpec>,
+) -> Result<Value, Error> {
+    if !field.is_array() {
+        return decode_single(reader, field, child);
+    }
+
+    let count = read_element_count(reader, field)?;
+    match field.type_().id() {
+        BuiltinType::Uint8 | BuiltinType::Byte | BuiltinType::Char => {
+            reader.allocate(count)?;
+            Ok(Value::Bytes(reader.read_bytes(count)?.to_vec()))
+        }
+        _ => {
+            reader.allocate(count.saturating_mul(size_of::<Value>()))?;
+            let mut elements = Vec::with_capacity(count);
+            for index in 0..count {
+                let position = reader.position();
+                elements.push(
+                    decode_single(reader, field, child)
+                        .map_err(|err| locate(err, &format!("[{index}]"), position))?,
+                );
+            }
+            Ok(Value::Array(elements))
+        }
+    }
+}
+
+/// Reads the number of elements of an array field, leaving the reader at its first element.
+///
+/// The DHEADER that precedes arrays of non-primitive elements in XCDR2 is skipped, and the
+/// length prefix is only read for sequences. As the length prefix comes from the buffer, it is
+/// checked against the bound of the sequence and against the number of elements that the
+/// remaining bytes could hold.
+///
+/// # Errors
+///
+/// This function will return an error if the buffer is too short, or if the length prefix is
+/// out of bounds.
+pub fn read_element_count(reader: &mut CdrReader<'_>, field: &Field) -> Result<usize, Error> {
+    if primitive_layout(field.type_().id()).is_none() {
+        reader.read_collection_header()?;
+    }
+    read_checked_count(reader, field)
+}
+
+/// Reads the number of elements of an array field, once past its DHEADER.
+pub(crate) fn read_checked_count(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+) -> Result<usize, Error> {
+    if let Ok(size) = usize::try_from(field.array_size()) {
+        return Ok(size);
+    }
+
+    let count = reader.read_length()?;
+    if let Some(bound) = field.array_bound() {
+        if count > bound {
+            bail!("Sequence length {count} exceeds the bound of {bound}");
+        }
+    }
+
+    // The smallest serialized element, to reject lengths the buffer cannot possibly hold
+    let min_size = match (primitive_layout(field.type_().id()), field.type_().id()) {
+        (Some((size, _)), _) => size,
+        (None, BuiltinType::String | BuiltinType::WString) => LENGTH_PREFIX_SIZE,
+        (None, _) => 1,
+    };
+    if count.saturating_mul(min_size) > reader.remaining() {
+        bail!(
+            "Sequence length {count} exceeds the {} remaining byte(s)",
+            reader.remaining()
+        );
+    }
+    Ok(count)
+}
+
+/// Checks the length of a decoded string, in bytes or wide characters, against the bound of
+/// its field.
+fn check_string_bound(field: &Field, len: usize) -> Result<(), Error> {
+    if let Some(bound) = field.string_bound().filter(|bound| len > *bound) {
+        bail!("String of length {len} exceeds the bound of {bound}");
+    }
+    Ok(())
+}
+
+fn decode_single(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+    child: Option<&MsgSpec>,
+) -> Result<Value, Error> {
+    Ok(match field.type_().id() {
+        BuiltinType::Bool => Value::Bool(reader.read_u8()? != 0),
+        BuiltinType::Byte | BuiltinType::Char | BuiltinType::Uint8 => {
+            Value::Uint8(reader.read_u8()?)
+        }
+        BuiltinType::Int8 => Value::Int8(reader.read_i8()?),
+        BuiltinType::Uint16 => Value::Uint16(reader.read_u16()?),
+        BuiltinType::Int16 => Value::Int16(reader.read_i16()?),
+        BuiltinType::Uint32 => Value::Uint32(reader.read_u32()?),
+        BuiltinType::Int32 => Value::Int32(reader.read_i32()?),
+        BuiltinType::Uint64 => Value::Uint64(reader.read_u64()?),
+        BuiltinType::Int64 => Value::Int64(reader.read_i64()?),
+        BuiltinType::Float32 => Value::Float32(reader.read_f32()?),
+        BuiltinType::Float64 => Value::Float64(reader.read_f64()?),
+        BuiltinType::String => {
+            let string = reader.read_string()?;
+            check_string_bound(field, string.len())?;
+            Value::String(string)
+        }
+        BuiltinType::WString => {
+            let string = reader.read_wstring()?;
+            check_string_bound(field, string.chars().count())?;
+            Value::String(string)
+        }
+        BuiltinType::Time => Value::Message(vec![
+            ("sec".to_owned(), Value::Uint32(reader.read_u32()?)),
+            ("nsec".to_owned(), Value::Uint32(reader.read_u32()?)),
+        ]),
+        BuiltinType::Duration => Value::Message(vec![
+            ("sec".to_owned(), Value::Int32(reader.read_i32()?)),
+            ("nsec".to_owned(), Value::Int32(reader.read_i32()?)),
+        ]),
+        BuiltinType::Other => {
+            let child = child
+                .ok_or_else(|| anyhow!("Missing specification of {}", field.type_().name()))?;
+            decode_message(reader, child)?
+        }
+    })
+}
+
+/// Skips a message at the current position of a reader, without decoding it.
+///
+/// Strings and sequences are skipped using their length prefixes, and delimited structures
+/// using their DHEADER.
+///
+/// # Errors
+///
+/// This function will return an error if the reader does not hold a message of the given type.
+pub fn skip_message(reader: &mut CdrReader<'_>, spec: &MsgSpec) -> Result<(), Error> {
+    if let Some(size) = reader.read_struct_header()? {
+        return reader.read_bytes(size).map(drop);
+    }
+
+    for (field, child) in spec.serialized_fields() {
+        let position = reader.position();
+        skip_field(reader, field, child).map_err(|err| locate(err, field.name(), position))?;
+    }
+
+    if spec.is_empty_struct() {
+        reader.read_empty_struct()?;
+    }
+    Ok(())
+}
+
+/// Skips a field at the current position of a reader, without decoding it.
+///
+/// # Errors
+///
+/// This function will return an error if the reader does not hold a value of the field's type.
+pub fn skip_field(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+    child: Option<&MsgSpec>,
+) -> Result<(), Error> {
+    let layout = primitive_layout(field.type_().id());
+    if field.is_array() && layout.is_none() {
+        if let Some(size) = reader.read_collection_header()? {
+            return reader.read_bytes(size).map(drop);
+        }
+    }
+
+    let count = if field.is_array() {
+        read_checked_count(reader, field)?
+    } else {
+        1
+    };
+
+    if let Some((size, align)) = layout {
+        // Like Fast-CDR, empty arrays are not padded
+        if count > 0 {
+            reader.align(align)?;
+        }
+        let len = size
+            .checked_mul(count)
+            .ok_or_else(|| anyhow!("Invalid sequence length {count}"))?;
+        return reader.read_bytes(len).map(drop);
+    }
+
+    for index in 0..count {
+        let position = reader.position();
+        skip_single(reader, field, child).map_err(|err| {
+            if field.is_array() {
+                locate(err, &format!("[{index}]"), position)
+            } else {
+                err
+            }
+        })?;
+    }
+    Ok(())
+}
+
+fn skip_single(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+    child: Option<&MsgSpec>,
+) -> Result<(), Error> {
+    match (field.type_().id(), child) {
+        (BuiltinType::Other, Some(child)) => skip_message(reader, child),
+        (BuiltinType::Other, None) => {
+            Err(anyhow!("Missing specification of {}", field.type_().name()))
+        }
+        (BuiltinType::WString, _) => {
+            let len = reader.read_length()?;
+            reader
+                .read_bytes(
+                    len.checked_mul(reader.wchar_size().bytes())
+                        .ok_or_else(|| anyhow!("Invalid wstring length {len}"))?,
+                )
+                .map(drop)
+        }
+        _ => {
+            let len = reader.read_length()?;
+            reader.read_bytes(len).map(drop)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdr::DecodeError;
+    use crate::ros_introspection::Dialect;
+
+    fn spec(definitions: &str) -> MsgSpec {
+        MsgSpec::new_from_definitions("test_msgs/Test", definitions, Dialect::Ros2).unwrap()
+    }
+
+    #[test]
+    fn test_decode() {
+        let spec = spec(
+            r#"
+            std_msgs/Header header
+            uint8[] data
+            geometry_msgs/Point[] points
+            int16[2] pair
+            bool flag
+            ================================================================================
+            MSG: std_msgs/Header
+            builtin_interfaces/Time stamp
+            string frame_id
+            ================================================================================
+            MSG: builtin_interfaces/Time
+            int32 sec
+            uint32 nanosec
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+            "#,
+        );
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(12_i32.to_le_bytes());
+        buffer.extend(34_u32.to_le_bytes());
+        buffer.extend(5_u32.to_le_bytes());
+        buffer.extend(b"odom\0");
+        buffer.extend([0, 0, 0]); // padding to 4
+        buffer.extend(2_u32.to_le_bytes());
+        buffer.extend([7, 8]);
+        buffer.extend([0, 0]); // padding to 4
+        buffer.extend(1_u32.to_le_bytes());
+        for coordinate in [1.0_f64, 2.0, 3.0] {
+            buffer.extend(coordinate.to_le_bytes());
+        }
+        buffer.extend((-1_i16).to_le_bytes());
+        buffer.extend(2_i16.to_le_bytes());
+        buffer.push(1);
+
+        let value = decode(&spec, &buffer).unwrap();
+        assert_eq!(value.get_i64("header.stamp.sec").unwrap(), 12);
+        assert_eq!(value.get_u64("header.stamp.nanosec").unwrap(), 34);
+        assert_eq!(value.get_str("header.frame_id").unwrap(), "odom");
+        assert_eq!(value.get_bytes("data").unwrap(), [7, 8]);
+        assert_eq!(value.get_f64("points[0].z").unwrap(), 3.0);
+        assert_eq!(
+            value.get("pair").unwrap().to_f64_vec().unwrap(),
+            [-1.0, 2.0]
+        );
+        assert!(value.get_bool("flag").unwrap());
+    }
+
+    #[test]
+    fn test_decode_empty_message() {
+        let spec = spec(
+            r#"
+            std_msgs/Empty empty
+            int32 value
+            ================================================================================
+            MSG: std_msgs/Empty
+            "#,
+        );
+
+        let mut buffer = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+        buffer.extend(5_i32.to_be_bytes());
+
+        let value = decode(&spec, &buffer).unwrap();
+        assert_eq!(value.get("empty").unwrap(), &Value::Message(Vec::new()));
+        assert_eq!(value.get_i64("value").unwrap(), 5);
+    }
+
+    #[test]
+    fn test_decode_delimited_xcdr2() {
+        let spec = spec(
+            r#"
+            float64 x
+            string[] names
+            geometry_msgs/Point point
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            "#,
+        );
+
+        let mut buffer = vec![0x00, 0x09, 0x00, 0x00];
+        buffer.extend(40_u32.to_le_bytes()); // DHEADER of the message
+        buffer.extend(1.5_f64.to_le_bytes()); // only aligned to 4
+        buffer.extend(16_u32.to_le_bytes()); // DHEADER of the sequence
+        buffer.extend(1_u32.to_le_bytes());
+        buffer.extend(7_u32.to_le_bytes());
+        buffer.extend(b"camera\0");
+        buffer.push(0); // padding to 4
+        buffer.extend(8_u32.to_le_bytes()); // DHEADER of the point
+        buffer.extend((-2.0_f64).to_le_bytes());
+
+        let value = decode(&spec, &buffer).unwrap();
+        assert_eq!(value.get_f64("x").unwrap(), 1.5);
+        assert_eq!(value.get("names[0]").unwrap().as_str().unwrap(), "camera");
+        assert_eq!(value.get_f64("point.x").unwrap(), -2.0);
+
+        // Skipping jumps over the DHEADERs
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        skip_message(&mut reader, &spec).unwrap();
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        let spec = spec(
+            r#"
+            geometry_msgs/Point point
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            "#,
+        );
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(1.0_f64.to_le_bytes());
+
+        let err = decode(&spec, &buffer).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Could not decode `point.y` at byte offset 12: \
+             Unexpected end of buffer: needed 8 byte(s), 0 remaining"
+        );
+    }
+
+    #[test]
+    fn test_decode_malicious_lengths() {
+        let spec = spec(
+            r#"
+            test_msgs/Entry[] entries
+            int32[<=2] bounded
+            ================================================================================
+            MSG: test_msgs/Entry
+            string<=4 name
+            float64[] values
+            "#,
+        );
+        let entries = |name: &[u8], values: u32| {
+            let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+            buffer.extend(1_u32.to_le_bytes());
+            buffer.extend(u32::try_from(name.len()).unwrap().to_le_bytes());
+            buffer.extend(name);
+            buffer.resize(4 + (buffer.len() - 4).next_multiple_of(4), 0);
+            buffer.extend(values.to_le_bytes());
+            buffer
+        };
+        let message = |buffer: &[u8]| {
+            let err = decode(&spec, buffer).unwrap_err();
+            let decode_error = err.downcast_ref::<DecodeError>().unwrap();
+            (
+                decode_error.path().to_owned(),
+                decode_error.offset(),
+                decode_error.message().to_owned(),
+            )
+        };
+
+        assert_eq!(
+            message(&entries(b"car\0", u32::MAX)),
+            (
+                "entries[0].values".to_owned(),
+                16,
+                "Sequence length 4294967295 exceeds the 0 remaining byte(s)".to_owned()
+            )
+        );
+        assert_eq!(
+            message(&entries(b"truck\0", 0)),
+            (
+                "entries[0].name".to_owned(),
+                8,
+                "String of length 5 exceeds the bound of 4".to_owned()
+            )
+        );
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(0x1000_0000_u32.to_le_bytes());
+        assert_eq!(
+            message(&buffer),
+            (
+                "entries".to_owned(),
+                4,
+                "Sequence length 268435456 exceeds the 0 remaining byte(s)".to_owned()
+            )
+        );
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(0_u32.to_le_bytes());
+        buffer.extend(3_u32.to_le_bytes());
+        buffer.extend([0; 12]);
+        assert_eq!(
+            message(&buffer),
+            (
+                "bounded".to_owned(),
+                8,
+                "Sequence length 3 exceeds the bound of 2".to_owned()
+            )
+        );
+
+        // Each element of `entries` is at least 8 bytes long, but decodes to much more
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(1000_u32.to_le_bytes());
+        for _ in 0..1000 {
+            buffer.extend([1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        }
+        buffer.extend(0_u32.to_le_bytes());
+        assert!(decode(&spec, &buffer).is_ok());
+        let err = decode_with_limits(&spec, &buffer, DecodeLimits::new(16 * 1024)).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Decoded values would exceed the limit of 16384 byte(s) per message"));
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use anyhow::{bail, Error, Result};
+
+/// The encapsulation kind announced by the first two bytes of a serialized message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encapsulation {
+    CdrBe,
+    CdrLe,
+    PlCdrBe,
+    PlCdrLe,
+    Cdr2Be,
+    Cdr2Le,
+    DCdr2Be,
+    DCdr2Le,
+    PlCdr2Be,
+    PlCdr2Le,
+}
+
+impl Encapsulation {
+    /// Returns the encapsulation with the given identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The identifier, as read big-endian from the first two bytes of a message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the encapsulation or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the identifier is unknown.
+    pub fn from_id(id: u16) -> Result<Self, Error> {
+        Ok(match id {
+            0x0000 => Self::CdrBe,
+            0x0001 => Self::CdrLe,
+            0x0002 => Self::PlCdrBe,
+            0x0003 => Self::PlCdrLe,
+            0x0006 => Self::Cdr2Be,
+            0x0007 => Self::Cdr2Le,
+            0x0008 => Self::DCdr2Be,
+            0x0009 => Self::DCdr2Le,
+            0x000a => Self::PlCdr2Be,
+            0x000b => Self::PlCdr2Le,
+            _ => bail!("Unknown CDR encapsulation 0x{id:04x}"),
+        })
+    }
+
+    /// Returns the identifier of the encapsulation.
+    ///
+    /// # Returns
+    ///
+    /// * `u16` - The identifier, written big-endian in the first two bytes of a message.
+    pub fn id(self) -> u16 {
+        match self {
+            Self::CdrBe => 0x0000,
+            Self::CdrLe => 0x0001,
+            Self::PlCdrBe => 0x0002,
+            Self::PlCdrLe => 0x0003,
+            Self::Cdr2Be => 0x0006,
+            Self::Cdr2Le => 0x0007,
+            Self::DCdr2Be => 0x0008,
+            Self::DCdr2Le => 0x0009,
+            Self::PlCdr2Be => 0x000a,
+            Self::PlCdr2Le => 0x000b,
+        }
+    }
+
+    /// Returns whether the payload is little-endian.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the payload is little-endian, `false` if it is big-endian.
+    pub fn is_little_endian(self) -> bool {
+        self.id() & 1 == 1
+    }
+
+    /// Returns whether the payload follows the XCDR2 rules.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for XCDR2 encapsulations, `false` for XCDR1 ones.
+    pub fn is_xcdr2(self) -> bool {
+        self.id() >= 0x0006
+    }
+
+    /// Returns whether every structure is preceded by a DHEADER holding its size.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for delimited encapsulations, `false` otherwise.
+    pub fn is_delimited(self) -> bool {
+        matches!(self, Self::DCdr2Be | Self::DCdr2Le)
+    }
+
+    /// Returns whether the payload is a parameter list, as used for mutable types.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for parameter list encapsulations, `false` otherwise.
+    pub fn is_parameter_list(self) -> bool {
+        matches!(
+            self,
+            Self::PlCdrBe | Self::PlCdrLe | Self::PlCdr2Be | Self::PlCdr2Le
+        )
+    }
+
+    /// Returns the largest alignment applied to any value.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - 8 bytes for XCDR1, 4 bytes for XCDR2.
+    pub fn max_align(self) -> usize {
+        if self.is_xcdr2() {
+            4
+        } else {
+            8
+        }
+    }
+}
+
+impl fmt::Display for Encapsulation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::CdrBe => "CDR_BE",
+            Self::CdrLe => "CDR_LE",
+            Self::PlCdrBe => "PL_CDR_BE",
+            Self::PlCdrLe => "PL_CDR_LE",
+            Self::Cdr2Be => "CDR2_BE",
+            Self::Cdr2Le => "CDR2_LE",
+            Self::DCdr2Be => "D_CDR2_BE",
+            Self::DCdr2Le => "D_CDR2_LE",
+            Self::PlCdr2Be => "PL_CDR2_BE",
+            Self::PlCdr2Le => "PL_CDR2_LE",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The size of the characters of a `wstring`, which the encapsulation does not tell.
+///
+/// Fast-CDR 1, which serializes messages for Fast DDS up to ROS 2 Iron, writes 32-bit
+/// characters. Fast-CDR 2, from ROS 2 Jazzy on, writes 16-bit ones, the UTF-16 code units of
+/// the string. Either way, the length prefix counts characters rather than bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WcharSize {
+    /// 16-bit characters, holding UTF-16 code units.
+    Two,
+    /// 32-bit characters, holding Unicode scalar values.
+    #[default]
+    Four,
+}
+
+impl WcharSize {
+    /// Returns the size of a character.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The size of a character, in bytes.
+    pub fn bytes(self) -> usize {
+        match self {
+            Self::Two => 2,
+            Self::Four => 4,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids() {
+        for id in [0x0, 0x1, 0x2, 0x3, 0x6, 0x7, 0x8, 0x9, 0xa, 0xb] {
+            assert_eq!(Encapsulation::from_id(id).unwrap().id(), id);
+        }
+        assert!(Encapsulation::from_id(0x4).is_err());
+        assert!(Encapsulation::from_id(0x0100).is_err());
+
+        assert!(Encapsulation::DCdr2Le.is_little_endian());
+        assert!(Encapsulation::DCdr2Le.is_delimited());
+        assert!(!Encapsulation::Cdr2Be.is_little_endian());
+        assert_eq!(Encapsulation::Cdr2Be.max_align(), 4);
+        assert_eq!(Encapsulation::CdrBe.max_align(), 8);
+        assert!(Encapsulation::PlCdr2Le.is_parameter_list());
+        assert_eq!(Encapsulation::PlCdrLe.to_string(), "PL_CDR_LE");
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use crate::cdr::{CdrWriter, Encapsulation};
+use crate::ros_introspection::{primitive_layout, BuiltinType, Field, MsgSpec};
+use crate::value::Value;
+
+/// Encodes a generic `Value` into a little-endian CDR serialized message.
+///
+/// Fields of the value are matched to the fields of the specification by name, and numbers
+/// are converted to the type of their field when they fit.
+///
+/// # Arguments
+///
+/// * `spec` - A reference to the `MsgSpec` of the message type.
+/// * `value` - A reference to the message to encode.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - A result containing the serialized message, starting with its encapsulation header, or an error.
+///
+/// # Errors
+///
+/// This function will return an error, naming the field being encoded, if the value does not
+/// match the specification.
+pub fn encode(spec: &MsgSpec, value: &Value) -> Result<Vec<u8>, Error> {
+    encode_with_encapsulation(spec, value, Encapsulation::CdrLe)
+}
+
+/// Encodes a generic `Value` into a CDR serialized message with the given encapsulation.
+///
+/// # Arguments
+///
+/// * `spec` - A reference to the `MsgSpec` of the message type.
+/// * `value` - A reference to the message to encode.
+/// * `encapsulation` - The encapsulation of the serialized message.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>, Error>` - A result containing the serialized message, starting with its encapsulation header, or an error.
+///
+/// # Errors
+///
+/// This function will return an error if the encapsulation is a parameter list, or, naming the
+/// field being encoded, if the value does not match the specification.
+pub fn encode_with_encapsulation(
+    spec: &MsgSpec,
+    value: &Value,
+    encapsulation: Encapsulation,
+) -> Result<Vec<u8>, Error> {
+    let mut writer = CdrWriter::new(encapsulation)?;
+    encode_message(&mut writer, spec, value)?;
+    Ok(writer.finish())
+}
+
+/// Encodes a message at the current position of a writer.
+///
+/// # Errors
+///
+/// This function will return an error, naming the field being encoded, if the value does not
+/// match the specification.
+pub fn encode_message(writer: &mut CdrWriter, spec: &MsgSpec, value: &Value) -> Result<(), Error> {
+    let Value::Message(fields) = value else {
+        bail!(
+            "Expected a message of type {}, found {}",
+            spec.data().type_().name(),
+            value.kind()
+        );
+    };
+
+    let header = writer.begin_struct();
+    for (field, child) in spec.serialized_fields() {
+        let value = value
+            .field(field.name())
+            .ok_or_else(|| anyhow!("Missing field {}", field.name()))?;
+        encode_field(writer, field, child, value)
+            .with_context(|| format!("Could not encode field {}", field.name()))?;
+    }
+
+    if let Some((name, _)) = fields.iter().find(|(name, _)| {
+        !spec
+            .serialized_fields()
+            .any(|(field, _)| field.name() == name)
+    }) {
+        bail!(
+            "Unknown field {name} for type {}",
+            spec.data().type_().name()
+        );
+    }
+
+    if spec.is_empty_struct() {
+        writer.write_empty_struct();
+    }
+    writer.end_header(header)
+}
+
+fn encode_field(
+    writer: &mut CdrWriter,
+    field: &Field,
+    child: Option<&MsgSpec>,
+    value: &Value,
+) -> Result<(), Error> {
+    if !field.is_array() {
+        return encode_single(writer, field, child, value);
+    }
+
+    let count = match value {
+        Value::Bytes(bytes) => bytes.len(),
+        _ => value.as_array()?.len(),
+    };
+
+    // Only arrays of non-primitive elements are delimited in XCDR2
+    let header = primitive_layout(field.type_().id())
+        .is_none()
+        .then(|| writer.begin_collection());
+    match usize::try_from(field.array_size()) {
+        Ok(size) if size != count => bail!("Expected {size} element(s), found {count}"),
+        Ok(_) => {}
+        Err(_) => {
+            if let Some(bound) = field.array_bound().filter(|bound| count > *bound) {
+                bail!("{count} element(s) exceed the bound of {bound}");
+            }
+            writer.write_length(count)?;
+        }
+    }
+
+    match value {
+        Value::Bytes(bytes) => {
+            if !matches!(
+                field.type_().id(),
+                BuiltinType::Uint8 | BuiltinType::Byte | BuiltinType::Char
+            ) {
+                bail!("Expected an array of {}, found bytes", field.type_().name());
+            }
+            writer.write_bytes(bytes);
+        }
+        _ => {
+            for (index, element) in value.as_array()?.iter().enumerate() {
+                encode_single(writer, field, child, element)
+                    .with_context(|| format!("Could not encode element {index}"))?;
+            }
+        }
+    }
+
+    match header {
+        Some(header) => writer.end_header(header),
+        None => Ok(()),
+    }
+}
+
+/// Converts an integer value into the integer type of a field.
+fn integer<T: TryFrom<i64>>(value: &Value, type_name: &str) -> Result<T, Error> {
+    let integer = value.as_i64()?;
+    T::try_from(integer).map_err(|_err| anyhow!("{integer} is out of range for {type_name}"))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_single(
+    writer: &mut CdrWriter,
+    field: &Field,
+    child: Option<&MsgSpec>,
+    value: &Value,
+) -> Result<(), Error> {
+    let type_name = field.type_().name();
+
+    match field.type_().id() {
+        BuiltinType::Bool => writer.write_u8(u8::from(value.as_bool()?)),
+        BuiltinType::Byte | BuiltinType::Char | BuiltinType::Uint8 => {
+            writer.write_u8(integer(value, type_name)?);
+        }
+        BuiltinType::Int8 => writer.write_i8(integer(value, type_name)?),
+        BuiltinType::Uint16 => writer.write_u16(integer(value, type_name)?),
+        BuiltinType::Int16 => writer.write_i16(integer(value, type_name)?),
+        BuiltinType::Uint32 => writer.write_u32(integer(value, type_name)?),
+        BuiltinType::Int32 => writer.write_i32(integer(value, type_name)?),
+        BuiltinType::Uint64 => writer.write_u64(value.as_u64()?),
+        BuiltinType::Int64 => writer.write_i64(value.as_i64()?),
+        BuiltinType::Float32 => writer.write_f32(value.as_f64()? as f32),
+        BuiltinType::Float64 => writer.write_f64(value.as_f64()?),
+        id @ (BuiltinType::String | BuiltinType::WString) => {
+            let string = value.as_str()?;
+            let len = if id == &BuiltinType::String {
+                string.len()
+            } else {
+                string.chars().count()
+            };
+            if let Some(bound) = field.string_bound().filter(|bound| len > *bound) {
+                bail!("String of length {len} exceeds the bound of {bound}");
+            }
+
+            if id == &BuiltinType::String {
+                writer.write_string(string)?;
+            } else {
+                writer.write_wstring(string)?;
+            }
+        }
+        BuiltinType::Time => {
+            writer.write_u32(
+                value
+                    .get_u64("sec")
+                    .and_then(|sec| Ok(u32::try_from(sec)?))?,
+            );
+            writer.write_u32(
+                value
+                    .get_u64("nsec")
+                    .and_then(|nsec| Ok(u32::try_from(nsec)?))?,
+            );
+        }
+        BuiltinType::Duration => {
+            writer.write_i32(
+                value
+                    .get_i64("sec")
+                    .and_then(|sec| Ok(i32::try_from(sec)?))?,
+            );
+            writer.write_i32(
+                value
+                    .get_i64("nsec")
+                    .and_then(|nsec| Ok(i32::try_from(nsec)?))?,
+            );
+        }
+        BuiltinType::Other => {
+            let child = child.ok_or_else(|| anyhow!("Missing specification of {type_name}"))?;
+            encode_message(writer, child, value)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdr::{decode, DecodePlan, MessageView, Projection};
+    use crate::ros_introspection::Dialect;
+
+    fn spec() -> MsgSpec {
+        MsgSpec::new_from_definitions(
+            "test_msgs/Test",
+            r#"
+            std_msgs/Header header
+            uint8[] data
+            float64[] empty_values
+            bool flag
+            test_msgs/Entry[<=3] entries
+            wstring<=5 label
+            std_msgs/Empty nothing
+            float32[2] pair
+            ================================================================================
+            MSG: std_msgs/Header
+            builtin_interfaces/Time stamp
+            string frame_id
+            ================================================================================
+            MSG: builtin_interfaces/Time
+            int32 sec
+            uint32 nanosec
+            ================================================================================
+            MSG: test_msgs/Entry
+            int8 small
+            uint64 large
+            ================================================================================
+            MSG: std_msgs/Empty
+            "#,
+            Dialect::Ros2,
+        )
+        .unwrap()
+    }
+
+    fn message(fields: Vec<(&str, Value)>) -> Value {
+        Value::Message(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    fn value() -> Value {
+        message(vec![
+            (
+                "header",
+                message(vec![
+                    (
+                        "stamp",
+                        message(vec![
+                            ("sec", Value::Int32(-5)),
+                            ("nanosec", Value::Uint32(7)),
+                        ]),
+                    ),
+                    ("frame_id", Value::String("map".to_owned())),
+                ]),
+            ),
+            ("data", Value::Bytes(vec![1, 2, 3])),
+            ("empty_values", Value::Array(Vec::new())),
+            ("flag", Value::Bool(true)),
+            (
+                "entries",
+                Value::Array(vec![message(vec![
+                    ("small", Value::Int8(-1)),
+                    ("large", Value::Uint64(u64::MAX)),
+                ])]),
+            ),
+            ("label", Value::String("héllo".to_owned())),
+            ("nothing", message(Vec::new())),
+            (
+                "pair",
+                Value::Array(vec![Value::Float32(0.5), Value::Float32(-0.5)]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let spec = spec();
+        let value = value();
+
+        let buffer = encode(&spec, &value).unwrap();
+        assert_eq!(&buffer[..4], [0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(decode(&spec, &buffer).unwrap(), value);
+    }
+
+    #[test]
+    fn test_roundtrip_encapsulations() {
+        let spec = spec();
+        let value = value();
+        let plan = DecodePlan::new(&spec).unwrap();
+        let projection = Projection::new(&spec, &["entries.large", "nothing", "pair"]).unwrap();
+
+        for encapsulation in [
+            Encapsulation::CdrBe,
+            Encapsulation::CdrLe,
+            Encapsulation::Cdr2Be,
+            Encapsulation::Cdr2Le,
+            Encapsulation::DCdr2Be,
+            Encapsulation::DCdr2Le,
+        ] {
+            let buffer = encode_with_encapsulation(&spec, &value, encapsulation).unwrap();
+            assert_eq!(&buffer[..2], encapsulation.id().to_be_bytes());
+
+            assert_eq!(decode(&spec, &buffer).unwrap(), value, "{encapsulation}");
+            assert_eq!(plan.decode(&buffer).unwrap(), value, "{encapsulation}");
+
+            let view = MessageView::new(&spec, &buffer).unwrap();
+            assert_eq!(view.decode().unwrap(), value, "{encapsulation}");
+            assert_eq!(
+                *view.field("pair").unwrap().f32_slice().unwrap(),
+                [0.5, -0.5],
+                "{encapsulation}"
+            );
+
+            let projected = projection.decode(&spec, &buffer).unwrap();
+            assert_eq!(
+                projected.get_u64("entries[0].large").unwrap(),
+                u64::MAX,
+                "{encapsulation}"
+            );
+            assert_eq!(projected.get_array("pair").unwrap().len(), 2);
+        }
+
+        assert!(encode_with_encapsulation(&spec, &value, Encapsulation::PlCdrLe).is_err());
+    }
+
+    #[test]
+    fn test_layout() {
+        let spec = MsgSpec::new_from_definitions(
+            "test_msgs/Test",
+            "uint8 a\nfloat64 b\nstring c\nuint16[] d",
+            Dialect::Ros2,
+        )
+        .unwrap();
+        let value = message(vec![
+            ("a", Value::Uint8(1)),
+            ("b", Value::Float64(2.0)),
+            ("c", Value::String("x".to_owned())),
+            ("d", Value::Array(vec![Value::Uint16(3)])),
+        ]);
+
+        let mut expected = vec![0x00, 0x01, 0x00, 0x00, 1, 0, 0, 0, 0, 0, 0, 0];
+        expected.extend(2.0_f64.to_le_bytes());
+        expected.extend(2_u32.to_le_bytes());
+        expected.extend(b"x\0");
+        expected.extend([0, 0]); // padding to 4
+        expected.extend(1_u32.to_le_bytes());
+        expected.extend(3_u16.to_le_bytes());
+        assert_eq!(encode(&spec, &value).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_coercion() {
+        let spec = MsgSpec::new_from_definitions(
+            "test_msgs/Test",
+            "int16 a\nfloat32 b\nuint8[] c",
+            Dialect::Ros2,
+        )
+        .unwrap();
+        let value = message(vec![
+            ("a", Value::Int64(-300)),
+            ("b", Value::Int32(2)),
+            ("c", Value::Array(vec![Value::Int32(255)])),
+        ]);
+
+        let decoded = decode(&spec, &encode(&spec, &value).unwrap()).unwrap();
+        assert_eq!(decoded.get("a").unwrap(), &Value::Int16(-300));
+        assert_eq!(decoded.get("b").unwrap(), &Value::Float32(2.0));
+        assert_eq!(decoded.get("c").unwrap(), &Value::Bytes(vec![255]));
+    }
+
+    #[test]
+    fn test_errors() {
+        let spec = spec();
+        let error = |path: &str, replacement: Option<Value>| {
+            let mut value = value();
+            let Value::Message(fields) = &mut value else {
+                unreachable!()
+            };
+            match replacement {
+                Some(replacement) => {
+                    fields.iter_mut().find(|(name, _)| name == path).unwrap().1 = replacement;
+                }
+                None => fields.retain(|(name, _)| name != path),
+            }
+            format!("{:#}", encode(&spec, &value).unwrap_err())
+        };
+
+        assert_eq!(error("flag", None), "Missing field flag");
+        assert_eq!(
+            error("pair", Some(Value::Array(vec![Value::Float32(1.0)]))),
+            "Could not encode field pair: Expected 2 element(s), found 1"
+        );
+        assert_eq!(
+            error("entries", Some(Value::Array(vec![message(Vec::new()); 4]))),
+            "Could not encode field entries: 4 element(s) exceed the bound of 3"
+        );
+        assert_eq!(
+            error("label", Some(Value::String("too long".to_owned()))),
+            "Could not encode field label: String of length 8 exceeds the bound of 5"
+        );
+        assert_eq!(
+            error("data", Some(Value::Array(vec![Value::Int32(256)]))),
+            "Could not encode field data: Could not encode element 0: 256 is out of range for uint8"
+        );
+
+        let mut value = value();
+        if let Value::Message(fields) = &mut value {
+            fields.push(("extra".to_owned(), Value::Bool(false)));
+        }
+        assert_eq!(
+            encode(&spec, &value).unwrap_err().to_string(),
+            "Unknown field extra for type test_msgs/Test"
+        );
+    }
+}
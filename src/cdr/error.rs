@@ -0,0 +1,147 @@
+use std::fmt;
+
+use anyhow::Error;
+
+/// An error raised while decoding a CDR serialized message, located by the path of the field
+/// being decoded and its byte offset.
+///
+/// Messages may come from untrusted sources, such as bags recorded by third parties, so every
+/// malformed input is reported through this error rather than a panic or an unbounded allocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    path: String,
+    offset: usize,
+    message: String,
+}
+
+impl DecodeError {
+    /// Creates a new `DecodeError` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path of the field, such as `poses[2].position.x`,
+    ///   empty for the message itself.
+    /// * `offset` - The offset where the field starts, before any alignment padding, from the
+    ///   start of the serialized message, including its encapsulation header.
+    /// * `message` - A string slice that describes what went wrong.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `DecodeError` instance.
+    pub fn new(path: &str, offset: usize, message: &str) -> Self {
+        Self {
+            path: path.to_owned(),
+            offset,
+            message: message.to_owned(),
+        }
+    }
+
+    /// Returns the path of the field that could not be decoded.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the path, empty for the message itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the offset where the field that could not be decoded starts.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The offset from the start of the serialized message, including its
+    ///   encapsulation header, before any alignment padding of the field.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the description of what went wrong.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the description.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Prepends a field name or an `[index]` to the path.
+    fn within(mut self, segment: &str) -> Self {
+        self.path = if segment.is_empty() {
+            self.path
+        } else if self.path.is_empty() {
+            segment.to_owned()
+        } else if self.path.starts_with('[') {
+            format!("{segment}{}", self.path)
+        } else {
+            format!("{segment}.{}", self.path)
+        };
+        self
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Could not decode message")?;
+        } else {
+            write!(f, "Could not decode `{}`", self.path)?;
+        }
+        write!(f, " at byte offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Attributes an error to a field, or to an element of an array, when unwinding out of it.
+///
+/// An error that is not a `DecodeError` yet was raised by the field itself, and is located at
+/// `offset`. Otherwise, it was raised by a nested field, whose path gets prefixed with `segment`.
+///
+/// # Arguments
+///
+/// * `err` - The error to attribute.
+/// * `segment` - A string slice that holds the name of the field, or an `[index]`. It may
+///   span several fields, such as `header.stamp`, or be empty for the message itself.
+/// * `offset` - The offset where the field starts, from the start of the serialized message.
+///
+/// # Returns
+///
+/// * `Error` - The attributed error, which downcasts to a `DecodeError`.
+pub(crate) fn locate(err: Error, segment: &str, offset: usize) -> Error {
+    match err.downcast::<DecodeError>() {
+        Ok(err) => err.within(segment).into(),
+        Err(err) => DecodeError::new(segment, offset, &format!("{err:#}")).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_locate() {
+        let err = locate(anyhow!("String length 9 exceeds the bound of 4"), "", 12);
+        let err = locate(err, "[2]", 8);
+        let err = locate(err, "poses", 4);
+        let err = locate(err, "", 0);
+
+        let decode_error = err.downcast_ref::<DecodeError>().unwrap();
+        assert_eq!(decode_error.path(), "poses[2]");
+        assert_eq!(decode_error.offset(), 12);
+        assert_eq!(
+            err.to_string(),
+            "Could not decode `poses[2]` at byte offset 12: String length 9 exceeds the bound of 4"
+        );
+
+        let err = locate(locate(anyhow!("oops"), "frame_id", 4), "header", 4);
+        assert_eq!(
+            err.to_string(),
+            "Could not decode `header.frame_id` at byte offset 4: oops"
+        );
+        assert_eq!(
+            locate(anyhow!("oops"), "", 4).to_string(),
+            "Could not decode message at byte offset 4: oops"
+        );
+    }
+}
@@ -0,0 +1,21 @@
+//! Decoding and encoding of CDR serialized ROS 2 messages, driven by their `MsgSpec`, either
+//! through generic values or through borrowed views over the serialized buffer.
+pub mod decoder;
+pub mod encapsulation;
+pub mod encoder;
+pub mod error;
+pub mod plan;
+pub mod projection;
+pub mod reader;
+pub mod view;
+pub mod writer;
+
+pub use decoder::*;
+pub use encapsulation::*;
+pub use encoder::*;
+pub use error::*;
+pub use plan::*;
+pub use projection::*;
+pub use reader::*;
+pub use view::*;
+pub use writer::*;
@@ -0,0 +1,803 @@
+use std::mem::size_of;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::cdr::{locate, read_checked_count, CdrReader, DecodeLimits, Encapsulation};
+use crate::ros_introspection::{BuiltinType, Field, MsgSpec, EMPTY_STRUCT_SIZE};
+use crate::value::Value;
+
+/// A `MsgSpec` compiled into a flat list of decoding steps.
+///
+/// Compiling a plan walks the specification once, so that decoding a message no longer has to.
+/// Consecutive fixed-size values are collapsed into a single step, which reads them with one
+/// bounds check at offsets computed ahead of time.
+///
+/// As XCDR2 aligns and delimits values differently, the steps are compiled once for each
+/// supported family of encapsulations.
+///
+/// Decoding enforces the same checks as [`decode`](crate::cdr::decode), except that the
+/// error of a collapsed step names its first field.
+#[derive(Debug, Clone)]
+pub struct DecodePlan {
+    steps: [Vec<Step>; Flavor::COUNT],
+}
+
+/// A family of encapsulations that share the same layout, regardless of byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavor {
+    Xcdr1,
+    Xcdr2,
+    DelimitedXcdr2,
+}
+
+impl Flavor {
+    const COUNT: usize = 3;
+    const ALL: [Self; Self::COUNT] = [Self::Xcdr1, Self::Xcdr2, Self::DelimitedXcdr2];
+
+    fn new(encapsulation: Encapsulation) -> Self {
+        if encapsulation.is_delimited() {
+            Self::DelimitedXcdr2
+        } else if encapsulation.is_xcdr2() {
+            Self::Xcdr2
+        } else {
+            Self::Xcdr1
+        }
+    }
+
+    fn max_align(self) -> usize {
+        match self {
+            Self::Xcdr1 => 8,
+            Self::Xcdr2 | Self::DelimitedXcdr2 => 4,
+        }
+    }
+}
+
+/// A decoding step, along with the path of the field it decodes, relative to the innermost
+/// array element it belongs to.
+#[derive(Debug, Clone)]
+struct Step {
+    op: Op,
+    path: String,
+}
+
+/// A single decoding operation.
+#[derive(Debug, Clone)]
+enum Op {
+    /// Reads `size` bytes from an offset aligned to `align`, and decodes the items from them.
+    Fixed {
+        align: usize,
+        size: usize,
+        items: Vec<Item>,
+    },
+
+    /// Reads a `string`, checking its length against an optional bound.
+    String { bound: Option<usize> },
+
+    /// Reads a `wstring`, checking its length against an optional bound.
+    WString { bound: Option<usize> },
+
+    /// Reads an array of primitives, with a length prefix unless the field has a fixed size.
+    Primitives {
+        primitive: Primitive,
+        field: Arc<Field>,
+    },
+
+    /// Runs the `body` steps that follow once per element, with a length prefix unless the
+    /// field has a fixed size, and collects the results into an array.
+    ///
+    /// With `header`, the elements are preceded by a DHEADER.
+    Loop {
+        field: Arc<Field>,
+        body: usize,
+        header: bool,
+    },
+
+    /// Builds a message from the last decoded values.
+    Message { names: Arc<Names> },
+}
+
+/// A value decoded by an [`Op::Fixed`] step.
+#[derive(Debug, Clone)]
+enum Item {
+    /// A primitive at an offset of the step.
+    Read { offset: usize, primitive: Primitive },
+
+    /// A fixed-size array of primitives at an offset of the step.
+    Array {
+        offset: usize,
+        primitive: Primitive,
+        count: usize,
+    },
+
+    /// A message built from the last decoded values.
+    Message { names: Arc<Names> },
+}
+
+/// The field names of a message built by a step.
+#[derive(Debug)]
+struct Names {
+    names: Vec<String>,
+    /// The number of bytes allocated to build the message.
+    cost: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Primitive {
+    Bool,
+    Uint8,
+    Int8,
+    Uint16,
+    Int16,
+    Uint32,
+    Int32,
+    Uint64,
+    Int64,
+    Float32,
+    Float64,
+}
+
+impl Primitive {
+    fn new(id: &BuiltinType) -> Option<Self> {
+        Some(match id {
+            BuiltinType::Bool => Self::Bool,
+            BuiltinType::Byte | BuiltinType::Char | BuiltinType::Uint8 => Self::Uint8,
+            BuiltinType::Int8 => Self::Int8,
+            BuiltinType::Uint16 => Self::Uint16,
+            BuiltinType::Int16 => Self::Int16,
+            BuiltinType::Uint32 => Self::Uint32,
+            BuiltinType::Int32 => Self::Int32,
+            BuiltinType::Uint64 => Self::Uint64,
+            BuiltinType::Int64 => Self::Int64,
+            BuiltinType::Float32 => Self::Float32,
+            BuiltinType::Float64 => Self::Float64,
+            _ => return None,
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Self::Bool | Self::Uint8 | Self::Int8 => 1,
+            Self::Uint16 | Self::Int16 => 2,
+            Self::Uint32 | Self::Int32 | Self::Float32 => 4,
+            Self::Uint64 | Self::Int64 | Self::Float64 => 8,
+        }
+    }
+
+    /// Decodes a single value from the start of `bytes`, which must be long enough.
+    fn decode(self, bytes: &[u8], little_endian: bool) -> Value {
+        macro_rules! read {
+            ($ty:ty) => {{
+                let mut array = [0; std::mem::size_of::<$ty>()];
+                array.copy_from_slice(&bytes[..std::mem::size_of::<$ty>()]);
+                if little_endian {
+                    <$ty>::from_le_bytes(array)
+                } else {
+                    <$ty>::from_be_bytes(array)
+                }
+            }};
+        }
+
+        match self {
+            Self::Bool => Value::Bool(bytes[0] != 0),
+            Self::Uint8 => Value::Uint8(bytes[0]),
+            Self::Int8 => Value::Int8(read!(i8)),
+            Self::Uint16 => Value::Uint16(read!(u16)),
+            Self::Int16 => Value::Int16(read!(i16)),
+            Self::Uint32 => Value::Uint32(read!(u32)),
+            Self::Int32 => Value::Int32(read!(i32)),
+            Self::Uint64 => Value::Uint64(read!(u64)),
+            Self::Int64 => Value::Int64(read!(i64)),
+            Self::Float32 => Value::Float32(read!(f32)),
+            Self::Float64 => Value::Float64(read!(f64)),
+        }
+    }
+
+    /// Returns the number of bytes allocated to decode an array of `count` elements.
+    fn array_cost(self, count: usize) -> usize {
+        if self == Self::Uint8 {
+            count
+        } else {
+            count.saturating_mul(size_of::<Value>())
+        }
+    }
+
+    /// Decodes an array from `bytes`, which must hold exactly the elements.
+    fn decode_array(self, bytes: &[u8], little_endian: bool) -> Value {
+        if self == Self::Uint8 {
+            return Value::Bytes(bytes.to_vec());
+        }
+        Value::Array(
+            bytes
+                .chunks_exact(self.size())
+                .map(|chunk| self.decode(chunk, little_endian))
+                .collect(),
+        )
+    }
+}
+
+impl DecodePlan {
+    /// Compiles the decoding plan of a message type.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` of the message type.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `DecodePlan` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the specification of a nested type is missing.
+    pub fn new(spec: &MsgSpec) -> Result<Self, Error> {
+        let mut steps: [Vec<Step>; Flavor::COUNT] = Default::default();
+        for (steps, flavor) in steps.iter_mut().zip(Flavor::ALL) {
+            let mut compiler = Compiler::new(flavor);
+            compiler.message(spec)?;
+            compiler.flush();
+            *steps = compiler.steps;
+        }
+        Ok(Self { steps })
+    }
+
+    /// Returns the number of steps of the plan for an encapsulation.
+    ///
+    /// # Arguments
+    ///
+    /// * `encapsulation` - The encapsulation of the messages to decode.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of steps.
+    pub fn step_count(&self, encapsulation: Encapsulation) -> usize {
+        self.steps[Flavor::new(encapsulation) as usize].len()
+    }
+
+    /// Decodes a CDR serialized message into a generic `Value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Value, Error>` - A result containing the decoded message or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`DecodeError`](crate::cdr::DecodeError), naming the field
+    /// being decoded and its offset, if the buffer does not hold a message of the planned type.
+    pub fn decode(&self, buffer: &[u8]) -> Result<Value, Error> {
+        self.decode_with_limits(buffer, DecodeLimits::default())
+    }
+
+    /// Decodes a CDR serialized message into a generic `Value`, enforcing custom limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    /// * `limits` - The limits to enforce while decoding the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Value, Error>` - A result containing the decoded message or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a [`DecodeError`](crate::cdr::DecodeError), naming the field
+    /// being decoded and its offset, if the buffer does not hold a message of the planned type
+    /// or if decoding it would exceed the limits.
+    pub fn decode_with_limits(&self, buffer: &[u8], limits: DecodeLimits) -> Result<Value, Error> {
+        let mut reader = CdrReader::with_limits(buffer, limits)?;
+        let steps = &self.steps[Flavor::new(reader.encapsulation()) as usize];
+        let mut stack = Vec::new();
+        run(steps, &mut reader, &mut stack)?;
+        stack
+            .pop()
+            .ok_or_else(|| anyhow!("Decoding plan produced no value"))
+    }
+}
+
+/// Runs decoding steps, pushing the values they produce onto the stack.
+fn run(steps: &[Step], reader: &mut CdrReader<'_>, stack: &mut Vec<Value>) -> Result<(), Error> {
+    let mut index = 0;
+    while index < steps.len() {
+        let position = reader.position();
+        index = run_step(steps, index, reader, stack)
+            .map_err(|err| locate(err, &steps[index].path, position))?;
+    }
+    Ok(())
+}
+
+/// Runs a single decoding step, returning the index of the next one.
+fn run_step(
+    steps: &[Step],
+    index: usize,
+    reader: &mut CdrReader<'_>,
+    stack: &mut Vec<Value>,
+) -> Result<usize, Error> {
+    let little_endian = reader.is_little_endian();
+
+    match &steps[index].op {
+        Op::Fixed { align, size, items } => {
+            reader.align(*align)?;
+            let bytes = reader.read_bytes(*size)?;
+            for item in items {
+                match item {
+                    Item::Read { offset, primitive } => {
+                        stack.push(primitive.decode(&bytes[*offset..], little_endian));
+                    }
+                    Item::Array {
+                        offset,
+                        primitive,
+                        count,
+                    } => {
+                        reader.allocate(primitive.array_cost(*count))?;
+                        let end = offset + primitive.size() * count;
+                        stack.push(primitive.decode_array(&bytes[*offset..end], little_endian));
+                    }
+                    Item::Message { names } => build_message(names, reader, stack)?,
+                }
+            }
+        }
+        Op::String { bound } => {
+            let string = reader.read_string()?;
+            check_bound(string.len(), *bound)?;
+            stack.push(Value::String(string));
+        }
+        Op::WString { bound } => {
+            let string = reader.read_wstring()?;
+            check_bound(string.chars().count(), *bound)?;
+            stack.push(Value::String(string));
+        }
+        Op::Primitives { primitive, field } => {
+            let count = read_checked_count(reader, field)?;
+            reader.allocate(primitive.array_cost(count))?;
+            // Like Fast-CDR, empty arrays are not padded
+            if count > 0 {
+                reader.align(primitive.size())?;
+            }
+            let bytes = reader.read_bytes(count * primitive.size())?;
+            stack.push(primitive.decode_array(bytes, little_endian));
+        }
+        Op::Loop {
+            field,
+            body,
+            header,
+        } => {
+            if *header {
+                reader.read_length()?;
+            }
+            let count = read_checked_count(reader, field)?;
+            reader.allocate(count.saturating_mul(size_of::<Value>()))?;
+            let body_range = index + 1..index + 1 + body;
+
+            let mut elements = Vec::with_capacity(count);
+            for element in 0..count {
+                let position = reader.position();
+                run(&steps[body_range.clone()], reader, stack)
+                    .map_err(|err| locate(err, &format!("[{element}]"), position))?;
+                elements.push(
+                    stack
+                        .pop()
+                        .ok_or_else(|| anyhow!("Decoding plan produced no element"))?,
+                );
+            }
+            stack.push(Value::Array(elements));
+            return Ok(body_range.end);
+        }
+        Op::Message { names } => build_message(names, reader, stack)?,
+    }
+
+    Ok(index + 1)
+}
+
+/// Checks the length of a decoded string, in bytes or wide characters, against its bound.
+fn check_bound(len: usize, bound: Option<usize>) -> Result<(), Error> {
+    if let Some(bound) = bound.filter(|bound| len > *bound) {
+        bail!("String of length {len} exceeds the bound of {bound}");
+    }
+    Ok(())
+}
+
+/// Replaces the last decoded values with a message made of them.
+fn build_message(
+    names: &Names,
+    reader: &mut CdrReader<'_>,
+    stack: &mut Vec<Value>,
+) -> Result<(), Error> {
+    reader.allocate(names.cost)?;
+    let start = stack
+        .len()
+        .checked_sub(names.names.len())
+        .ok_or_else(|| anyhow!("Decoding plan is missing message fields"))?;
+    let fields = names
+        .names
+        .iter()
+        .cloned()
+        .zip(stack.drain(start..))
+        .collect();
+    stack.push(Value::Message(fields));
+    Ok(())
+}
+
+/// A run of fixed-size values being collapsed into a single step.
+#[derive(Debug)]
+struct Run {
+    align: usize,
+    size: usize,
+    items: Vec<Item>,
+    path: String,
+}
+
+#[derive(Debug)]
+struct Compiler {
+    flavor: Flavor,
+    steps: Vec<Step>,
+    run: Option<Run>,
+    /// The field names leading to the current field, from the innermost array element.
+    path: Vec<String>,
+}
+
+impl Compiler {
+    fn new(flavor: Flavor) -> Self {
+        Self {
+            flavor,
+            steps: Vec::new(),
+            run: None,
+            path: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, op: Op) {
+        self.steps.push(Step {
+            op,
+            path: self.path.join("."),
+        });
+    }
+
+    /// Ends the current run of fixed-size values, if any.
+    fn flush(&mut self) {
+        if let Some(run) = self.run.take() {
+            self.steps.push(Step {
+                op: Op::Fixed {
+                    align: run.align,
+                    size: run.size,
+                    items: run.items,
+                },
+                path: run.path,
+            });
+        }
+    }
+
+    /// Reserves room for a fixed-size value, returning its offset in the current run.
+    ///
+    /// A value can only join the current run if its alignment does not exceed the alignment
+    /// of the run's start, as its padding would otherwise depend on where the run starts.
+    fn reserve(&mut self, size: usize, align: usize) -> usize {
+        let align = align.min(self.flavor.max_align());
+        if self.run.as_ref().is_some_and(|run| align > run.align) {
+            self.flush();
+        }
+        let path = &self.path;
+        let run = self.run.get_or_insert_with(|| Run {
+            align,
+            size: 0,
+            items: Vec::new(),
+            path: path.join("."),
+        });
+
+        let offset = run.size.next_multiple_of(align);
+        run.size = offset + size;
+        offset
+    }
+
+    fn push_item(&mut self, item: Item) {
+        if let Some(run) = self.run.as_mut() {
+            run.items.push(item);
+        }
+    }
+
+    fn build_message(&mut self, names: Vec<String>) {
+        let cost = names
+            .iter()
+            .map(|name| size_of::<(String, Value)>() + name.len())
+            .sum();
+        let names = Arc::new(Names { names, cost });
+        match self.run.as_mut() {
+            Some(run) => run.items.push(Item::Message { names }),
+            None => self.push(Op::Message { names }),
+        }
+    }
+
+    fn message(&mut self, spec: &MsgSpec) -> Result<(), Error> {
+        if self.flavor == Flavor::DelimitedXcdr2 {
+            // The DHEADER is not needed when decoding every field
+            self.reserve(4, 4);
+        }
+
+        let mut names = Vec::new();
+        for (field, child) in spec.serialized_fields() {
+            self.path.push(field.name().to_owned());
+            self.field(field, child)?;
+            self.path.pop();
+            names.push(field.name().to_owned());
+        }
+
+        if spec.is_empty_struct() {
+            self.reserve(EMPTY_STRUCT_SIZE, 1);
+        }
+        self.build_message(names);
+        Ok(())
+    }
+
+    fn field(&mut self, field: &Field, child: Option<&MsgSpec>) -> Result<(), Error> {
+        if !field.is_array() {
+            return self.single(field, child);
+        }
+
+        let count = usize::try_from(field.array_size()).ok();
+        match (Primitive::new(field.type_().id()), count) {
+            (Some(primitive), Some(count)) => {
+                // Like sequences, empty arrays are not aligned
+                let align = if count == 0 { 1 } else { primitive.size() };
+                let offset = self.reserve(primitive.size() * count, align);
+                self.push_item(Item::Array {
+                    offset,
+                    primitive,
+                    count,
+                });
+            }
+            (Some(primitive), None) => {
+                self.flush();
+                self.push(Op::Primitives {
+                    primitive,
+                    field: Arc::new(field.clone()),
+                });
+            }
+            (None, _) => {
+                self.flush();
+                let start = self.steps.len();
+                self.push(Op::Loop {
+                    field: Arc::new(field.clone()),
+                    body: 0,
+                    header: self.flavor != Flavor::Xcdr1,
+                });
+
+                // Paths within the body are relative to the element
+                let outer = std::mem::take(&mut self.path);
+                self.single(field, child)?;
+                self.flush();
+                self.path = outer;
+
+                let body_len = self.steps.len() - start - 1;
+                if let Op::Loop { body, .. } = &mut self.steps[start].op {
+                    *body = body_len;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn single(&mut self, field: &Field, child: Option<&MsgSpec>) -> Result<(), Error> {
+        let id = field.type_().id();
+        if let Some(primitive) = Primitive::new(id) {
+            let offset = self.reserve(primitive.size(), primitive.size());
+            self.push_item(Item::Read { offset, primitive });
+            return Ok(());
+        }
+
+        match id {
+            BuiltinType::Time | BuiltinType::Duration => {
+                let primitive = if id == &BuiltinType::Time {
+                    Primitive::Uint32
+                } else {
+                    Primitive::Int32
+                };
+                for _ in 0..2 {
+                    let offset = self.reserve(4, 4);
+                    self.push_item(Item::Read { offset, primitive });
+                }
+                self.build_message(vec!["sec".to_owned(), "nsec".to_owned()]);
+            }
+            BuiltinType::String => {
+                self.flush();
+                self.push(Op::String {
+                    bound: field.string_bound(),
+                });
+            }
+            BuiltinType::WString => {
+                self.flush();
+                self.push(Op::WString {
+                    bound: field.string_bound(),
+                });
+            }
+            _ => {
+                let child = child
+                    .ok_or_else(|| anyhow!("Missing specification of {}", field.type_().name()))?;
+                self.message(child)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdr::decode;
+    use crate::ros_introspection::Dialect;
+
+    fn spec(definitions: &str) -> MsgSpec {
+        MsgSpec::new_from_definitions("test_msgs/Test", definitions, Dialect::Ros2).unwrap()
+    }
+
+    #[test]
+    fn test_fixed_fields_collapse() {
+        let spec = spec(
+            r#"
+            geometry_msgs/Pose pose
+            float64[36] covariance
+            ================================================================================
+            MSG: geometry_msgs/Pose
+            geometry_msgs/Point position
+            geometry_msgs/Quaternion orientation
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+            ================================================================================
+            MSG: geometry_msgs/Quaternion
+            float64 x
+            float64 y
+            float64 z
+            float64 w
+            "#,
+        );
+
+        let plan = DecodePlan::new(&spec).unwrap();
+        assert_eq!(plan.step_count(Encapsulation::CdrLe), 1);
+        assert_eq!(plan.step_count(Encapsulation::Cdr2Le), 1);
+        assert_eq!(plan.step_count(Encapsulation::DCdr2Le), 1);
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        for index in 0..43_u32 {
+            buffer.extend(f64::from(index).to_le_bytes());
+        }
+        let value = plan.decode(&buffer).unwrap();
+        assert_eq!(value, decode(&spec, &buffer).unwrap());
+        assert_eq!(value.get_f64("pose.orientation.w").unwrap(), 6.0);
+        assert_eq!(value.get_f64("covariance[35]").unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_run_breaks_on_larger_alignment() {
+        let spec = spec(
+            r#"
+            uint8 a
+            float64 b
+            uint16 c
+            "#,
+        );
+
+        let plan = DecodePlan::new(&spec).unwrap();
+        assert_eq!(plan.step_count(Encapsulation::CdrBe), 2);
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00, 7, 0, 0, 0, 0, 0, 0, 0];
+        buffer.extend(1.5_f64.to_le_bytes());
+        buffer.extend(9_u16.to_le_bytes());
+        assert_eq!(
+            plan.decode(&buffer).unwrap(),
+            decode(&spec, &buffer).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_empty_fixed_array() {
+        let spec = spec(
+            r#"
+            int8 a
+            uint32[0] none
+            uint16 b
+            "#,
+        );
+
+        let buffer = [0x00, 0x01, 0x00, 0x00, 0xff, 0, 3, 0];
+        let value = DecodePlan::new(&spec).unwrap().decode(&buffer).unwrap();
+        assert_eq!(value, decode(&spec, &buffer).unwrap());
+        assert_eq!(value.get("b").unwrap(), &Value::Uint16(3));
+    }
+
+    #[test]
+    fn test_variable_fields() {
+        let spec = spec(
+            r#"
+            string frame_id
+            test_msgs/Entry[] entries
+            float64[] values
+            test_msgs/Empty empty
+            uint8 last
+            ================================================================================
+            MSG: test_msgs/Entry
+            string name
+            int32[2] pair
+            ================================================================================
+            MSG: test_msgs/Empty
+            "#,
+        );
+        let plan = DecodePlan::new(&spec).unwrap();
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(4_u32.to_le_bytes());
+        buffer.extend(b"map\0");
+        buffer.extend(2_u32.to_le_bytes());
+        for (name, pair) in [(b"a\0", [1_i32, 2]), (b"b\0", [3, 4])] {
+            buffer.extend(2_u32.to_le_bytes());
+            buffer.extend(name);
+            buffer.extend([0, 0]); // padding to 4
+            for value in pair {
+                buffer.extend(value.to_le_bytes());
+            }
+        }
+        buffer.extend(0_u32.to_le_bytes()); // empty values, not padded
+        buffer.push(0); // empty message placeholder
+        buffer.push(5);
+
+        let value = plan.decode(&buffer).unwrap();
+        assert_eq!(value, decode(&spec, &buffer).unwrap());
+        assert_eq!(value.get_str("entries[1].name").unwrap(), "b");
+        assert_eq!(value.get_i64("entries[1].pair[0]").unwrap(), 3);
+        assert_eq!(value.get_array("values").unwrap().len(), 0);
+        assert_eq!(value.get_u64("last").unwrap(), 5);
+
+        assert!(plan.decode(&buffer[..buffer.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_errors_match_tree_decoder() {
+        let spec = spec(
+            r#"
+            uint32 id
+            test_msgs/Entry[<=4] entries
+            ================================================================================
+            MSG: test_msgs/Entry
+            string<=3 name
+            uint16[] values
+            "#,
+        );
+        let plan = DecodePlan::new(&spec).unwrap();
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(7_u32.to_le_bytes());
+        buffer.extend(2_u32.to_le_bytes());
+        buffer.extend(2_u32.to_le_bytes());
+        buffer.extend(b"a\0\0\0");
+        buffer.extend(0_u32.to_le_bytes());
+        buffer.extend(2_u32.to_le_bytes());
+        buffer.extend(b"b\0\0\0");
+        buffer.extend(1000_u32.to_le_bytes());
+        buffer.extend([0; 6]);
+
+        let errors = [
+            plan.decode(&buffer).unwrap_err().to_string(),
+            decode(&spec, &buffer).unwrap_err().to_string(),
+        ];
+        assert_eq!(
+            errors,
+            [
+                "Could not decode `entries[1].values` at byte offset 30: \
+                 Sequence length 1000 exceeds the 6 remaining byte(s)",
+                "Could not decode `entries[1].values` at byte offset 30: \
+                 Sequence length 1000 exceeds the 6 remaining byte(s)",
+            ]
+        );
+
+        buffer[8] = 5;
+        assert_eq!(
+            plan.decode(&buffer).unwrap_err().to_string(),
+            "Could not decode `entries` at byte offset 8: Sequence length 5 exceeds the bound of 4"
+        );
+    }
+}
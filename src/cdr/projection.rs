@@ -0,0 +1,364 @@
+use std::mem::size_of;
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+
+use crate::cdr::{decode_field, locate, read_element_count, skip_field, CdrReader};
+use crate::ros_introspection::{Field, MsgSpec};
+use crate::value::Value;
+
+/// The fields of a message type that should be decoded, computed from a set of field paths.
+///
+/// Decoding through a projection skips every other field, jumping over strings and
+/// sequences using their length prefixes, and stops reading once the last selected field of
+/// the message has been decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Projection {
+    type_name: String,
+    fields: Vec<Option<Selection>>,
+}
+
+/// What to decode of a single field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selection {
+    /// The whole field.
+    All,
+
+    /// Only some fields of a nested message, or of each element of a sequence of messages,
+    /// indexed like the serialized fields of the nested type.
+    Fields(Vec<Option<Selection>>),
+}
+
+impl Projection {
+    /// Creates a new `Projection` instance from field paths such as `header.stamp` or
+    /// `pose.position`.
+    ///
+    /// A path that ends at a nested message selects the whole message. A path that goes
+    /// through a sequence of messages selects the field in every element.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` of the message type.
+    /// * `paths` - The paths of the fields to decode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `Projection` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if a path does not lead to a field
+    /// of the message type.
+    pub fn new<S: AsRef<str>>(spec: &MsgSpec, paths: &[S]) -> Result<Self, Error> {
+        let mut fields = vec![None; spec.serialized_fields().count()];
+
+        for path in paths {
+            let path = path.as_ref();
+            let segments: Vec<_> = path.split('.').collect();
+            select(&mut fields, spec, &segments)
+                .with_context(|| format!("Invalid field path `{path}`"))?;
+        }
+
+        Ok(Self {
+            type_name: spec.data().type_().name().to_owned(),
+            fields,
+        })
+    }
+
+    /// Decodes the selected fields of a CDR serialized message.
+    ///
+    /// The decoded message only holds the selected fields, in definition order.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` the projection was created from.
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Value, Error>` - A result containing the decoded message or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the projection was created for another type, or
+    /// a [`DecodeError`](crate::cdr::DecodeError) if the buffer does not hold a message of this
+    /// type.
+    pub fn decode(&self, spec: &MsgSpec, buffer: &[u8]) -> Result<Value, Error> {
+        let type_name = spec.data().type_().name();
+        if type_name != self.type_name {
+            bail!("Projection of {} cannot decode {type_name}", self.type_name);
+        }
+
+        let mut reader = CdrReader::new(buffer)?;
+        let position = reader.position();
+        decode_selected(&mut reader, spec, &self.fields, true)
+            .map_err(|err| locate(err, "", position))
+    }
+}
+
+/// Marks the field at the end of `segments` as selected.
+fn select(
+    fields: &mut [Option<Selection>],
+    spec: &MsgSpec,
+    segments: &[&str],
+) -> Result<(), Error> {
+    let Some((name, rest)) = segments.split_first() else {
+        bail!("Empty path");
+    };
+
+    let (index, (field, child)) = spec
+        .serialized_fields()
+        .enumerate()
+        .find(|(_, (field, _))| field.name() == *name)
+        .ok_or_else(|| anyhow!("No field named `{name}` in {}", spec.data().type_().name()))?;
+
+    let selection = &mut fields[index];
+    if rest.is_empty() {
+        *selection = Some(Selection::All);
+        return Ok(());
+    }
+
+    let child = child.ok_or_else(|| {
+        anyhow!(
+            "Field `{}` is a {}, not a message",
+            field.name(),
+            field.type_().name()
+        )
+    })?;
+    match selection
+        .get_or_insert_with(|| Selection::Fields(vec![None; child.serialized_fields().count()]))
+    {
+        // The whole field is already selected
+        Selection::All => Ok(()),
+        Selection::Fields(nested) => select(nested, child, rest),
+    }
+}
+
+/// Decodes the selected fields of a message, skipping the others.
+///
+/// With `stop_early`, nothing is read after the last selected field, which is only possible
+/// when nothing else follows the message. Delimited structures are always left early, by
+/// jumping to their end.
+fn decode_selected(
+    reader: &mut CdrReader<'_>,
+    spec: &MsgSpec,
+    selections: &[Option<Selection>],
+    stop_early: bool,
+) -> Result<Value, Error> {
+    let size = reader.read_struct_header()?;
+    let start = reader.offset();
+
+    let end = if stop_early || size.is_some() {
+        selections
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last + 1)
+    } else {
+        selections.len()
+    };
+
+    let mut values = Vec::new();
+    for ((field, child), selection) in spec.serialized_fields().zip(selections).take(end) {
+        let position = reader.position();
+        match selection {
+            None => skip_field(reader, field, child)
+                .map_err(|err| locate(err, field.name(), position))?,
+            Some(selection) => {
+                let value = reader
+                    .allocate(size_of::<(String, Value)>() + field.name().len())
+                    .and_then(|()| decode_selected_field(reader, field, child, selection))
+                    .map_err(|err| locate(err, field.name(), position))?;
+                values.push((field.name().to_owned(), value));
+            }
+        }
+    }
+
+    if let Some(size) = size {
+        if !stop_early {
+            let rest = (start + size).checked_sub(reader.offset()).ok_or_else(|| {
+                anyhow!(
+                    "Fields of {} overrun its DHEADER",
+                    spec.data().type_().name()
+                )
+            })?;
+            reader.read_bytes(rest)?;
+        }
+    } else if spec.is_empty_struct() && !stop_early {
+        reader.read_empty_struct()?;
+    }
+
+    Ok(Value::Message(values))
+}
+
+fn decode_selected_field(
+    reader: &mut CdrReader<'_>,
+    field: &Field,
+    child: Option<&MsgSpec>,
+    selection: &Selection,
+) -> Result<Value, Error> {
+    let (Selection::Fields(nested), Some(child)) = (selection, child) else {
+        return decode_field(reader, field, child);
+    };
+
+    if !field.is_array() {
+        return decode_selected(reader, child, nested, false);
+    }
+
+    let count = read_element_count(reader, field)?;
+    reader.allocate(count.saturating_mul(size_of::<Value>()))?;
+
+    let mut elements = Vec::with_capacity(count);
+    for index in 0..count {
+        let position = reader.position();
+        elements.push(
+            decode_selected(reader, child, nested, false)
+                .map_err(|err| locate(err, &format!("[{index}]"), position))?,
+        );
+    }
+    Ok(Value::Array(elements))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdr::decode;
+    use crate::ros_introspection::Dialect;
+
+    fn spec() -> MsgSpec {
+        MsgSpec::new_from_definitions(
+            "test_msgs/Detections",
+            r#"
+            std_msgs/Header header
+            string[] labels
+            test_msgs/Detection[] detections
+            geometry_msgs/Point position
+            float64 score
+            uint8[] debug_image
+            ================================================================================
+            MSG: std_msgs/Header
+            builtin_interfaces/Time stamp
+            string frame_id
+            ================================================================================
+            MSG: builtin_interfaces/Time
+            int32 sec
+            uint32 nanosec
+            ================================================================================
+            MSG: test_msgs/Detection
+            string label
+            geometry_msgs/Point center
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+            "#,
+            Dialect::Ros2,
+        )
+        .unwrap()
+    }
+
+    fn buffer() -> Vec<u8> {
+        fn string(buffer: &mut Vec<u8>, value: &str) {
+            buffer.resize(4 + (buffer.len() - 4).next_multiple_of(4), 0);
+            buffer.extend(u32::try_from(value.len() + 1).unwrap().to_le_bytes());
+            buffer.extend(value.as_bytes());
+            buffer.push(0);
+        }
+        fn point(buffer: &mut Vec<u8>, coordinates: [f64; 3]) {
+            buffer.resize(4 + (buffer.len() - 4).next_multiple_of(8), 0);
+            for coordinate in coordinates {
+                buffer.extend(coordinate.to_le_bytes());
+            }
+        }
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(3_i32.to_le_bytes());
+        buffer.extend(4_u32.to_le_bytes());
+        string(&mut buffer, "camera");
+        buffer.resize(4 + (buffer.len() - 4).next_multiple_of(4), 0);
+        buffer.extend(2_u32.to_le_bytes());
+        string(&mut buffer, "car");
+        string(&mut buffer, "pedestrian");
+        buffer.resize(4 + (buffer.len() - 4).next_multiple_of(4), 0);
+        buffer.extend(2_u32.to_le_bytes());
+        string(&mut buffer, "car");
+        point(&mut buffer, [1.0, 2.0, 3.0]);
+        string(&mut buffer, "pedestrian");
+        point(&mut buffer, [4.0, 5.0, 6.0]);
+        point(&mut buffer, [7.0, 8.0, 9.0]);
+        buffer.extend(0.5_f64.to_le_bytes());
+        buffer.extend(3_u32.to_le_bytes());
+        buffer.extend([1, 2, 3]);
+        buffer
+    }
+
+    #[test]
+    fn test_projection() {
+        let spec = spec();
+        let buffer = buffer();
+        let full = decode(&spec, &buffer).unwrap();
+
+        let projection =
+            Projection::new(&spec, &["header.stamp", "detections.center.y", "position"]).unwrap();
+        let value = projection.decode(&spec, &buffer).unwrap();
+
+        assert_eq!(
+            value.get("header.stamp").unwrap(),
+            full.get("header.stamp").unwrap()
+        );
+        assert!(value.get("header.frame_id").is_err());
+        assert!(value.get("labels").is_err());
+        assert_eq!(value.get_f64("detections[1].center.y").unwrap(), 5.0);
+        assert!(value.get("detections[1].center.x").is_err());
+        assert!(value.get("detections[1].label").is_err());
+        assert_eq!(
+            value.get("position").unwrap(),
+            full.get("position").unwrap()
+        );
+        assert!(value.get("score").is_err());
+    }
+
+    #[test]
+    fn test_projection_stops_after_last_selected_field() {
+        let spec = spec();
+        let buffer = buffer();
+
+        // Nothing after `position` is read, so a truncated message still decodes
+        let projection = Projection::new(&spec, &["position.z"]).unwrap();
+        let value = projection
+            .decode(&spec, &buffer[..buffer.len() - 15])
+            .unwrap();
+        assert_eq!(value.get_f64("position.z").unwrap(), 9.0);
+
+        let everything = Projection::new(&spec, &["header", "header.stamp.sec", "debug_image"])
+            .unwrap()
+            .decode(&spec, &buffer)
+            .unwrap();
+        assert_eq!(
+            everything.get("header").unwrap(),
+            decode(&spec, &buffer).unwrap().get("header").unwrap()
+        );
+        assert_eq!(everything.get_bytes("debug_image").unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_invalid_paths() {
+        let spec = spec();
+
+        let err = Projection::new(&spec, &["header.stmp"]).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Invalid field path `header.stmp`: No field named `stmp` in std_msgs/Header"
+        );
+        let err = Projection::new(&spec, &["score.value"]).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "Invalid field path `score.value`: Field `score` is a float64, not a message"
+        );
+        assert!(Projection::new(&spec, &["header."]).is_err());
+
+        let other =
+            MsgSpec::new_from_definitions("test_msgs/Other", "int32 a", Dialect::Ros2).unwrap();
+        let projection = Projection::new(&spec, &["score"]).unwrap();
+        assert!(projection.decode(&other, &buffer()).is_err());
+    }
+}
@@ -0,0 +1,577 @@
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::cdr::{Encapsulation, WcharSize};
+use crate::ros_introspection::EMPTY_STRUCT_SIZE;
+
+/// Size in bytes of the encapsulation header that precedes every serialized message.
+pub const ENCAPSULATION_HEADER_SIZE: usize = 4;
+
+/// Default cap on the memory allocated while decoding a single message, in bytes.
+pub const DEFAULT_MAX_ALLOCATION: usize = 1 << 30;
+
+/// Limits enforced while decoding a single message, to survive malicious inputs, along with the
+/// size of the `wstring` characters of the serializer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    max_allocation: usize,
+    wchar_size: WcharSize,
+}
+
+impl DecodeLimits {
+    /// Creates a new `DecodeLimits` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_allocation` - The maximum number of bytes allocated for the decoded values of a
+    ///   single message, including strings, byte arrays and the values of arrays and messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `DecodeLimits` instance.
+    pub fn new(max_allocation: usize) -> Self {
+        Self {
+            max_allocation,
+            wchar_size: WcharSize::default(),
+        }
+    }
+
+    /// Sets the size of the `wstring` characters, which depends on the serializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `wchar_size` - The size of the characters, 4 bytes by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The `DecodeLimits` instance, for chaining.
+    #[must_use]
+    pub fn with_wchar_size(mut self, wchar_size: WcharSize) -> Self {
+        self.wchar_size = wchar_size;
+        self
+    }
+
+    /// Returns the maximum number of bytes allocated while decoding a single message.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The maximum allocation, in bytes.
+    pub fn max_allocation(&self) -> usize {
+        self.max_allocation
+    }
+
+    /// Returns the size of the `wstring` characters.
+    ///
+    /// # Returns
+    ///
+    /// * `WcharSize` - The size of the characters.
+    pub fn wchar_size(&self) -> WcharSize {
+        self.wchar_size
+    }
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ALLOCATION)
+    }
+}
+
+/// Reads primitive values from a CDR buffer, handling alignment and byte order.
+///
+/// Offsets and alignment are relative to the start of the payload, right after the
+/// encapsulation header.
+///
+/// The reader also keeps track of the memory allocated for the values decoded from it, which
+/// decoders account for through [`CdrReader::allocate`].
+#[derive(Debug, Clone)]
+pub struct CdrReader<'a> {
+    payload: &'a [u8],
+    offset: usize,
+    encapsulation: Encapsulation,
+    little_endian: bool,
+    max_align: usize,
+    limits: DecodeLimits,
+    allocated: usize,
+}
+
+macro_rules! read_primitive {
+    ($(#[$doc:meta])* $name:ident, $ty:ty) => {
+        $(#[$doc])*
+        ///
+        /// # Errors
+        ///
+        /// This function will return an error if the buffer is too short.
+        pub fn $name(&mut self) -> Result<$ty, Error> {
+            const SIZE: usize = std::mem::size_of::<$ty>();
+            self.align(SIZE)?;
+            let bytes: [u8; SIZE] = self.read_bytes(SIZE)?.try_into()?;
+            Ok(if self.little_endian {
+                <$ty>::from_le_bytes(bytes)
+            } else {
+                <$ty>::from_be_bytes(bytes)
+            })
+        }
+    };
+}
+
+impl<'a> CdrReader<'a> {
+    /// Creates a new `CdrReader` instance over a serialized message, starting with its
+    /// encapsulation header.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The serialized message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `CdrReader` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is shorter than the encapsulation
+    /// header, or if the encapsulation is unknown or a parameter list.
+    pub fn new(buffer: &'a [u8]) -> Result<Self, Error> {
+        Self::with_limits(buffer, DecodeLimits::default())
+    }
+
+    /// Creates a new `CdrReader` instance over a serialized message, enforcing custom limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The serialized message.
+    /// * `limits` - The limits to enforce while decoding the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `CdrReader` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is shorter than the encapsulation
+    /// header, or if the encapsulation is unknown or a parameter list.
+    pub fn with_limits(buffer: &'a [u8], limits: DecodeLimits) -> Result<Self, Error> {
+        if buffer.len() < ENCAPSULATION_HEADER_SIZE {
+            bail!(
+                "Serialized message is {} byte(s) long, shorter than its encapsulation header",
+                buffer.len()
+            );
+        }
+
+        let encapsulation = Encapsulation::from_id(u16::from_be_bytes([buffer[0], buffer[1]]))?;
+        if encapsulation.is_parameter_list() {
+            bail!("Unsupported CDR encapsulation {encapsulation}: parameter lists of mutable types cannot be decoded");
+        }
+
+        Ok(Self {
+            payload: &buffer[ENCAPSULATION_HEADER_SIZE..],
+            offset: 0,
+            encapsulation,
+            little_endian: encapsulation.is_little_endian(),
+            max_align: encapsulation.max_align(),
+            limits,
+            allocated: 0,
+        })
+    }
+
+    /// Returns the offset of the next value to be read, relative to the start of the payload.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The current offset.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the offset of the next value to be read, relative to the start of the serialized
+    /// message, including its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The current position in the buffer.
+    pub fn position(&self) -> usize {
+        ENCAPSULATION_HEADER_SIZE + self.offset
+    }
+
+    /// Returns the number of bytes left to read.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of remaining bytes.
+    pub fn remaining(&self) -> usize {
+        self.payload.len() - self.offset
+    }
+
+    /// Returns the encapsulation of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Encapsulation` - The encapsulation announced by the header.
+    pub fn encapsulation(&self) -> Encapsulation {
+        self.encapsulation
+    }
+
+    /// Returns whether the payload is little-endian.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the payload is little-endian, `false` if it is big-endian.
+    pub fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    /// Accounts for memory allocated for decoded values, failing once the limit is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The number of bytes about to be allocated.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the total allocation for the message would exceed
+    /// the limit of the reader.
+    pub fn allocate(&mut self, bytes: usize) -> Result<(), Error> {
+        self.allocated = self.allocated.saturating_add(bytes);
+        if self.allocated > self.limits.max_allocation {
+            bail!(
+                "Decoded values would exceed the limit of {} byte(s) per message",
+                self.limits.max_allocation
+            );
+        }
+        Ok(())
+    }
+
+    /// Skips the padding needed for the next value to be aligned.
+    ///
+    /// The alignment is capped to the largest one of the encapsulation, e.g. 8-byte values are
+    /// only aligned to 4 bytes in XCDR2.
+    ///
+    /// # Arguments
+    ///
+    /// * `align` - The alignment of the next value, in bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer ends within the padding.
+    pub fn align(&mut self, align: usize) -> Result<(), Error> {
+        let align = align.min(self.max_align);
+        let padding = self.offset.next_multiple_of(align) - self.offset;
+        self.read_bytes(padding).map(drop)
+    }
+
+    /// Reads raw bytes, without any alignment.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of bytes to read.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<&[u8], Error>` - A result containing the bytes, borrowed from the buffer, or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if fewer than `len` bytes remain.
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.payload.get(self.offset..end))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unexpected end of buffer: needed {len} byte(s), {} remaining",
+                    self.remaining()
+                )
+            })?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    read_primitive!(
+        /// Reads a `uint8`.
+        read_u8,
+        u8
+    );
+    read_primitive!(
+        /// Reads an `int8`.
+        read_i8,
+        i8
+    );
+    read_primitive!(
+        /// Reads a `uint16`.
+        read_u16,
+        u16
+    );
+    read_primitive!(
+        /// Reads an `int16`.
+        read_i16,
+        i16
+    );
+    read_primitive!(
+        /// Reads a `uint32`.
+        read_u32,
+        u32
+    );
+    read_primitive!(
+        /// Reads an `int32`.
+        read_i32,
+        i32
+    );
+    read_primitive!(
+        /// Reads a `uint64`.
+        read_u64,
+        u64
+    );
+    read_primitive!(
+        /// Reads an `int64`.
+        read_i64,
+        i64
+    );
+    read_primitive!(
+        /// Reads a `float32`.
+        read_f32,
+        f32
+    );
+    read_primitive!(
+        /// Reads a `float64`.
+        read_f64,
+        f64
+    );
+
+    /// Reads the length prefix of a string or sequence.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is too short.
+    pub fn read_length(&mut self) -> Result<usize, Error> {
+        Ok(usize::try_from(self.read_u32()?)?)
+    }
+
+    /// Reads the DHEADER that precedes a structure in delimited encapsulations.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<usize>, Error>` - A result containing the size of the structure that follows, `None` if the encapsulation has no such header, or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is too short.
+    pub fn read_struct_header(&mut self) -> Result<Option<usize>, Error> {
+        if !self.encapsulation.is_delimited() {
+            return Ok(None);
+        }
+        self.read_length().map(Some)
+    }
+
+    /// Reads the placeholder that stands for a structure without fields, as told by
+    /// [`MsgSpec::is_empty_struct`](crate::ros_introspection::MsgSpec::is_empty_struct).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is too short.
+    pub fn read_empty_struct(&mut self) -> Result<(), Error> {
+        self.read_bytes(EMPTY_STRUCT_SIZE).map(drop)
+    }
+
+    /// Reads the DHEADER that precedes an array or sequence of non-primitive elements in XCDR2.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<usize>, Error>` - A result containing the size of the elements that follow, including the length of a sequence, `None` if the encapsulation has no such header, or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer is too short.
+    pub fn read_collection_header(&mut self) -> Result<Option<usize>, Error> {
+        if !self.encapsulation.is_xcdr2() {
+            return Ok(None);
+        }
+        self.read_length().map(Some)
+    }
+
+    /// Reads a null-terminated `string`.
+    ///
+    /// Invalid UTF-8 sequences are replaced, rather than failing the whole message.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the length prefix exceeds the remaining bytes, or
+    /// if the string would exceed the allocation limit.
+    pub fn read_string(&mut self) -> Result<String, Error> {
+        let len = self.read_length()?;
+        if len > self.remaining() {
+            bail!(
+                "String length {len} exceeds the {} remaining byte(s)",
+                self.remaining()
+            );
+        }
+        self.allocate(len)?;
+        let bytes = self.read_bytes(len)?;
+        // The length includes the null terminator, which some writers omit for empty strings
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        Ok(String::from_utf8_lossy(bytes).into_owned())
+    }
+
+    /// Reads a `wstring`, made of characters of the size set by the limits of the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the length prefix exceeds the remaining bytes, or
+    /// if the string would exceed the allocation limit.
+    pub fn read_wstring(&mut self) -> Result<String, Error> {
+        let len = self.read_length()?;
+        let wchar_size = self.limits.wchar_size();
+        let size = len
+            .checked_mul(wchar_size.bytes())
+            .filter(|size| *size <= self.remaining())
+            .ok_or_else(|| {
+                anyhow!(
+                    "Wstring length {len} exceeds the {} remaining byte(s)",
+                    self.remaining()
+                )
+            })?;
+        // Each character takes at most 4 bytes once encoded as UTF-8
+        self.allocate(len.saturating_mul(4))?;
+        let bytes = self.read_bytes(size)?;
+
+        Ok(match wchar_size {
+            WcharSize::Two => {
+                let units = bytes.chunks_exact(2).map(|chunk| {
+                    let bytes = [chunk[0], chunk[1]];
+                    if self.little_endian {
+                        u16::from_le_bytes(bytes)
+                    } else {
+                        u16::from_be_bytes(bytes)
+                    }
+                });
+                widestring::U16String::from_vec(units.collect::<Vec<_>>()).to_string_lossy()
+            }
+            WcharSize::Four => {
+                let chars = bytes.chunks_exact(4).map(|chunk| {
+                    let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+                    if self.little_endian {
+                        u32::from_le_bytes(bytes)
+                    } else {
+                        u32::from_be_bytes(bytes)
+                    }
+                });
+                widestring::U32String::from_vec(chars.collect::<Vec<_>>()).to_string_lossy()
+            }
+        })
+    }
+
+    /// Returns the size of the `wstring` characters the reader expects.
+    ///
+    /// # Returns
+    ///
+    /// * `WcharSize` - The size of the characters.
+    pub fn wchar_size(&self) -> WcharSize {
+        self.limits.wchar_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alignment_and_byte_order() {
+        let buffer = [
+            0x00, 0x01, 0x00, 0x00, // CDR_LE
+            0x07, 0x00, 0x00, 0x00, // u8 + padding
+            0x2a, 0x00, 0x00, 0x00, // u32
+            0x03, 0x00, 0x00, 0x00, b'h', b'i', 0x00, // "hi"
+        ];
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(reader.read_u8().unwrap(), 7);
+        assert_eq!(reader.read_u32().unwrap(), 42);
+        assert_eq!(reader.read_string().unwrap(), "hi");
+        assert_eq!(reader.remaining(), 0);
+
+        let buffer = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x02];
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert!(!reader.is_little_endian());
+        assert_eq!(reader.read_u32().unwrap(), 0x0102);
+    }
+
+    #[test]
+    fn test_xcdr2_alignment() {
+        let mut buffer = vec![0x00, 0x07, 0x00, 0x00];
+        buffer.extend(1_u32.to_le_bytes());
+        buffer.extend(2.5_f64.to_le_bytes());
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(reader.encapsulation(), Encapsulation::Cdr2Le);
+        assert_eq!(reader.read_u32().unwrap(), 1);
+        // 8-byte values are only aligned to 4 bytes
+        assert_eq!(reader.read_f64().unwrap(), 2.5);
+        assert_eq!(
+            reader.read_collection_header().unwrap_err().to_string(),
+            "Unexpected end of buffer: needed 4 byte(s), 0 remaining"
+        );
+        assert_eq!(reader.read_struct_header().unwrap(), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(CdrReader::new(&[0x00, 0x01]).is_err());
+        assert!(CdrReader::new(&[0x00, 0x04, 0x00, 0x00]).is_err());
+        assert_eq!(
+            CdrReader::new(&[0x00, 0x03, 0x00, 0x00])
+                .unwrap_err()
+                .to_string(),
+            "Unsupported CDR encapsulation PL_CDR_LE: parameter lists of mutable types cannot be decoded"
+        );
+
+        let mut reader = CdrReader::new(&[0x00, 0x01, 0x00, 0x00, 0x01]).unwrap();
+        assert_eq!(
+            reader.read_u32().unwrap_err().to_string(),
+            "Unexpected end of buffer: needed 4 byte(s), 1 remaining"
+        );
+    }
+
+    #[test]
+    fn test_wchar_sizes() {
+        let buffer = [
+            0x00, 0x01, 0x00, 0x00, // CDR_LE
+            0x02, 0x00, 0x00, 0x00, // 2 characters
+            b'h', 0x00, 0xe9, 0x00, // "hé" as UTF-16
+        ];
+        let limits = DecodeLimits::default().with_wchar_size(WcharSize::Two);
+        let mut reader = CdrReader::with_limits(&buffer, limits).unwrap();
+        assert_eq!(reader.read_wstring().unwrap(), "hé");
+        assert_eq!(reader.remaining(), 0);
+
+        // Read as 32-bit characters, the same buffer is too short
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(
+            reader.read_wstring().unwrap_err().to_string(),
+            "Wstring length 2 exceeds the 4 remaining byte(s)"
+        );
+    }
+
+    #[test]
+    fn test_malicious_lengths() {
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(u32::MAX.to_le_bytes());
+        buffer.extend(b"hi\0");
+
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(
+            reader.read_string().unwrap_err().to_string(),
+            "String length 4294967295 exceeds the 3 remaining byte(s)"
+        );
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(
+            reader.read_wstring().unwrap_err().to_string(),
+            "Wstring length 4294967295 exceeds the 3 remaining byte(s)"
+        );
+
+        let mut buffer = vec![0x00, 0x01, 0x00, 0x00];
+        buffer.extend(3_u32.to_le_bytes());
+        buffer.extend(b"hi\0");
+        let mut reader = CdrReader::with_limits(&buffer, DecodeLimits::new(2)).unwrap();
+        assert_eq!(
+            reader.read_string().unwrap_err().to_string(),
+            "Decoded values would exceed the limit of 2 byte(s) per message"
+        );
+        assert_eq!(reader.position(), 8);
+    }
+}
@@ -0,0 +1,589 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Error, Result};
+
+use crate::cdr::{
+    decode_field, decode_message, locate, read_element_count, skip_field, skip_message, CdrReader,
+};
+use crate::ros_introspection::{BuiltinType, Field, MsgSpec};
+use crate::value::Value;
+
+/// A primitive type that can be viewed as a slice of a CDR buffer.
+pub trait CdrPrimitive: bytemuck::Pod {
+    /// Returns whether fields of the given builtin type hold values of this type.
+    fn matches(id: &BuiltinType) -> bool;
+
+    /// Converts the serialized bytes of a single value.
+    fn from_cdr_bytes(bytes: &[u8], little_endian: bool) -> Self;
+}
+
+macro_rules! impl_cdr_primitive {
+    ($ty:ty, $($id:ident)|+) => {
+        impl CdrPrimitive for $ty {
+            fn matches(id: &BuiltinType) -> bool {
+                matches!(id, $(BuiltinType::$id)|+)
+            }
+
+            fn from_cdr_bytes(bytes: &[u8], little_endian: bool) -> Self {
+                let mut array = [0; std::mem::size_of::<$ty>()];
+                array.copy_from_slice(bytes);
+                if little_endian {
+                    <$ty>::from_le_bytes(array)
+                } else {
+                    <$ty>::from_be_bytes(array)
+                }
+            }
+        }
+    };
+}
+
+impl_cdr_primitive!(u8, Uint8 | Byte | Char);
+impl_cdr_primitive!(i8, Int8);
+impl_cdr_primitive!(u16, Uint16);
+impl_cdr_primitive!(i16, Int16);
+impl_cdr_primitive!(u32, Uint32);
+impl_cdr_primitive!(i32, Int32);
+impl_cdr_primitive!(u64, Uint64);
+impl_cdr_primitive!(i64, Int64);
+impl_cdr_primitive!(f32, Float32);
+impl_cdr_primitive!(f64, Float64);
+
+/// A borrowed view over a CDR serialized message.
+///
+/// Nothing is decoded up front: fields are located when they are accessed, by skipping the
+/// fields before them, and primitive arrays are returned as slices of the buffer.
+#[derive(Debug, Clone)]
+pub struct MessageView<'a> {
+    spec: &'a MsgSpec,
+    reader: CdrReader<'a>,
+}
+
+impl<'a> MessageView<'a> {
+    /// Creates a new `MessageView` instance over a serialized message.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` of the message type.
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `MessageView` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the encapsulation header is invalid.
+    pub fn new(spec: &'a MsgSpec, buffer: &'a [u8]) -> Result<Self, Error> {
+        Ok(Self {
+            spec,
+            reader: CdrReader::new(buffer)?,
+        })
+    }
+
+    /// Returns the specification of the viewed message.
+    ///
+    /// # Returns
+    ///
+    /// * `&MsgSpec` - A reference to the `MsgSpec` of the message.
+    pub fn spec(&self) -> &'a MsgSpec {
+        self.spec
+    }
+
+    /// Returns an iterator over the views of the serialized fields, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `FieldViews` - An iterator over the fields, which stops after the first error.
+    pub fn fields(&self) -> FieldViews<'a> {
+        FieldViews {
+            fields: self.spec.data().fields().iter(),
+            children: self.spec.children().iter(),
+            reader: Some(self.reader.clone()),
+            started: false,
+        }
+    }
+
+    /// Returns the view of a field, by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FieldView, Error>` - A result containing the view of the field or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the message has no such field, or if a field
+    /// before it cannot be skipped.
+    pub fn field(&self, name: &str) -> Result<FieldView<'a>, Error> {
+        for field in self.fields() {
+            let field = field?;
+            if field.field.name() == name {
+                return Ok(field);
+            }
+        }
+        Err(anyhow!(
+            "No field named `{name}` in {}",
+            self.spec.data().type_().name()
+        ))
+    }
+
+    /// Returns the view of a nested field by its path, such as `header.frame_id` or
+    /// `poses[2].position`.
+    ///
+    /// Indices may only be applied to sequences of messages, and the path must end with a
+    /// field name.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path of the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FieldView, Error>` - A result containing the view of the field or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the path does not lead to a field.
+    pub fn get(&self, path: &str) -> Result<FieldView<'a>, Error> {
+        let mut message = self.clone();
+        let mut segments = path.split('.').peekable();
+
+        while let Some(segment) = segments.next() {
+            let (name, index) = parse_segment(segment)
+                .ok_or_else(|| anyhow!("Field `{path}`: malformed segment `{segment}`"))?;
+            let field = message
+                .field(name)
+                .map_err(|err| anyhow!("Field `{path}`: {err}"))?;
+
+            if segments.peek().is_none() {
+                if index.is_some() {
+                    return Err(anyhow!(
+                        "Field `{path}`: the path must end with a field name"
+                    ));
+                }
+                return Ok(field);
+            }
+
+            let nested = match index {
+                Some(index) => field.messages().and_then(|mut elements| {
+                    elements
+                        .nth(index)
+                        .ok_or_else(|| anyhow!("index {index} out of bounds"))?
+                }),
+                None => field.message(),
+            };
+            message = nested.map_err(|err| anyhow!("Field `{path}`: {err}"))?;
+        }
+
+        Err(anyhow!("Field `{path}`: empty path"))
+    }
+
+    /// Decodes the whole message.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer does not hold a message of this type.
+    pub fn decode(&self) -> Result<Value, Error> {
+        let position = self.reader.position();
+        decode_message(&mut self.reader.clone(), self.spec).map_err(|err| locate(err, "", position))
+    }
+}
+
+/// An iterator over the fields of a [`MessageView`].
+#[derive(Debug)]
+pub struct FieldViews<'a> {
+    fields: std::slice::Iter<'a, Field>,
+    children: std::slice::Iter<'a, std::sync::Arc<MsgSpec>>,
+    reader: Option<CdrReader<'a>>,
+    started: bool,
+}
+
+impl<'a> Iterator for FieldViews<'a> {
+    type Item = Result<FieldView<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = self.reader.as_mut()?;
+
+        if !self.started {
+            self.started = true;
+            if let Err(err) = reader.read_struct_header() {
+                self.reader = None;
+                return Some(Err(err));
+            }
+        }
+
+        loop {
+            let field = self.fields.next()?;
+            let child = if field.type_().id() == &BuiltinType::Other {
+                self.children.next().map(AsRef::as_ref)
+            } else {
+                None
+            };
+            if field.is_constant() {
+                continue;
+            }
+
+            let view = FieldView {
+                field,
+                child,
+                reader: reader.clone(),
+            };
+            let position = reader.position();
+            if let Err(err) = skip_field(reader, field, child) {
+                self.reader = None;
+                return Some(Err(locate(err, field.name(), position)));
+            }
+            return Some(Ok(view));
+        }
+    }
+}
+
+/// A borrowed view over a single field of a CDR serialized message.
+#[derive(Debug, Clone)]
+pub struct FieldView<'a> {
+    field: &'a Field,
+    child: Option<&'a MsgSpec>,
+    reader: CdrReader<'a>,
+}
+
+impl<'a> FieldView<'a> {
+    /// Returns the definition of the viewed field.
+    ///
+    /// # Returns
+    ///
+    /// * `&Field` - A reference to the field.
+    pub fn field(&self) -> &'a Field {
+        self.field
+    }
+
+    /// Decodes the field.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer does not hold a value of the field's type.
+    pub fn decode(&self) -> Result<Value, Error> {
+        let position = self.reader.position();
+        decode_field(&mut self.reader.clone(), self.field, self.child)
+            .map_err(|err| locate(err, self.field.name(), position))
+    }
+
+    /// Returns the view of a field that holds a single message.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not a single message.
+    pub fn message(&self) -> Result<MessageView<'a>, Error> {
+        match self.child {
+            Some(spec) if !self.field.is_array() => Ok(MessageView {
+                spec,
+                reader: self.reader.clone(),
+            }),
+            _ => Err(anyhow!(
+                "Field {} is not a single message",
+                self.field.name()
+            )),
+        }
+    }
+
+    /// Returns an iterator over the views of the elements of an array of messages.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not an array of messages, or if its
+    /// length cannot be read.
+    pub fn messages(&self) -> Result<MessageViews<'a>, Error> {
+        let spec = match self.child {
+            Some(spec) if self.field.is_array() => spec,
+            _ => {
+                return Err(anyhow!(
+                    "Field {} is not an array of messages",
+                    self.field.name()
+                ))
+            }
+        };
+
+        let mut reader = self.reader.clone();
+        let remaining = self.element_count_from(&mut reader)?;
+        Ok(MessageViews {
+            spec,
+            reader: Some(reader),
+            remaining,
+        })
+    }
+
+    /// Returns the number of elements of an array field.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not an array, or if its length
+    /// cannot be read.
+    pub fn element_count(&self) -> Result<usize, Error> {
+        if !self.field.is_array() {
+            return Err(anyhow!("Field {} is not an array", self.field.name()));
+        }
+        self.element_count_from(&mut self.reader.clone())
+    }
+
+    /// Returns the contents of a `uint8`, `byte` or `char` array, borrowed from the buffer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not a byte array, or if the buffer
+    /// is too short.
+    pub fn bytes(&self) -> Result<&'a [u8], Error> {
+        self.raw_primitives::<u8>()
+    }
+
+    /// Returns the elements of a primitive array.
+    ///
+    /// The elements are borrowed from the buffer if they are suitably aligned in memory and
+    /// serialized in the native byte order, and copied otherwise. As CDR alignment is relative
+    /// to the end of the 4-byte encapsulation header, 8-byte elements can only be borrowed
+    /// when the buffer starts 4 bytes past an 8-byte boundary.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not an array of `T`, or if the
+    /// buffer is too short.
+    pub fn slice<T: CdrPrimitive>(&self) -> Result<Cow<'a, [T]>, Error> {
+        let bytes = self.raw_primitives::<T>()?;
+        let little_endian = self.reader.is_little_endian();
+
+        if little_endian == cfg!(target_endian = "little") {
+            if let Ok(slice) = bytemuck::try_cast_slice(bytes) {
+                return Ok(Cow::Borrowed(slice));
+            }
+        }
+
+        Ok(Cow::Owned(
+            bytes
+                .chunks_exact(std::mem::size_of::<T>())
+                .map(|chunk| T::from_cdr_bytes(chunk, little_endian))
+                .collect(),
+        ))
+    }
+
+    /// Returns the elements of a `float32` array, borrowed from the buffer when possible.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not a `float32` array.
+    pub fn f32_slice(&self) -> Result<Cow<'a, [f32]>, Error> {
+        self.slice()
+    }
+
+    /// Returns the elements of a `float64` array, borrowed from the buffer when possible.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the field is not a `float64` array.
+    pub fn f64_slice(&self) -> Result<Cow<'a, [f64]>, Error> {
+        self.slice()
+    }
+
+    /// Returns the serialized bytes of the elements of an array of `T`.
+    fn raw_primitives<T: CdrPrimitive>(&self) -> Result<&'a [u8], Error> {
+        if !self.field.is_array() || !T::matches(self.field.type_().id()) {
+            return Err(anyhow!(
+                "Field {} is not an array of {}",
+                self.field.name(),
+                std::any::type_name::<T>()
+            ));
+        }
+
+        let mut reader = self.reader.clone();
+        let count = self.element_count_from(&mut reader)?;
+        let size = std::mem::size_of::<T>();
+        // Like Fast-CDR, empty arrays are not padded
+        if count > 0 {
+            reader.align(size)?;
+        }
+        reader.read_bytes(
+            count
+                .checked_mul(size)
+                .ok_or_else(|| anyhow!("Invalid sequence length {count}"))?,
+        )
+    }
+
+    /// Reads the length of an array field, leaving the reader at its first element.
+    fn element_count_from(&self, reader: &mut CdrReader<'a>) -> Result<usize, Error> {
+        read_element_count(reader, self.field)
+    }
+}
+
+/// An iterator over the elements of an array of messages.
+#[derive(Debug)]
+pub struct MessageViews<'a> {
+    spec: &'a MsgSpec,
+    reader: Option<CdrReader<'a>>,
+    remaining: usize,
+}
+
+impl<'a> Iterator for MessageViews<'a> {
+    type Item = Result<MessageView<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let reader = self.reader.as_mut()?;
+        self.remaining -= 1;
+
+        let view = MessageView {
+            spec: self.spec,
+            reader: reader.clone(),
+        };
+        let position = reader.position();
+        if let Err(err) = skip_message(reader, self.spec) {
+            self.reader = None;
+            return Some(Err(locate(err, "", position)));
+        }
+        Some(Ok(view))
+    }
+}
+
+/// Splits a path segment such as `poses[2]` into its name and optional index.
+fn parse_segment(segment: &str) -> Option<(&str, Option<usize>)> {
+    match segment.split_once('[') {
+        None => Some((segment, None)),
+        Some((name, rest)) => {
+            let index = rest.strip_suffix(']')?.parse().ok()?;
+            Some((name, Some(index)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ros_introspection::Dialect;
+
+    fn cloud_spec() -> MsgSpec {
+        MsgSpec::new_from_definitions(
+            "test_msgs/Cloud",
+            r#"
+            string frame_id
+            test_msgs/Field[] fields
+            uint8[] data
+            float32[] ranges
+            float64[2] pair
+            ================================================================================
+            MSG: test_msgs/Field
+            string name
+            uint32 offset
+            "#,
+            Dialect::Ros2,
+        )
+        .unwrap()
+    }
+
+    fn cloud_buffer(little_endian: bool) -> Vec<u8> {
+        let u32_bytes = |value: u32| {
+            if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            }
+        };
+
+        let mut buffer = vec![0x00, u8::from(little_endian), 0x00, 0x00];
+        buffer.extend(u32_bytes(4));
+        buffer.extend(b"map\0");
+        buffer.extend(u32_bytes(2));
+        for (name, offset) in [("x", 0), ("y", 4)] {
+            buffer.extend(u32_bytes(2));
+            buffer.extend(name.as_bytes());
+            buffer.extend([0, 0, 0]); // null and padding to 4
+            buffer.extend(u32_bytes(offset));
+        }
+        buffer.extend(u32_bytes(3));
+        buffer.extend([1, 2, 3]);
+        buffer.push(0); // padding to 4
+        buffer.extend(u32_bytes(2));
+        for range in [0.5_f32, 1.5] {
+            buffer.extend(u32_bytes(range.to_bits()));
+        }
+        for value in [2.5_f64, -1.0] {
+            buffer.extend(if little_endian {
+                value.to_le_bytes()
+            } else {
+                value.to_be_bytes()
+            });
+        }
+        buffer
+    }
+
+    #[test]
+    fn test_view() {
+        let spec = cloud_spec();
+        let buffer = cloud_buffer(cfg!(target_endian = "little"));
+        // Place the payload, rather than the buffer, at an 8-byte aligned address
+        let mut storage = vec![0_u64; buffer.len().div_ceil(8) + 1];
+        let storage: &mut [u8] = bytemuck::cast_slice_mut(&mut storage);
+        let shifted = &mut storage[4..4 + buffer.len()];
+        shifted.copy_from_slice(&buffer);
+        let view = MessageView::new(&spec, shifted).unwrap();
+
+        assert_eq!(view.field("data").unwrap().bytes().unwrap(), [1, 2, 3]);
+        assert_eq!(
+            view.get("fields[1].name").unwrap().decode().unwrap(),
+            Value::String("y".to_owned())
+        );
+        assert_eq!(view.get("fields").unwrap().element_count().unwrap(), 2);
+
+        let ranges = view.field("ranges").unwrap().f32_slice().unwrap();
+        assert!(matches!(ranges, Cow::Borrowed(_)));
+        assert_eq!(*ranges, [0.5, 1.5]);
+
+        let pair = view.field("pair").unwrap().f64_slice().unwrap();
+        assert!(matches!(pair, Cow::Borrowed(_)));
+        assert_eq!(*pair, [2.5, -1.0]);
+
+        assert_eq!(
+            view.decode().unwrap(),
+            crate::cdr::decode(&spec, &buffer).unwrap()
+        );
+
+        // With the buffer itself 8-byte aligned, float64 elements are only 4-byte aligned
+        let storage = &mut storage[..buffer.len()];
+        storage.copy_from_slice(&buffer);
+        let view = MessageView::new(&spec, storage).unwrap();
+        let pair = view.field("pair").unwrap().f64_slice().unwrap();
+        assert!(matches!(pair, Cow::Owned(_)));
+        assert_eq!(*pair, [2.5, -1.0]);
+    }
+
+    #[test]
+    fn test_view_copies_foreign_byte_order() {
+        let spec = cloud_spec();
+        let buffer = cloud_buffer(!cfg!(target_endian = "little"));
+        let view = MessageView::new(&spec, &buffer).unwrap();
+
+        let ranges = view.field("ranges").unwrap().f32_slice().unwrap();
+        assert!(matches!(ranges, Cow::Owned(_)));
+        assert_eq!(*ranges, [0.5, 1.5]);
+        assert_eq!(
+            *view.field("pair").unwrap().f64_slice().unwrap(),
+            [2.5, -1.0]
+        );
+    }
+
+    #[test]
+    fn test_view_errors() {
+        let spec = cloud_spec();
+        let buffer = cloud_buffer(true);
+        let view = MessageView::new(&spec, &buffer).unwrap();
+
+        assert!(view.field("missing").is_err());
+        assert!(view.field("ranges").unwrap().f64_slice().is_err());
+        assert!(view.field("frame_id").unwrap().bytes().is_err());
+        assert!(view.get("fields[2].name").is_err());
+        assert!(view.get("fields[0]").is_err());
+
+        let view = MessageView::new(&spec, &buffer[..20]).unwrap();
+        assert!(view.field("data").is_err());
+    }
+}
@@ -0,0 +1,378 @@
+use anyhow::{anyhow, bail, Error, Result};
+
+use crate::cdr::{Encapsulation, WcharSize, ENCAPSULATION_HEADER_SIZE};
+use crate::ros_introspection::EMPTY_STRUCT_SIZE;
+
+/// Writes primitive values into a CDR buffer, handling alignment and byte order.
+///
+/// Alignment is relative to the start of the payload, right after the encapsulation header.
+#[derive(Debug, Clone)]
+pub struct CdrWriter {
+    buffer: Vec<u8>,
+    encapsulation: Encapsulation,
+    little_endian: bool,
+    max_align: usize,
+    wchar_size: WcharSize,
+}
+
+/// The position of a DHEADER whose size is written once the data it covers is complete.
+#[derive(Debug)]
+#[must_use = "the DHEADER must be completed with `CdrWriter::end_header`"]
+pub struct PendingHeader(Option<usize>);
+
+macro_rules! write_primitive {
+    ($(#[$doc:meta])* $name:ident, $ty:ty) => {
+        $(#[$doc])*
+        pub fn $name(&mut self, value: $ty) {
+            self.align(std::mem::size_of::<$ty>());
+            if self.little_endian {
+                self.buffer.extend_from_slice(&value.to_le_bytes());
+            } else {
+                self.buffer.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+    };
+}
+
+impl CdrWriter {
+    /// Creates a new `CdrWriter` instance, starting with the encapsulation header.
+    ///
+    /// # Arguments
+    ///
+    /// * `encapsulation` - The encapsulation of the payload.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `CdrWriter` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the encapsulation is a parameter list.
+    pub fn new(encapsulation: Encapsulation) -> Result<Self, Error> {
+        if encapsulation.is_parameter_list() {
+            bail!("Unsupported CDR encapsulation {encapsulation}: parameter lists of mutable types cannot be encoded");
+        }
+
+        let mut buffer = encapsulation.id().to_be_bytes().to_vec();
+        buffer.extend([0x00, 0x00]);
+        Ok(Self {
+            buffer,
+            encapsulation,
+            little_endian: encapsulation.is_little_endian(),
+            max_align: encapsulation.max_align(),
+            wchar_size: WcharSize::default(),
+        })
+    }
+
+    /// Sets the size of the `wstring` characters, to match a given serializer.
+    ///
+    /// # Arguments
+    ///
+    /// * `wchar_size` - The size of the characters, 4 bytes by default.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The `CdrWriter` instance, for chaining.
+    #[must_use]
+    pub fn with_wchar_size(mut self, wchar_size: WcharSize) -> Self {
+        self.wchar_size = wchar_size;
+        self
+    }
+
+    /// Returns the encapsulation of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Encapsulation` - The encapsulation written in the header.
+    pub fn encapsulation(&self) -> Encapsulation {
+        self.encapsulation
+    }
+
+    /// Returns the offset of the next value to be written, relative to the start of the payload.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The current offset.
+    pub fn offset(&self) -> usize {
+        self.buffer.len() - ENCAPSULATION_HEADER_SIZE
+    }
+
+    /// Writes the padding needed for the next value to be aligned.
+    ///
+    /// The alignment is capped to the largest one of the encapsulation, e.g. 8-byte values are
+    /// only aligned to 4 bytes in XCDR2.
+    ///
+    /// # Arguments
+    ///
+    /// * `align` - The alignment of the next value, in bytes.
+    pub fn align(&mut self, align: usize) {
+        let align = align.min(self.max_align);
+        let offset = self.offset();
+        let padding = offset.next_multiple_of(align) - offset;
+        self.buffer.resize(self.buffer.len() + padding, 0);
+    }
+
+    /// Writes raw bytes, without any alignment.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to write.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    write_primitive!(
+        /// Writes a `uint8`.
+        write_u8,
+        u8
+    );
+    write_primitive!(
+        /// Writes an `int8`.
+        write_i8,
+        i8
+    );
+    write_primitive!(
+        /// Writes a `uint16`.
+        write_u16,
+        u16
+    );
+    write_primitive!(
+        /// Writes an `int16`.
+        write_i16,
+        i16
+    );
+    write_primitive!(
+        /// Writes a `uint32`.
+        write_u32,
+        u32
+    );
+    write_primitive!(
+        /// Writes an `int32`.
+        write_i32,
+        i32
+    );
+    write_primitive!(
+        /// Writes a `uint64`.
+        write_u64,
+        u64
+    );
+    write_primitive!(
+        /// Writes an `int64`.
+        write_i64,
+        i64
+    );
+    write_primitive!(
+        /// Writes a `float32`.
+        write_f32,
+        f32
+    );
+    write_primitive!(
+        /// Writes a `float64`.
+        write_f64,
+        f64
+    );
+
+    /// Writes the length prefix of a string or sequence.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the length does not fit in 32 bits.
+    pub fn write_length(&mut self, len: usize) -> Result<(), Error> {
+        let len = u32::try_from(len).map_err(|_err| anyhow!("Length {len} is too large"))?;
+        self.write_u32(len);
+        Ok(())
+    }
+
+    /// Starts the DHEADER that precedes a structure in delimited encapsulations.
+    ///
+    /// # Returns
+    ///
+    /// * `PendingHeader` - The header to complete once the structure is written.
+    pub fn begin_struct(&mut self) -> PendingHeader {
+        let delimited = self.encapsulation.is_delimited();
+        self.begin_header(delimited)
+    }
+
+    /// Writes the placeholder that stands for a structure without fields, as told by
+    /// [`MsgSpec::is_empty_struct`](crate::ros_introspection::MsgSpec::is_empty_struct).
+    pub fn write_empty_struct(&mut self) {
+        self.write_bytes(&[0; EMPTY_STRUCT_SIZE]);
+    }
+
+    /// Starts the DHEADER that precedes an array or sequence of non-primitive elements in XCDR2.
+    ///
+    /// # Returns
+    ///
+    /// * `PendingHeader` - The header to complete once the elements are written.
+    pub fn begin_collection(&mut self) -> PendingHeader {
+        let xcdr2 = self.encapsulation.is_xcdr2();
+        self.begin_header(xcdr2)
+    }
+
+    fn begin_header(&mut self, present: bool) -> PendingHeader {
+        if !present {
+            return PendingHeader(None);
+        }
+        self.write_u32(0);
+        PendingHeader(Some(self.buffer.len()))
+    }
+
+    /// Completes a DHEADER with the size of the data written since it was started.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The header returned by `begin_struct` or `begin_collection`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the size does not fit in 32 bits.
+    #[allow(clippy::needless_pass_by_value)] // Taken by value so it cannot be completed twice
+    pub fn end_header(&mut self, header: PendingHeader) -> Result<(), Error> {
+        let PendingHeader(Some(start)) = header else {
+            return Ok(());
+        };
+
+        let size = self.buffer.len() - start;
+        let size = u32::try_from(size).map_err(|_err| anyhow!("Size {size} is too large"))?;
+        let bytes = if self.little_endian {
+            size.to_le_bytes()
+        } else {
+            size.to_be_bytes()
+        };
+        self.buffer[start - bytes.len()..start].copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    /// Writes a null-terminated `string`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the string is too long.
+    pub fn write_string(&mut self, value: &str) -> Result<(), Error> {
+        self.write_length(value.len() + 1)?;
+        self.write_bytes(value.as_bytes());
+        self.buffer.push(0);
+        Ok(())
+    }
+
+    /// Writes a `wstring`, made of characters of the size set on the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the string is too long.
+    pub fn write_wstring(&mut self, value: &str) -> Result<(), Error> {
+        match self.wchar_size {
+            WcharSize::Two => {
+                self.write_length(value.encode_utf16().count())?;
+                for unit in value.encode_utf16() {
+                    let bytes = if self.little_endian {
+                        unit.to_le_bytes()
+                    } else {
+                        unit.to_be_bytes()
+                    };
+                    self.write_bytes(&bytes);
+                }
+            }
+            WcharSize::Four => {
+                self.write_length(value.chars().count())?;
+                for character in value.chars() {
+                    let bytes = if self.little_endian {
+                        u32::from(character).to_le_bytes()
+                    } else {
+                        u32::from(character).to_be_bytes()
+                    };
+                    self.write_bytes(&bytes);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - The serialized message.
+    pub fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdr::{CdrReader, DecodeLimits};
+
+    #[test]
+    fn test_roundtrip() {
+        for little_endian in [true, false] {
+            let encapsulation = if little_endian {
+                Encapsulation::CdrLe
+            } else {
+                Encapsulation::CdrBe
+            };
+            let mut writer = CdrWriter::new(encapsulation).unwrap();
+            writer.write_u8(7);
+            writer.write_f64(1.5);
+            writer.write_string("hi").unwrap();
+            writer.write_wstring("né").unwrap();
+            writer.write_i16(-3);
+            let buffer = writer.finish();
+
+            let mut reader = CdrReader::new(&buffer).unwrap();
+            assert_eq!(reader.is_little_endian(), little_endian);
+            assert_eq!(reader.read_u8().unwrap(), 7);
+            assert_eq!(reader.offset(), 1);
+            assert_eq!(reader.read_f64().unwrap(), 1.5);
+            assert_eq!(reader.offset(), 16);
+            assert_eq!(reader.read_string().unwrap(), "hi");
+            assert_eq!(reader.read_wstring().unwrap(), "né");
+            assert_eq!(reader.read_i16().unwrap(), -3);
+            assert_eq!(reader.remaining(), 0);
+        }
+    }
+
+    #[test]
+    fn test_dheaders() {
+        let mut writer = CdrWriter::new(Encapsulation::DCdr2Be).unwrap();
+        let header = writer.begin_struct();
+        writer.write_u8(1);
+        writer.write_f64(2.0);
+        writer.end_header(header).unwrap();
+        let header = writer.begin_collection();
+        writer.end_header(header).unwrap();
+        let buffer = writer.finish();
+
+        assert_eq!(&buffer[..4], [0x00, 0x08, 0x00, 0x00]);
+        let mut reader = CdrReader::new(&buffer).unwrap();
+        assert_eq!(reader.read_struct_header().unwrap(), Some(12));
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert_eq!(reader.read_f64().unwrap(), 2.0);
+        assert_eq!(reader.read_collection_header().unwrap(), Some(0));
+
+        let mut writer = CdrWriter::new(Encapsulation::CdrLe).unwrap();
+        let header = writer.begin_struct();
+        writer.end_header(header).unwrap();
+        assert_eq!(writer.offset(), 0);
+
+        assert!(CdrWriter::new(Encapsulation::PlCdr2Le).is_err());
+    }
+    #[test]
+    fn test_wchar_sizes() {
+        for (wchar_size, len) in [(WcharSize::Two, 3_u32), (WcharSize::Four, 2)] {
+            let mut writer = CdrWriter::new(Encapsulation::CdrLe)
+                .unwrap()
+                .with_wchar_size(wchar_size);
+            // The emoji takes two UTF-16 code units
+            writer.write_wstring("é😀").unwrap();
+            let buffer = writer.finish();
+            assert_eq!(buffer[4..8], len.to_le_bytes());
+            assert_eq!(buffer.len(), 8 + len as usize * wchar_size.bytes());
+
+            let limits = DecodeLimits::default().with_wchar_size(wchar_size);
+            let mut reader = CdrReader::with_limits(&buffer, limits).unwrap();
+            assert_eq!(reader.read_wstring().unwrap(), "é😀");
+            assert_eq!(reader.remaining(), 0);
+        }
+    }
+}
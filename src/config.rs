@@ -11,11 +11,44 @@ struct Conversion {
     frame_id: String,
     ros_type: String,
     entity_path: String,
+    #[serde(default)]
+    converter: Option<String>,
+    #[serde(default)]
+    options: toml::Table,
+}
+
+/// The settings of a conversion that change how its messages are logged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConversionSettings {
+    converter: Option<String>,
+    options: toml::Table,
+}
+
+impl ConversionSettings {
+    /// Returns the name of the converter chosen for the conversion.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The name of the converter, or `None` to use the one registered for
+    ///   the ROS type.
+    pub fn converter(&self) -> Option<&str> {
+        self.converter.as_deref()
+    }
+
+    /// Returns the options of the converter, from the `options` table of the conversion.
+    ///
+    /// # Returns
+    ///
+    /// * `&toml::Table` - A reference to the options, which are empty if there is no table.
+    pub fn options(&self) -> &toml::Table {
+        &self.options
+    }
 }
 
 /// Parses and holds conversion configurations.
 pub struct ConfigParser {
     conversions: HashMap<(String, String), (String, String)>,
+    settings: HashMap<(String, String), ConversionSettings>,
 }
 
 impl ConfigParser {
@@ -32,8 +65,9 @@ impl ConfigParser {
     /// - The configuration file contains invalid TOML.
     /// - The configuration file does not contain the expected structure.
     pub fn new(config_file: &str) -> Result<Self> {
-        let conversions = {
+        let (conversions, settings) = {
             let mut conversions = HashMap::new();
+            let mut settings = HashMap::new();
             let config_path = Path::new(config_file);
             let full_path = config_path.canonicalize()?;
 
@@ -45,18 +79,33 @@ impl ConfigParser {
                     (conversion.topic.clone(), conversion.frame_id.clone()),
                     (conversion.ros_type.clone(), conversion.entity_path.clone()),
                 );
+                settings.insert(
+                    (conversion.topic.clone(), conversion.frame_id.clone()),
+                    ConversionSettings {
+                        converter: conversion.converter.clone(),
+                        options: conversion.options.clone(),
+                    },
+                );
             }
 
-            conversions
+            (conversions, settings)
         };
 
-        Ok(Self { conversions })
+        Ok(Self {
+            conversions,
+            settings,
+        })
     }
 
     /// Returns a reference to the conversions hashmap.
     pub fn conversions(&self) -> &HashMap<(String, String), (String, String)> {
         &self.conversions
     }
+
+    /// Returns a reference to the settings of the conversions, keyed like the conversions.
+    pub fn settings(&self) -> &HashMap<(String, String), ConversionSettings> {
+        &self.settings
+    }
 }
 
 #[cfg(test)]
@@ -107,6 +156,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_config_parser_settings() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("config.toml");
+
+        let mut file = File::create(&file_path).unwrap();
+        writeln!(
+            file,
+            r#"
+            [[conversion]]
+            topic = "topic1"
+            frame_id = "frame1"
+            ros_type = "type1"
+            entity_path = "foo/bar1"
+            converter = "text_document"
+            [[conversion]]
+            topic = "topic2"
+            frame_id = "frame2"
+            ros_type = "type2"
+            entity_path = "foo/bar2"
+            [conversion.options]
+            depth_meter = 1000.0
+            "#
+        )
+        .unwrap();
+
+        let config_parser = ConfigParser::new(file_path.to_str().unwrap()).unwrap();
+
+        let settings = config_parser.settings();
+        assert_eq!(settings.len(), 2);
+        assert_eq!(
+            settings[&("topic1".to_owned(), "frame1".to_owned())].converter(),
+            Some("text_document")
+        );
+        assert!(settings[&("topic1".to_owned(), "frame1".to_owned())]
+            .options()
+            .is_empty());
+
+        let settings = &settings[&("topic2".to_owned(), "frame2".to_owned())];
+        assert_eq!(settings.converter(), None);
+        assert_eq!(
+            settings.options().get("depth_meter"),
+            Some(&toml::Value::Float(1000.0))
+        );
+    }
+
     #[test]
     fn test_config_parser_new_invalid_file() {
         // Create a temporary directory
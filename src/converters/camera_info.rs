@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Error, Result};
+
+use crate::converters::{
+    normalize_type_name, CdrMessage, CompressedImageConverter, ConversionContext, Converter,
+    ImageConverter,
+};
+use crate::value::Value;
+
+/// The child of the entity path of a camera the distortion model is logged at.
+const DISTORTION_ENTITY: &str = "distortion";
+
+/// Logs `sensor_msgs/CameraInfo` messages as a pinhole camera, which projects the images logged
+/// at the same entity path into the 3D frame of the camera.
+///
+/// The distortion model and its coefficients, which rerun does not apply, are kept as a text
+/// document.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CameraInfoConverter;
+
+impl CameraInfoConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "pinhole";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/CameraInfo"];
+}
+
+impl Converter for CameraInfoConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        let entity_path = context.entity_path();
+        stream.log(entity_path, &pinhole(value)?)?;
+        stream.log(
+            format!("{entity_path}/{DISTORTION_ENTITY}"),
+            &distortion(value)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Returns the entity path a camera info conversion should be logged at, which is the one of an
+/// image conversion with the same frame ID, so that the pinhole projects its images.
+///
+/// # Arguments
+///
+/// * `conversions` - The conversions of the configuration, keyed by topic and frame ID, with
+///   their ROS type and entity path.
+/// * `converter` - The name of the converter the conversion resolves to, which may have been
+///   chosen in the configuration instead of the one registered for its type.
+/// * `frame_id` - The frame ID of the conversion.
+///
+/// # Returns
+///
+/// * `Option<&str>` - The entity path of the image conversion, the first one in order if there
+///   are several, or `None` if the conversion is not logged by the [`CameraInfoConverter`] or
+///   no image shares its frame.
+pub fn camera_entity_path<'a>(
+    conversions: &'a HashMap<(String, String), (String, String)>,
+    converter: &str,
+    frame_id: &str,
+) -> Option<&'a str> {
+    if converter != CameraInfoConverter::NAME {
+        return None;
+    }
+
+    let is_any_of = |ros_type: &str, ros_types: &[&str]| {
+        let ros_type = normalize_type_name(ros_type);
+        ros_types
+            .iter()
+            .any(|candidate| normalize_type_name(candidate) == ros_type)
+    };
+    let image_types: Vec<_> = ImageConverter::ROS_TYPES
+        .into_iter()
+        .chain(CompressedImageConverter::ROS_TYPES)
+        .collect();
+    conversions
+        .iter()
+        .filter(|((_, image_frame_id), (image_type, _))| {
+            image_frame_id == frame_id && is_any_of(image_type, &image_types)
+        })
+        .map(|(_, (_, entity_path))| entity_path.as_str())
+        .min()
+}
+
+/// Reads a field of a camera info, named in lowercase in ROS 2 and in uppercase in ROS 1.
+fn matrix_field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, Error> {
+    value.get(name).or_else(|_| value.get(&name.to_uppercase()))
+}
+
+/// Converts a `sensor_msgs/CameraInfo` message into a pinhole built from its intrinsic matrix
+/// `K` and its resolution.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn pinhole(value: &Value) -> Result<rerun::Pinhole, Error> {
+    let k = matrix_field(value, "k")?.to_f64_vec()?;
+    let k: [f64; 9] = k
+        .try_into()
+        .map_err(|k: Vec<f64>| anyhow!("Expected 9 elements in K, found {}", k.len()))?;
+    if k[0] == 0.0 || k[4] == 0.0 {
+        return Err(anyhow!(
+            "The camera is not calibrated, its focal lengths are zero"
+        ));
+    }
+    let width = value.get_u64("width")?;
+    let height = value.get_u64("height")?;
+
+    // K is stored in row-major order, while rerun takes the columns of the matrix
+    let k = k.map(|element| element as f32);
+    let columns = [[k[0], k[3], k[6]], [k[1], k[4], k[7]], [k[2], k[5], k[8]]];
+    Ok(rerun::Pinhole::new(rerun::datatypes::Mat3x3::from(columns))
+        .with_resolution([width as f32, height as f32]))
+}
+
+/// Writes the distortion model and the `D` coefficients of a `sensor_msgs/CameraInfo` message
+/// into a text document.
+fn distortion(value: &Value) -> Result<rerun::TextDocument, Error> {
+    let model = value.get_str("distortion_model")?;
+    let coefficients = matrix_field(value, "d")?
+        .to_f64_vec()?
+        .iter()
+        .map(f64::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    Ok(rerun::TextDocument::new(format!(
+        "distortion_model: {model}\nd: [{coefficients}]\n"
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+    use crate::converters::TextDocumentConverter;
+
+    #[test]
+    fn test_pinhole() {
+        let value = TestMessage::new(
+            "sensor_msgs/msg/CameraInfo",
+            &json!({
+                "height": 480,
+                "width": 640,
+                "distortion_model": "plumb_bob",
+                "d": [-0.25, 0.5, 0.0, 0.0, 0.0],
+                "k": [500.0, 0.0, 320.0, 0.0, 505.0, 240.0, 0.0, 0.0, 1.0],
+            }),
+        )
+        .value();
+        assert_eq!(
+            pinhole(&value).unwrap(),
+            rerun::Pinhole::new(rerun::datatypes::Mat3x3::from([
+                [500.0, 0.0, 0.0],
+                [0.0, 505.0, 0.0],
+                [320.0, 240.0, 1.0],
+            ]))
+            .with_resolution([640.0, 480.0])
+        );
+        assert_eq!(
+            distortion(&value).unwrap(),
+            rerun::TextDocument::new("distortion_model: plumb_bob\nd: [-0.25, 0.5, 0, 0, 0]\n")
+        );
+
+        // The matrices of an uncalibrated camera are left to zero
+        let value = TestMessage::new(
+            "sensor_msgs/msg/CameraInfo",
+            &json!({"height": 480, "width": 640}),
+        )
+        .value();
+        assert_eq!(
+            pinhole(&value).err().unwrap().to_string(),
+            "The camera is not calibrated, its focal lengths are zero"
+        );
+    }
+
+    #[test]
+    fn test_camera_entity_path() {
+        let conversions: HashMap<_, _> = [
+            (
+                ("camera/info", "camera"),
+                ("sensor_msgs/msg/CameraInfo", "camera/info"),
+            ),
+            (
+                ("camera/image", "camera"),
+                ("sensor_msgs/Image", "camera/image"),
+            ),
+            (
+                ("depth/info", "depth"),
+                ("sensor_msgs/CameraInfo", "depth/info"),
+            ),
+            (
+                ("camera/points", "camera"),
+                ("sensor_msgs/msg/PointCloud2", "camera/points"),
+            ),
+        ]
+        .into_iter()
+        .map(|((topic, frame_id), (ros_type, entity_path))| {
+            (
+                (topic.to_owned(), frame_id.to_owned()),
+                (ros_type.to_owned(), entity_path.to_owned()),
+            )
+        })
+        .collect();
+
+        assert_eq!(
+            camera_entity_path(&conversions, CameraInfoConverter::NAME, "camera"),
+            Some("camera/image")
+        );
+        assert_eq!(
+            camera_entity_path(&conversions, CameraInfoConverter::NAME, "depth"),
+            None
+        );
+        // A camera info the configuration logs with another converter stays at its own path
+        assert_eq!(
+            camera_entity_path(&conversions, TextDocumentConverter::NAME, "camera"),
+            None
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use serde::Deserialize;
+
+/// A colormap, which turns scalars such as intensities into colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Colormap {
+    /// From black to white.
+    Grayscale,
+
+    /// A rainbow from dark blue to dark red, with a perceptually smooth lightness.
+    #[default]
+    Turbo,
+
+    /// From dark purple to yellow, perceptually uniform.
+    Viridis,
+}
+
+impl Colormap {
+    /// Returns the color of a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value, between 0 and 1. Values out of range are clamped, and values that
+    ///   are not finite get the color of 0.
+    ///
+    /// # Returns
+    ///
+    /// * `rerun::Color` - The color of the value.
+    pub fn color(self, value: f64) -> rerun::Color {
+        let t = if value.is_finite() {
+            value.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        // Polynomial fits of the colormaps, in the Horner form
+        let [r, g, b] = match self {
+            Self::Grayscale => [t, t, t],
+            Self::Turbo => [
+                polynomial(
+                    t,
+                    &[
+                        0.135_721_38,
+                        4.615_392_6,
+                        -42.660_322_58,
+                        132.131_082_34,
+                        -152.942_393_96,
+                        59.286_379_43,
+                    ],
+                ),
+                polynomial(
+                    t,
+                    &[
+                        0.091_402_61,
+                        2.194_188_39,
+                        4.842_966_58,
+                        -14.185_033_33,
+                        4.277_298_57,
+                        2.829_566_04,
+                    ],
+                ),
+                polynomial(
+                    t,
+                    &[
+                        0.106_673_3,
+                        12.641_946_08,
+                        -60.582_048_36,
+                        110.362_767_71,
+                        -89.903_109_12,
+                        27.348_249_73,
+                    ],
+                ),
+            ],
+            Self::Viridis => [
+                polynomial(
+                    t,
+                    &[
+                        0.277_727_327_223_417_7,
+                        0.105_093_043_108_577_4,
+                        -0.330_861_828_725_556_3,
+                        -4.634_230_498_983_486,
+                        6.228_269_936_347_081,
+                        4.776_384_997_670_288,
+                        -5.435_455_855_934_631,
+                    ],
+                ),
+                polynomial(
+                    t,
+                    &[
+                        0.005_407_344_544_966_578,
+                        1.404_613_529_898_575,
+                        0.214_847_559_468_213,
+                        -5.799_100_973_351_585,
+                        14.179_933_366_805_09,
+                        -13.745_145_377_746_01,
+                        4.645_852_612_178_535,
+                    ],
+                ),
+                polynomial(
+                    t,
+                    &[
+                        0.334_099_805_335_306_1,
+                        1.384_590_162_594_685,
+                        0.095_095_163_028_236_59,
+                        -19.332_440_956_279_87,
+                        56.690_552_600_681_05,
+                        -65.353_032_633_372_34,
+                        26.312_435_249_583_2,
+                    ],
+                ),
+            ],
+        };
+        rerun::Color::from_rgb(unit_to_u8(r), unit_to_u8(g), unit_to_u8(b))
+    }
+
+    /// Returns the colors of values, scaled from a range into the colormap.
+    ///
+    /// # Arguments
+    ///
+    /// * `values` - The values to color.
+    /// * `range` - The values mapped to both ends of the colormap, or `None` for the smallest
+    ///   and largest finite values.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<rerun::Color>` - The colors of the values, in order.
+    pub fn colors(self, values: &[f64], range: Option<[f64; 2]>) -> Vec<rerun::Color> {
+        let [min, max] = range.unwrap_or_else(|| {
+            values
+                .iter()
+                .filter(|value| value.is_finite())
+                .fold([f64::INFINITY, f64::NEG_INFINITY], |[min, max], value| {
+                    [min.min(*value), max.max(*value)]
+                })
+        });
+        let span = max - min;
+        values
+            .iter()
+            .map(|value| {
+                if span > 0.0 {
+                    self.color((value - min) / span)
+                } else {
+                    self.color(0.0)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Evaluates a polynomial, whose coefficients are in increasing order of degree.
+fn polynomial(t: f64, coefficients: &[f64]) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0.0, |result, coefficient| result * t + coefficient)
+}
+
+/// Converts a color channel from `[0, 1]` into `[0, 255]`, clamping values out of range.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn unit_to_u8(channel: f64) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colors() {
+        assert_eq!(
+            Colormap::Grayscale.colors(&[1.0, 3.0, 2.0, f64::NAN], None),
+            [
+                rerun::Color::from_rgb(0, 0, 0),
+                rerun::Color::from_rgb(255, 255, 255),
+                rerun::Color::from_rgb(128, 128, 128),
+                rerun::Color::from_rgb(0, 0, 0),
+            ]
+        );
+        assert_eq!(
+            Colormap::Grayscale.colors(&[5.0, 15.0], Some([0.0, 10.0])),
+            [
+                rerun::Color::from_rgb(128, 128, 128),
+                rerun::Color::from_rgb(255, 255, 255),
+            ]
+        );
+
+        // The ends of the fitted colormaps are within a few units of the reference ones, such as
+        // (253, 231, 37) for the end of viridis
+        assert_eq!(
+            Colormap::Turbo.color(0.0),
+            rerun::Color::from_rgb(35, 23, 27)
+        );
+        assert_eq!(
+            Colormap::Viridis.color(1.0),
+            rerun::Color::from_rgb(252, 231, 33)
+        );
+    }
+}
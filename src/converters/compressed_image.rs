@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error, Result};
+use rerun::ChannelDatatype;
+
+use crate::cdr::MessageView;
+use crate::converters::{
+    parse_options, CdrMessage, ConversionContext, Converter, ImageArchetype, ImageOptions,
+};
+
+/// The signature at the start of JPEG files.
+const JPEG_SIGNATURE: [u8; 3] = [0xFF, 0xD8, 0xFF];
+
+/// The signature at the start of PNG files.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The size of the header `compressed_depth_image_transport` writes before the PNG data: the
+/// compression format as an `int32`, then the two `float32` depth quantization parameters.
+const COMPRESSED_DEPTH_HEADER_SIZE: usize = 12;
+
+/// Logs `sensor_msgs/CompressedImage` messages as encoded images, or as depth images for the
+/// `compressedDepth` format of `compressed_depth_image_transport`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompressedImageConverter {
+    options: ImageOptions,
+}
+
+impl CompressedImageConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "compressed_image";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/CompressedImage"];
+
+    /// Creates a new `CompressedImageConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `ImageOptions` of the converter, whose depth scale applies to the
+    ///   `compressedDepth` format.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `CompressedImageConverter` instance.
+    pub fn new(options: ImageOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Converter for CompressedImageConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        compressed_image(&message.view()?, &self.options)?.log(stream, context.entity_path())
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(parse_options(options)?)))
+    }
+}
+
+/// Converts a `sensor_msgs/CompressedImage` message into an encoded image or a depth image.
+fn compressed_image(
+    view: &MessageView<'_>,
+    options: &ImageOptions,
+) -> Result<ImageArchetype, Error> {
+    let format = view.field("format")?.decode()?;
+    let format = format.as_str()?;
+    let data = view.field("data")?.bytes()?;
+
+    if format.contains("compressedDepth") {
+        return compressed_depth(format, data, options);
+    }
+
+    // The format is free text, such as `jpeg` or `bgr8; png compressed bgr8`, hence the data
+    // is identified by its signature instead
+    let media_type = if data.starts_with(&JPEG_SIGNATURE) {
+        rerun::MediaType::jpeg()
+    } else if data.starts_with(&PNG_SIGNATURE) {
+        rerun::MediaType::png()
+    } else {
+        return Err(anyhow!(
+            "Unsupported compressed image format {format:?}, expected JPEG or PNG data"
+        ));
+    };
+    Ok(ImageArchetype::Encoded(
+        rerun::EncodedImage::from_file_contents(data.to_vec()).with_media_type(media_type),
+    ))
+}
+
+/// Decodes the `compressedDepth` format, such as `16UC1; compressedDepth png`, into a depth
+/// image.
+///
+/// `16UC1` images are stored as is, while `32FC1` images are stored as inverse depths quantized
+/// with the parameters of the header, zero standing for an invalid depth.
+fn compressed_depth(
+    format: &str,
+    data: &[u8],
+    options: &ImageOptions,
+) -> Result<ImageArchetype, Error> {
+    if data.len() < COMPRESSED_DEPTH_HEADER_SIZE {
+        return Err(anyhow!(
+            "The compressedDepth data of {} bytes is shorter than its header",
+            data.len()
+        ));
+    }
+    let (header, png_data) = data.split_at(COMPRESSED_DEPTH_HEADER_SIZE);
+    if !png_data.starts_with(&PNG_SIGNATURE) {
+        return Err(anyhow!(
+            "Unsupported compressedDepth format {format:?}, expected PNG data"
+        ));
+    }
+    let (resolution, samples) = decode_gray16_png(png_data)?;
+
+    let encoding = format.split(';').next().unwrap_or_default().trim();
+    let image = match encoding {
+        "16UC1" => rerun::DepthImage::from_data_type_and_bytes(
+            samples
+                .iter()
+                .flat_map(|sample| sample.to_le_bytes())
+                .collect::<Vec<_>>(),
+            resolution,
+            ChannelDatatype::U16,
+        )
+        .with_meter(options.depth_meter().unwrap_or(1000.0)),
+        "32FC1" => {
+            let depth_quant_a = f32::from_le_bytes(header[4..8].try_into()?);
+            let depth_quant_b = f32::from_le_bytes(header[8..12].try_into()?);
+            rerun::DepthImage::from_data_type_and_bytes(
+                samples
+                    .iter()
+                    .map(|sample| match sample {
+                        0 => f32::NAN,
+                        _ => depth_quant_a / (f32::from(*sample) - depth_quant_b),
+                    })
+                    .flat_map(f32::to_le_bytes)
+                    .collect::<Vec<_>>(),
+                resolution,
+                ChannelDatatype::F32,
+            )
+            .with_meter(options.depth_meter().unwrap_or(1.0))
+        }
+        _ => {
+            return Err(anyhow!(
+                "Unsupported compressedDepth encoding {encoding:?}, expected 16UC1 or 32FC1"
+            ))
+        }
+    };
+    Ok(ImageArchetype::Depth(image))
+}
+
+/// Decodes a 16-bit grayscale PNG image into its resolution and samples.
+fn decode_gray16_png(data: &[u8]) -> Result<([u32; 2], Vec<u16>), Error> {
+    let mut reader = png::Decoder::new(data).read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    if info.color_type != png::ColorType::Grayscale || info.bit_depth != png::BitDepth::Sixteen {
+        return Err(anyhow!(
+            "Expected a 16-bit grayscale PNG image, found {:?} with {} bits",
+            info.color_type,
+            info.bit_depth as u8
+        ));
+    }
+    buffer.truncate(info.buffer_size());
+
+    // PNG stores the samples in big-endian byte order
+    let samples = buffer
+        .chunks_exact(2)
+        .map(|sample| u16::from_be_bytes([sample[0], sample[1]]))
+        .collect();
+    Ok(([info.width, info.height], samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    /// Writes a `compressedDepth` message of a 2x1 image with the given samples.
+    fn compressed_depth_data(samples: [u16; 2], depth_quant_a: f32, depth_quant_b: f32) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0i32.to_le_bytes());
+        data.extend_from_slice(&depth_quant_a.to_le_bytes());
+        data.extend_from_slice(&depth_quant_b.to_le_bytes());
+
+        let mut encoder = png::Encoder::new(&mut data, 2, 1);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Sixteen);
+        let mut writer = encoder.write_header().unwrap();
+        let pixels: Vec<u8> = samples.iter().flat_map(|s| s.to_be_bytes()).collect();
+        writer.write_image_data(&pixels).unwrap();
+        writer.finish().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_encoded_image() {
+        let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0x00];
+        let message = TestMessage::new(
+            "sensor_msgs/msg/CompressedImage",
+            &json!({
+                "format": "bgr8; jpeg compressed bgr8",
+                "data": jpeg,
+            }),
+        );
+        assert_eq!(
+            compressed_image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Encoded(
+                rerun::EncodedImage::from_file_contents(jpeg)
+                    .with_media_type(rerun::MediaType::jpeg())
+            )
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/CompressedImage",
+            &json!({
+                "format": "tiff",
+                "data": vec![b'I', b'I', 42, 0],
+            }),
+        );
+        assert_eq!(
+            compressed_image(&message.view(), &ImageOptions::default())
+                .err()
+                .unwrap()
+                .to_string(),
+            "Unsupported compressed image format \"tiff\", expected JPEG or PNG data"
+        );
+    }
+
+    #[test]
+    fn test_compressed_depth() {
+        let data = compressed_depth_data([0x0102, 0], 0.0, 0.0);
+        let message = TestMessage::new(
+            "sensor_msgs/msg/CompressedImage",
+            &json!({
+                "format": "16UC1; compressedDepth png",
+                "data": data,
+            }),
+        );
+        assert_eq!(
+            compressed_image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Depth(
+                rerun::DepthImage::from_data_type_and_bytes(
+                    vec![0x02, 0x01, 0, 0],
+                    [2, 1],
+                    ChannelDatatype::U16
+                )
+                .with_meter(1000.0)
+            )
+        );
+
+        let data = compressed_depth_data([258, 0], 100.0, 2.0);
+        let message = TestMessage::new(
+            "sensor_msgs/msg/CompressedImage",
+            &json!({
+                "format": "32FC1; compressedDepth",
+                "data": data,
+            }),
+        );
+        let depths: Vec<u8> = [0.390_625, f32::NAN]
+            .into_iter()
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        assert_eq!(
+            compressed_image(&message.view(), &ImageOptions::new(Some(0.5))).unwrap(),
+            ImageArchetype::Depth(
+                rerun::DepthImage::from_data_type_and_bytes(depths, [2, 1], ChannelDatatype::F32)
+                    .with_meter(0.5)
+            )
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/CompressedImage",
+            &json!({
+                "format": "16UC1; compressedDepth rvl",
+                "data": vec![0; 16],
+            }),
+        );
+        assert_eq!(
+            compressed_image(&message.view(), &ImageOptions::default())
+                .err()
+                .unwrap()
+                .to_string(),
+            "Unsupported compressedDepth format \"16UC1; compressedDepth rvl\", expected PNG data"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! Test messages serialized from the full definitions of their types under `tests/fixtures`, so
+//! that the converters are tested on the values the decoder produces for real messages.
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value as Json;
+
+use crate::cdr::{encode, DecodePlan, MessageView, Projection};
+use crate::converters::CdrMessage;
+use crate::ros_introspection::{BuiltinType, Dialect, Field, MsgSpec};
+use crate::value::Value;
+
+/// The directories holding full message definitions, named `<package>__<Name>.msg`.
+const DEFINITION_DIRECTORIES: [&str; 2] = ["tests/fixtures/msg", "tests/fixtures/cdr"];
+
+/// A message serialized in CDR, along with the specification of its type.
+pub(crate) struct TestMessage {
+    spec: MsgSpec,
+    plan: DecodePlan,
+    buffer: Vec<u8>,
+}
+
+impl TestMessage {
+    /// Serializes a message given as a JSON object.
+    ///
+    /// Missing fields take the default value of their type, and floats may also be written as
+    /// strings such as `"inf"` or `"NaN"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type has no definition under `tests/fixtures`, or if the JSON object has
+    /// a field the type does not have.
+    pub(crate) fn new(ros_type: &str, fields: &Json) -> Self {
+        let spec = definition_spec(ros_type);
+        let value = message(&spec, fields);
+        let buffer = encode(&spec, &value).unwrap();
+        let plan = DecodePlan::new(&spec).unwrap();
+        Self { spec, plan, buffer }
+    }
+
+    /// Returns the specification of the type of the message.
+    pub(crate) fn spec(&self) -> &MsgSpec {
+        &self.spec
+    }
+
+    /// Returns the message as handed to a converter.
+    pub(crate) fn message(&self) -> CdrMessage<'_> {
+        CdrMessage::new(&self.spec, &self.plan, &self.buffer)
+    }
+
+    /// Returns a view over the message.
+    pub(crate) fn view(&self) -> MessageView<'_> {
+        self.message().view().unwrap()
+    }
+
+    /// Decodes the message.
+    pub(crate) fn value(&self) -> Value {
+        self.message().decode().unwrap()
+    }
+
+    /// Decodes the fields of the message at the given paths, as for a converter declaring them
+    /// in its `field_paths`.
+    pub(crate) fn projected_value<S: AsRef<str>>(&self, paths: &[S]) -> Value {
+        let projection = Projection::new(&self.spec, paths).unwrap();
+        CdrMessage::new_with_projection(&self.spec, &self.plan, Some(&projection), &self.buffer)
+            .decode()
+            .unwrap()
+    }
+}
+
+fn definition_spec(ros_type: &str) -> MsgSpec {
+    let package = ros_type.split('/').next().unwrap();
+    let name = ros_type.rsplit('/').next().unwrap();
+    let file_name = format!("{package}__{name}.msg");
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let path = DEFINITION_DIRECTORIES
+        .iter()
+        .map(|directory| root.join(directory).join(&file_name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| panic!("No definition of {ros_type} under tests/fixtures"));
+    let definitions = fs::read_to_string(path).unwrap();
+    MsgSpec::new_from_definitions(ros_type, &definitions, Dialect::Ros2).unwrap()
+}
+
+fn message(spec: &MsgSpec, json: &Json) -> Value {
+    for name in json.as_object().into_iter().flat_map(serde_json::Map::keys) {
+        assert!(
+            spec.serialized_fields()
+                .any(|(field, _)| field.name() == name),
+            "{} has no field {name}",
+            spec.data().type_().name()
+        );
+    }
+    Value::Message(
+        spec.serialized_fields()
+            .map(|(field, child)| {
+                let value = field_value(field, child, &json[field.name()]);
+                (field.name().to_owned(), value)
+            })
+            .collect(),
+    )
+}
+
+fn field_value(field: &Field, child: Option<&MsgSpec>, json: &Json) -> Value {
+    let id = field.type_().id();
+    if !field.is_array() {
+        return single(id, child, json);
+    }
+
+    let defaults;
+    let elements = match (json.as_array(), usize::try_from(field.array_size())) {
+        (Some(elements), _) => elements,
+        (None, Ok(size)) => {
+            defaults = vec![Json::Null; size];
+            &defaults
+        }
+        (None, Err(_)) => return Value::Array(Vec::new()),
+    };
+    Value::Array(
+        elements
+            .iter()
+            .map(|element| single(id, child, element))
+            .collect(),
+    )
+}
+
+fn single(id: &BuiltinType, child: Option<&MsgSpec>, json: &Json) -> Value {
+    match id {
+        BuiltinType::Bool => Value::Bool(json.as_bool().unwrap_or_default()),
+        BuiltinType::Float32 | BuiltinType::Float64 => Value::Float64(match json {
+            Json::String(special) => special.parse().unwrap(),
+            _ => json.as_f64().unwrap_or_default(),
+        }),
+        BuiltinType::Uint64 => Value::Uint64(json.as_u64().unwrap_or_default()),
+        BuiltinType::String | BuiltinType::WString => {
+            Value::String(json.as_str().unwrap_or_default().to_owned())
+        }
+        BuiltinType::Other => message(child.unwrap(), json),
+        _ => Value::Int64(json.as_i64().unwrap_or_default()),
+    }
+}
@@ -0,0 +1,539 @@
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error, Result};
+use rerun::{ChannelDatatype, ColorModel, PixelFormat};
+use serde::Deserialize;
+
+use crate::cdr::MessageView;
+use crate::converters::{parse_options, CdrMessage, ConversionContext, Converter};
+
+/// The options of the image converters, from the options table of a conversion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageOptions {
+    depth_meter: Option<f32>,
+}
+
+impl ImageOptions {
+    /// Creates a new `ImageOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth_meter` - How many units of depth make a meter, or `None` for the default of
+    ///   the encoding: 1000 for `16UC1` millimeters and 1 for `32FC1` meters.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ImageOptions` instance.
+    pub fn new(depth_meter: Option<f32>) -> Self {
+        Self { depth_meter }
+    }
+
+    /// Returns how many units of depth make a meter.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f32>` - The units per meter, or `None` for the default of the encoding.
+    pub fn depth_meter(&self) -> Option<f32> {
+        self.depth_meter
+    }
+}
+
+/// Logs `sensor_msgs/Image` messages as images, or as depth images for the depth encodings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImageConverter {
+    options: ImageOptions,
+}
+
+impl ImageConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "image";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/Image"];
+
+    /// Creates a new `ImageConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `ImageOptions` of the converter.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ImageConverter` instance.
+    pub fn new(options: ImageOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Converter for ImageConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        image(&message.view()?, &self.options)?.log(stream, context.entity_path())
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(parse_options(options)?)))
+    }
+}
+
+/// An image converted from ROS, which is either in color, in depth, or still encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ImageArchetype {
+    Color(rerun::Image),
+    Depth(rerun::DepthImage),
+    Encoded(rerun::EncodedImage),
+}
+
+impl ImageArchetype {
+    /// Logs the image at an entity path.
+    pub(crate) fn log(&self, stream: &rerun::RecordingStream, entity_path: &str) -> Result<()> {
+        match self {
+            Self::Color(image) => stream.log(entity_path, image)?,
+            Self::Depth(image) => stream.log(entity_path, image)?,
+            Self::Encoded(image) => stream.log(entity_path, image)?,
+        }
+        Ok(())
+    }
+}
+
+/// The order of the colors in the 2x2 blocks of a Bayer filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BayerPattern {
+    Rggb,
+    Bggr,
+    Gbrg,
+    Grbg,
+}
+
+impl BayerPattern {
+    /// Returns the position of the red pixel in a block, the blue one being on the opposite
+    /// corner and the green ones on the other two.
+    fn red(self) -> (usize, usize) {
+        match self {
+            Self::Rggb => (0, 0),
+            Self::Bggr => (1, 1),
+            Self::Gbrg => (0, 1),
+            Self::Grbg => (1, 0),
+        }
+    }
+}
+
+/// The layout of the pixels of an encoding from `sensor_msgs/image_encodings`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Color(ColorModel, ChannelDatatype),
+    /// A depth image, and how many of its units make a meter by default.
+    Depth(ChannelDatatype, f32),
+    Bayer(BayerPattern, ChannelDatatype),
+    /// YUV 4:2:2, with the chroma first in each pair of pixels if `uyvy`.
+    Yuv422 {
+        uyvy: bool,
+    },
+}
+
+impl Encoding {
+    fn new(encoding: &str) -> Result<Self, Error> {
+        use ChannelDatatype::{F32, U16, U8};
+        use ColorModel::{BGR, BGRA, L, RGB, RGBA};
+
+        Ok(match encoding {
+            "rgb8" => Self::Color(RGB, U8),
+            "rgba8" => Self::Color(RGBA, U8),
+            "bgr8" | "8UC3" => Self::Color(BGR, U8),
+            "bgra8" | "8UC4" => Self::Color(BGRA, U8),
+            "mono8" | "8UC1" => Self::Color(L, U8),
+            "rgb16" => Self::Color(RGB, U16),
+            "rgba16" => Self::Color(RGBA, U16),
+            "bgr16" | "16UC3" => Self::Color(BGR, U16),
+            "bgra16" | "16UC4" => Self::Color(BGRA, U16),
+            "mono16" => Self::Color(L, U16),
+            "16UC1" => Self::Depth(U16, 1000.0),
+            "32FC1" => Self::Depth(F32, 1.0),
+            "bayer_rggb8" => Self::Bayer(BayerPattern::Rggb, U8),
+            "bayer_bggr8" => Self::Bayer(BayerPattern::Bggr, U8),
+            "bayer_gbrg8" => Self::Bayer(BayerPattern::Gbrg, U8),
+            "bayer_grbg8" => Self::Bayer(BayerPattern::Grbg, U8),
+            "bayer_rggb16" => Self::Bayer(BayerPattern::Rggb, U16),
+            "bayer_bggr16" => Self::Bayer(BayerPattern::Bggr, U16),
+            "bayer_gbrg16" => Self::Bayer(BayerPattern::Gbrg, U16),
+            "bayer_grbg16" => Self::Bayer(BayerPattern::Grbg, U16),
+            "yuv422" | "uyvy" => Self::Yuv422 { uyvy: true },
+            "yuv422_yuy2" | "yuyv" => Self::Yuv422 { uyvy: false },
+            _ => return Err(anyhow!("Unsupported image encoding {encoding:?}")),
+        })
+    }
+
+    /// Returns the size of a channel, which is the unit swapped for big-endian data.
+    fn channel_size(self) -> usize {
+        match self {
+            Self::Color(_, datatype) | Self::Depth(datatype, _) | Self::Bayer(_, datatype) => {
+                datatype_size(datatype)
+            }
+            Self::Yuv422 { .. } => 1,
+        }
+    }
+
+    fn pixel_size(self) -> usize {
+        match self {
+            Self::Color(color_model, datatype) => {
+                let channels = match color_model {
+                    ColorModel::L => 1,
+                    ColorModel::RGB | ColorModel::BGR => 3,
+                    ColorModel::RGBA | ColorModel::BGRA => 4,
+                };
+                channels * datatype_size(datatype)
+            }
+            Self::Depth(datatype, _) | Self::Bayer(_, datatype) => datatype_size(datatype),
+            Self::Yuv422 { .. } => 2,
+        }
+    }
+}
+
+fn datatype_size(datatype: ChannelDatatype) -> usize {
+    match datatype {
+        ChannelDatatype::U8 | ChannelDatatype::I8 => 1,
+        ChannelDatatype::U16 | ChannelDatatype::I16 | ChannelDatatype::F16 => 2,
+        ChannelDatatype::U32 | ChannelDatatype::I32 | ChannelDatatype::F32 => 4,
+        ChannelDatatype::U64 | ChannelDatatype::I64 | ChannelDatatype::F64 => 8,
+    }
+}
+
+/// Converts a `sensor_msgs/Image` message into an image or a depth image.
+///
+/// The pixels are read from the serialized message and copied once, into the image.
+fn image(view: &MessageView<'_>, options: &ImageOptions) -> Result<ImageArchetype, Error> {
+    let width = u32::try_from(view.field("width")?.decode()?.as_u64()?)?;
+    let height = u32::try_from(view.field("height")?.decode()?.as_u64()?)?;
+    let encoding = Encoding::new(view.field("encoding")?.decode()?.as_str()?)?;
+    let is_bigendian = view.field("is_bigendian")?.decode()?.as_bool()?;
+    let step = usize::try_from(view.field("step")?.decode()?.as_u64()?)?;
+    let data = view.field("data")?.bytes()?;
+
+    let row_size = usize::try_from(width)? * encoding.pixel_size();
+    let mut pixels = packed_rows(data, usize::try_from(height)?, step, row_size)?;
+    // Rerun reads the channels in little-endian byte order
+    if is_bigendian && encoding.channel_size() > 1 {
+        for channel in pixels.to_mut().chunks_exact_mut(encoding.channel_size()) {
+            channel.reverse();
+        }
+    }
+
+    let resolution = [width, height];
+    Ok(match encoding {
+        Encoding::Color(color_model, datatype) => {
+            ImageArchetype::Color(rerun::Image::from_color_model_and_bytes(
+                pixels.into_owned(),
+                resolution,
+                color_model,
+                datatype,
+            ))
+        }
+        Encoding::Depth(datatype, default_meter) => ImageArchetype::Depth(
+            rerun::DepthImage::from_data_type_and_bytes(pixels.into_owned(), resolution, datatype)
+                .with_meter(options.depth_meter().unwrap_or(default_meter)),
+        ),
+        Encoding::Bayer(pattern, datatype) => {
+            let rgb = demosaic(
+                &pixels,
+                usize::try_from(width)?,
+                usize::try_from(height)?,
+                pattern,
+                datatype_size(datatype),
+            )?;
+            ImageArchetype::Color(rerun::Image::from_color_model_and_bytes(
+                rgb,
+                resolution,
+                ColorModel::RGB,
+                datatype,
+            ))
+        }
+        Encoding::Yuv422 { uyvy } => {
+            if width % 2 != 0 {
+                return Err(anyhow!(
+                    "YUV 4:2:2 images must have an even width, not {width}"
+                ));
+            }
+            // Rerun only knows the YUY2 order, which swaps each chroma with its luma
+            if uyvy {
+                for pair in pixels.to_mut().chunks_exact_mut(2) {
+                    pair.swap(0, 1);
+                }
+            }
+            ImageArchetype::Color(rerun::Image::from_pixel_format(
+                resolution,
+                PixelFormat::YUY2,
+                pixels.into_owned(),
+            ))
+        }
+    })
+}
+
+/// Returns the rows of an image without the padding at the end of each of them, which are only
+/// copied if there is padding to remove.
+///
+/// # Errors
+///
+/// This function will return an error if the step is smaller than a row, or if the data is too
+/// short for the rows.
+pub(crate) fn packed_rows(
+    data: &[u8],
+    height: usize,
+    step: usize,
+    row_size: usize,
+) -> Result<Cow<'_, [u8]>, Error> {
+    if step < row_size {
+        return Err(anyhow!(
+            "The step of {step} bytes is smaller than a row of {row_size} bytes"
+        ));
+    }
+    if height == 0 || row_size == 0 {
+        return Ok(Cow::Borrowed(&[]));
+    }
+
+    let size = step
+        .checked_mul(height - 1)
+        .and_then(|size| size.checked_add(row_size))
+        .filter(|size| *size <= data.len())
+        .ok_or_else(|| {
+            anyhow!(
+                "The data of {} bytes is too short for {height} rows of {step} bytes",
+                data.len()
+            )
+        })?;
+    if step == row_size {
+        return Ok(Cow::Borrowed(&data[..size]));
+    }
+    Ok(Cow::Owned(
+        data[..size]
+            .chunks(step)
+            .flat_map(|row| &row[..row_size])
+            .copied()
+            .collect(),
+    ))
+}
+
+/// Converts the raw pixels of a Bayer filter into RGB, with the colors of the 2x2 block each
+/// pixel is part of.
+///
+/// The samples are little-endian and `sample_size` bytes long, as are the channels of the
+/// result.
+fn demosaic(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    pattern: BayerPattern,
+    sample_size: usize,
+) -> Result<Vec<u8>, Error> {
+    if width < 2 || height < 2 {
+        return Err(anyhow!(
+            "Bayer images must be at least 2x2 pixels, not {width}x{height}"
+        ));
+    }
+
+    let sample = |x: usize, y: usize| {
+        let mut bytes = [0u8; 4];
+        let offset = (y * width + x) * sample_size;
+        bytes[..sample_size].copy_from_slice(&pixels[offset..offset + sample_size]);
+        u32::from_le_bytes(bytes)
+    };
+    // The last block of an odd row or column overlaps the previous one
+    let block_origin = |position: usize, size: usize| {
+        let origin = position & !1;
+        if origin + 1 < size {
+            origin
+        } else {
+            origin - 2
+        }
+    };
+
+    let (red_x, red_y) = pattern.red();
+    let mut rgb = Vec::with_capacity(width * height * 3 * sample_size);
+    for y in 0..height {
+        let origin_y = block_origin(y, height);
+        for x in 0..width {
+            let origin_x = block_origin(x, width);
+            let red = sample(origin_x + red_x, origin_y + red_y);
+            let blue = sample(origin_x + 1 - red_x, origin_y + 1 - red_y);
+            let green = (sample(origin_x + 1 - red_x, origin_y + red_y)
+                + sample(origin_x + red_x, origin_y + 1 - red_y))
+                / 2;
+            for channel in [red, green, blue] {
+                rgb.extend_from_slice(&channel.to_le_bytes()[..sample_size]);
+            }
+        }
+    }
+    Ok(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[test]
+    fn test_color_with_padding() {
+        // Two rows of one BGR pixel, padded to four bytes
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({
+                "height": 2,
+                "width": 1,
+                "encoding": "bgr8",
+                "step": 4,
+                "data": [1, 2, 3, 0, 4, 5, 6, 0],
+            }),
+        );
+        assert_eq!(
+            image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Color(rerun::Image::from_color_model_and_bytes(
+                vec![1, 2, 3, 4, 5, 6],
+                [1, 2],
+                ColorModel::BGR,
+                ChannelDatatype::U8,
+            ))
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({
+                "height": 1,
+                "width": 2,
+                "encoding": "16UC1",
+                "is_bigendian": 1,
+                "step": 4,
+                "data": [0x01, 0x02, 0x03, 0x04],
+            }),
+        );
+        assert_eq!(
+            image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Depth(
+                rerun::DepthImage::from_data_type_and_bytes(
+                    vec![0x02, 0x01, 0x04, 0x03],
+                    [2, 1],
+                    ChannelDatatype::U16,
+                )
+                .with_meter(1000.0)
+            )
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({
+                "height": 1,
+                "width": 1,
+                "encoding": "32FC1",
+                "step": 4,
+                "data": 1.5f32.to_le_bytes(),
+            }),
+        );
+        assert_eq!(
+            image(&message.view(), &ImageOptions::new(Some(2.0))).unwrap(),
+            ImageArchetype::Depth(
+                rerun::DepthImage::from_data_type_and_bytes(
+                    1.5f32.to_le_bytes().to_vec(),
+                    [1, 1],
+                    ChannelDatatype::F32,
+                )
+                .with_meter(2.0)
+            )
+        );
+    }
+
+    #[test]
+    fn test_bayer() {
+        // A 3x2 image, whose last column reuses the colors of the first block
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({
+                "height": 2,
+                "width": 3,
+                "encoding": "bayer_grbg8",
+                "step": 3,
+                "data": [10, 200, 30, 50, 40, 70],
+            }),
+        );
+        assert_eq!(
+            image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Color(rerun::Image::from_color_model_and_bytes(
+                [200, 25, 50].repeat(6),
+                [3, 2],
+                ColorModel::RGB,
+                ChannelDatatype::U8,
+            ))
+        );
+    }
+
+    #[test]
+    fn test_yuv422() {
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({
+                "height": 1,
+                "width": 2,
+                "encoding": "yuv422",
+                "step": 4,
+                "data": [1, 2, 3, 4],
+            }),
+        );
+        assert_eq!(
+            image(&message.view(), &ImageOptions::default()).unwrap(),
+            ImageArchetype::Color(rerun::Image::from_pixel_format(
+                [2, 1],
+                PixelFormat::YUY2,
+                vec![2, 1, 4, 3],
+            ))
+        );
+    }
+
+    #[test]
+    fn test_packed_rows_borrow_unpadded_data() {
+        let data = [1, 2, 3, 4, 5];
+        let rows = packed_rows(&data, 2, 2, 2).unwrap();
+        assert!(matches!(rows, Cow::Borrowed(&[1, 2, 3, 4])));
+        let rows = packed_rows(&data, 2, 3, 2).unwrap();
+        assert!(matches!(rows, Cow::Owned(ref pixels) if pixels == &[1, 2, 4, 5]));
+    }
+
+    #[test]
+    fn test_invalid_images() {
+        let options = ImageOptions::default();
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({"height": 1, "width": 1, "encoding": "jpeg", "step": 3, "data": vec![0; 3]}),
+        );
+        assert_eq!(
+            image(&message.view(), &options).err().unwrap().to_string(),
+            "Unsupported image encoding \"jpeg\""
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({"height": 2, "width": 2, "encoding": "rgb8", "step": 6, "data": vec![0; 11]}),
+        );
+        assert_eq!(
+            image(&message.view(), &options).err().unwrap().to_string(),
+            "The data of 11 bytes is too short for 2 rows of 6 bytes"
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/Image",
+            &json!({"height": 2, "width": 2, "encoding": "rgb8", "step": 5, "data": vec![0; 12]}),
+        );
+        assert_eq!(
+            image(&message.view(), &options).err().unwrap().to_string(),
+            "The step of 5 bytes is smaller than a row of 6 bytes"
+        );
+    }
+}
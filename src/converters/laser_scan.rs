@@ -0,0 +1,366 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use serde::Deserialize;
+
+use crate::converters::{parse_options, CdrMessage, Colormap, ConversionContext, Converter};
+use crate::value::Value;
+
+/// The child of the entity path of a scan the rays are logged at.
+const RAYS_ENTITY: &str = "rays";
+
+/// The fields of single and multi-echo scans the converters read.
+const SCAN_FIELDS: [&str; 6] = [
+    "angle_min",
+    "angle_increment",
+    "range_min",
+    "range_max",
+    "ranges",
+    "intensities",
+];
+
+/// The options of the laser scan converters, from the options table of a conversion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LaserScanOptions {
+    #[serde(default)]
+    draw_rays: bool,
+    #[serde(default)]
+    echo: usize,
+    #[serde(default)]
+    colormap: Colormap,
+    color_range: Option<[f64; 2]>,
+}
+
+impl LaserScanOptions {
+    /// Creates a new `LaserScanOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `draw_rays` - Whether to also draw a line strip from the sensor to each point.
+    /// * `echo` - The index of the echo to use in multi-echo scans, 0 being the first one.
+    /// * `colormap` - The `Colormap` the intensities are mapped through.
+    /// * `color_range` - The intensities mapped to both ends of the colormap, or `None` for the
+    ///   smallest and largest intensities of each scan.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `LaserScanOptions` instance.
+    pub fn new(
+        draw_rays: bool,
+        echo: usize,
+        colormap: Colormap,
+        color_range: Option<[f64; 2]>,
+    ) -> Self {
+        Self {
+            draw_rays,
+            echo,
+            colormap,
+            color_range,
+        }
+    }
+
+    /// Returns whether the rays are drawn.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if a line strip is drawn from the sensor to each point.
+    pub fn draw_rays(&self) -> bool {
+        self.draw_rays
+    }
+
+    /// Returns the index of the echo to use in multi-echo scans.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The index of the echo, 0 being the first one.
+    pub fn echo(&self) -> usize {
+        self.echo
+    }
+
+    /// Returns the colormap the intensities are mapped through.
+    ///
+    /// # Returns
+    ///
+    /// * `Colormap` - The colormap.
+    pub fn colormap(&self) -> Colormap {
+        self.colormap
+    }
+
+    /// Returns the intensities mapped to both ends of the colormap.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<[f64; 2]>` - The range, or `None` for the range of each scan.
+    pub fn color_range(&self) -> Option<[f64; 2]> {
+        self.color_range
+    }
+}
+
+/// Logs `sensor_msgs/LaserScan` messages as 3D points in the frame of the sensor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LaserScanConverter {
+    options: LaserScanOptions,
+}
+
+impl LaserScanConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "laser_scan";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/LaserScan"];
+
+    /// Creates a new `LaserScanConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `LaserScanOptions` of the converter.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `LaserScanConverter` instance.
+    pub fn new(options: LaserScanOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Converter for LaserScanConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        let ranges = value.get("ranges")?.to_f64_vec()?;
+        let intensities = value.get("intensities")?.to_f64_vec()?;
+        scan(value, &ranges, &intensities, &self.options)?.log(stream, context.entity_path())
+    }
+
+    fn field_paths(&self) -> Option<Vec<String>> {
+        Some(SCAN_FIELDS.map(str::to_owned).to_vec())
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(parse_options(options)?)))
+    }
+}
+
+/// Logs `sensor_msgs/MultiEchoLaserScan` messages as 3D points in the frame of the sensor,
+/// keeping one of the echoes of each ray.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MultiEchoLaserScanConverter {
+    options: LaserScanOptions,
+}
+
+impl MultiEchoLaserScanConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "multi_echo_laser_scan";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/MultiEchoLaserScan"];
+
+    /// Creates a new `MultiEchoLaserScanConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `LaserScanOptions` of the converter.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `MultiEchoLaserScanConverter` instance.
+    pub fn new(options: LaserScanOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Converter for MultiEchoLaserScanConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        let ranges = echoes(value, "ranges", self.options.echo())?;
+        let intensities = echoes(value, "intensities", self.options.echo())?;
+        scan(value, &ranges, &intensities, &self.options)?.log(stream, context.entity_path())
+    }
+
+    fn field_paths(&self) -> Option<Vec<String>> {
+        Some(SCAN_FIELDS.map(str::to_owned).to_vec())
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(parse_options(options)?)))
+    }
+}
+
+/// Picks one echo of each ray of a multi-echo scan, rays without it having a NaN range.
+fn echoes(value: &Value, path: &str, echo: usize) -> Result<Vec<f64>, Error> {
+    value
+        .get_array(path)?
+        .iter()
+        .map(|ray| {
+            let echoes = ray.get("echoes")?.to_f64_vec()?;
+            Ok(echoes.get(echo).copied().unwrap_or(f64::NAN))
+        })
+        .collect()
+}
+
+/// A scan converted into 3D points, and the rays to them if they are drawn.
+#[derive(Debug, Clone, PartialEq)]
+struct ScanArchetypes {
+    points: rerun::Points3D,
+    rays: Option<rerun::LineStrips3D>,
+}
+
+impl ScanArchetypes {
+    fn log(&self, stream: &rerun::RecordingStream, entity_path: &str) -> Result<()> {
+        stream.log(entity_path, &self.points)?;
+        if let Some(rays) = &self.rays {
+            stream.log(format!("{entity_path}/{RAYS_ENTITY}"), rays)?;
+        }
+        Ok(())
+    }
+}
+
+/// Converts the ranges of a scan into 3D points in the XY plane of the sensor, dropping the
+/// ranges that are out of `range_min` and `range_max`, or not finite.
+///
+/// The points are colored by intensity if there is one per range.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn scan(
+    value: &Value,
+    ranges: &[f64],
+    intensities: &[f64],
+    options: &LaserScanOptions,
+) -> Result<ScanArchetypes, Error> {
+    let angle_min = value.get_f64("angle_min")?;
+    let angle_increment = value.get_f64("angle_increment")?;
+    let range_min = value.get_f64("range_min")?;
+    let range_max = value.get_f64("range_max")?;
+    let has_intensities = !intensities.is_empty() && intensities.len() == ranges.len();
+
+    let mut positions = Vec::new();
+    let mut kept_intensities = Vec::new();
+    for (index, range) in ranges.iter().enumerate() {
+        if !range.is_finite() || *range < range_min || *range > range_max {
+            continue;
+        }
+        let angle = angle_min + index as f64 * angle_increment;
+        positions.push([
+            (range * angle.cos()) as f32,
+            (range * angle.sin()) as f32,
+            0.0,
+        ]);
+        if has_intensities {
+            kept_intensities.push(intensities[index]);
+        }
+    }
+
+    let colors = has_intensities.then(|| {
+        options
+            .colormap()
+            .colors(&kept_intensities, options.color_range())
+    });
+    let rays = options.draw_rays().then(|| {
+        let rays = rerun::LineStrips3D::new(
+            positions
+                .iter()
+                .map(|position| [[0.0, 0.0, 0.0], *position]),
+        );
+        match &colors {
+            Some(colors) => rays.with_colors(colors.clone()),
+            None => rays,
+        }
+    });
+    let points = rerun::Points3D::new(positions);
+    Ok(ScanArchetypes {
+        points: match colors {
+            Some(colors) => points.with_colors(colors),
+            None => points,
+        },
+        rays,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn position(range: f64, angle: f64) -> [f32; 3] {
+        [
+            (range * angle.cos()) as f32,
+            (range * angle.sin()) as f32,
+            0.0,
+        ]
+    }
+
+    #[test]
+    fn test_scan() {
+        let value = TestMessage::new(
+            "sensor_msgs/msg/LaserScan",
+            &json!({
+                "angle_min": 0.0,
+                "angle_max": 4.5,
+                "angle_increment": 1.5,
+                "scan_time": 0.1,
+                "range_min": 0.5,
+                "range_max": 10.0,
+                "ranges": [1.0, 0.1, "inf", 2.0],
+                "intensities": [10.0, 0.0, 0.0, 20.0],
+            }),
+        )
+        .projected_value(&SCAN_FIELDS);
+        let ranges = value.get("ranges").unwrap().to_f64_vec().unwrap();
+        let intensities = value.get("intensities").unwrap().to_f64_vec().unwrap();
+
+        let positions = [position(1.0, 0.0), position(2.0, 4.5)];
+        let colors = [
+            rerun::Color::from_rgb(0, 0, 0),
+            rerun::Color::from_rgb(255, 255, 255),
+        ];
+        let options = LaserScanOptions::new(true, 0, Colormap::Grayscale, None);
+        assert_eq!(
+            scan(&value, &ranges, &intensities, &options).unwrap(),
+            ScanArchetypes {
+                points: rerun::Points3D::new(positions).with_colors(colors),
+                rays: Some(
+                    rerun::LineStrips3D::new(positions.map(|position| [[0.0, 0.0, 0.0], position]))
+                        .with_colors(colors)
+                ),
+            }
+        );
+
+        // Intensities that do not match the ranges are ignored
+        assert_eq!(
+            scan(&value, &ranges, &[1.0], &LaserScanOptions::default()).unwrap(),
+            ScanArchetypes {
+                points: rerun::Points3D::new(positions),
+                rays: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_echoes() {
+        let value = TestMessage::new(
+            "sensor_msgs/msg/MultiEchoLaserScan",
+            &json!({
+                "ranges": [{"echoes": [1.0, 2.0]}, {"echoes": []}, {"echoes": [3.0]}],
+            }),
+        )
+        .projected_value(&SCAN_FIELDS);
+
+        assert_eq!(echoes(&value, "ranges", 0).unwrap()[..1], [1.0]);
+        let second = echoes(&value, "ranges", 1).unwrap();
+        assert_eq!(second[0], 2.0);
+        assert!(second[1..].iter().all(|range| range.is_nan()));
+        assert!(echoes(&value, "intensities", 0).unwrap().is_empty());
+    }
+}
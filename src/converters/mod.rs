@@ -0,0 +1,619 @@
+//! Conversion of decoded ROS messages into Rerun archetypes, through converters registered by
+//! ROS type.
+pub mod camera_info;
+pub mod colormap;
+pub mod compressed_image;
+#[cfg(test)]
+mod fixtures;
+pub mod image;
+pub mod laser_scan;
+pub mod point_cloud;
+pub mod std_msgs;
+pub mod text;
+pub mod tf;
+
+pub use camera_info::*;
+pub use colormap::*;
+pub use compressed_image::*;
+pub use image::*;
+pub use laser_scan::*;
+pub use point_cloud::*;
+pub use std_msgs::*;
+pub use text::*;
+pub use tf::*;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error, Result};
+
+use crate::cdr::{DecodePlan, MessageView, Projection};
+use crate::ros_introspection::{MsgSpec, Type};
+use crate::value::Value;
+
+/// The timeline on which messages are logged at the stamp of their header.
+pub const ROS_TIME_TIMELINE: &str = "ros_time";
+
+/// The name of the converter used for types that have no converter of their own.
+pub const FALLBACK_CONVERTER: &str = TextDocumentConverter::NAME;
+
+/// Where and when a message is logged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionContext {
+    entity_path: String,
+    frame_id: String,
+    stamp: Option<i64>,
+}
+
+impl ConversionContext {
+    /// Creates a new `ConversionContext` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `entity_path` - A string slice that holds the entity path to log at.
+    /// * `frame_id` - A string slice that holds the frame of the conversion, from the
+    ///   configuration.
+    /// * `stamp` - The stamp of the message in nanoseconds since the epoch, if it has one.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ConversionContext` instance.
+    pub fn new(entity_path: &str, frame_id: &str, stamp: Option<i64>) -> Self {
+        Self {
+            entity_path: entity_path.to_owned(),
+            frame_id: frame_id.to_owned(),
+            stamp,
+        }
+    }
+
+    /// Returns the entity path to log at.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the entity path.
+    pub fn entity_path(&self) -> &str {
+        &self.entity_path
+    }
+
+    /// Returns the frame of the conversion.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the frame ID.
+    pub fn frame_id(&self) -> &str {
+        &self.frame_id
+    }
+
+    /// Returns the stamp of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The stamp in nanoseconds since the epoch, or `None` if the message has
+    ///   no header.
+    pub fn stamp(&self) -> Option<i64> {
+        self.stamp
+    }
+
+    /// Sets the time of the data logged next on the current thread to the stamp of the
+    /// message, on the [`ROS_TIME_TIMELINE`].
+    ///
+    /// Times set for previous messages are cleared first, so that messages without a header
+    /// do not inherit them.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A reference to the recording stream.
+    pub fn set_time(&self, stream: &rerun::RecordingStream) {
+        stream.reset_time();
+        if let Some(stamp) = self.stamp {
+            stream.set_time_nanos(ROS_TIME_TIMELINE, stamp);
+        }
+    }
+}
+
+/// A CDR serialized message handed to a converter, which only decodes what it needs of it.
+///
+/// Converters of large messages, such as images and point clouds, read them through a
+/// `MessageView` that borrows their payload from the buffer, while the others decode them with
+/// the `DecodePlan` compiled once for their subscription, or with the `Projection` of the
+/// fields they declare in [`Converter::field_paths`].
+#[derive(Debug, Clone, Copy)]
+pub struct CdrMessage<'a> {
+    spec: &'a MsgSpec,
+    plan: &'a DecodePlan,
+    projection: Option<&'a Projection>,
+    buffer: &'a [u8],
+}
+
+impl<'a> CdrMessage<'a> {
+    /// Creates a new `CdrMessage` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` of the message type.
+    /// * `plan` - A reference to the `DecodePlan` compiled from `spec`.
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `CdrMessage` instance.
+    pub fn new(spec: &'a MsgSpec, plan: &'a DecodePlan, buffer: &'a [u8]) -> Self {
+        Self::new_with_projection(spec, plan, None, buffer)
+    }
+
+    /// Creates a new `CdrMessage` instance that only decodes the fields of a projection.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` of the message type.
+    /// * `plan` - A reference to the `DecodePlan` compiled from `spec`.
+    /// * `projection` - A reference to the `Projection` created from `spec`, or `None` to
+    ///   decode whole messages.
+    /// * `buffer` - The serialized message, starting with its encapsulation header.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `CdrMessage` instance.
+    pub fn new_with_projection(
+        spec: &'a MsgSpec,
+        plan: &'a DecodePlan,
+        projection: Option<&'a Projection>,
+        buffer: &'a [u8],
+    ) -> Self {
+        Self {
+            spec,
+            plan,
+            projection,
+            buffer,
+        }
+    }
+
+    /// Returns the specification of the message type.
+    ///
+    /// # Returns
+    ///
+    /// * `&MsgSpec` - A reference to the `MsgSpec` of the message.
+    pub fn spec(&self) -> &'a MsgSpec {
+        self.spec
+    }
+
+    /// Returns a view over the message, through which its fields are read without decoding
+    /// the others.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the encapsulation header is invalid.
+    pub fn view(&self) -> Result<MessageView<'a>, Error> {
+        MessageView::new(self.spec, self.buffer)
+    }
+
+    /// Decodes the message, or only the fields of its projection if it has one.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffer does not hold a message of this type.
+    pub fn decode(&self) -> Result<Value, Error> {
+        match self.projection {
+            Some(projection) => projection.decode(self.spec, self.buffer),
+            None => self.plan.decode(self.buffer),
+        }
+    }
+
+    /// Reads the stamp of the header of the message, without decoding the rest of it.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The stamp in nanoseconds since the epoch, or `None` if the message has
+    ///   no `header.stamp` field.
+    pub fn stamp(&self) -> Option<i64> {
+        let stamp = self.view().ok()?.get("header.stamp").ok()?.decode().ok()?;
+        stamp_nanos(&stamp)
+    }
+}
+
+/// Reads the stamp of the header of a message.
+///
+/// Both the ROS 2 `builtin_interfaces/Time` and the ROS 1 `time` layouts are supported.
+///
+/// # Arguments
+///
+/// * `value` - A reference to the decoded message.
+///
+/// # Returns
+///
+/// * `Option<i64>` - The stamp in nanoseconds since the epoch, or `None` if the message has no
+///   `header.stamp` field.
+pub fn header_stamp(value: &Value) -> Option<i64> {
+    stamp_nanos(value.get("header.stamp").ok()?)
+}
+
+/// Converts a decoded `builtin_interfaces/Time` or ROS 1 `time` into nanoseconds.
+fn stamp_nanos(stamp: &Value) -> Option<i64> {
+    let sec = stamp.get_i64("sec").ok()?;
+    let nanosec = stamp
+        .get_i64("nanosec")
+        .or_else(|_| stamp.get_i64("nsec"))
+        .ok()?;
+    Some(sec * 1_000_000_000 + nanosec)
+}
+
+/// Logs serialized messages of some ROS types as Rerun archetypes.
+pub trait Converter: Send + Sync {
+    /// Logs a serialized message.
+    ///
+    /// The time of the message has already been set on the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A reference to the recording stream to log to.
+    /// * `context` - A reference to the `ConversionContext` of the message.
+    /// * `message` - A reference to the `CdrMessage`, to view or decode.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the message does not have the expected fields,
+    /// holds invalid data, or cannot be logged.
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error>;
+
+    /// Returns the paths of the fields the converter reads after decoding, such as
+    /// `header.stamp` or `pose.position`, so that the other fields are skipped.
+    ///
+    /// Converters that read every field, or read messages through a view, keep the default
+    /// `None` and get whole messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<String>>` - The paths of the fields, or `None` to decode whole messages.
+    fn field_paths(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Creates a converter configured with the options of a conversion.
+    ///
+    /// Converters take no options unless they override this function.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A reference to the options table of the conversion, which is not empty.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<dyn Converter>, Error>` - A result containing the configured converter or
+    ///   an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the options are unknown or invalid.
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        let names: Vec<&str> = options.keys().map(String::as_str).collect();
+        Err(anyhow!(
+            "The converter takes no options, found: {}",
+            names.join(", ")
+        ))
+    }
+}
+
+/// Deserializes the options table of a conversion, for the converters that take options.
+///
+/// # Arguments
+///
+/// * `options` - A reference to the options table of the conversion.
+///
+/// # Returns
+///
+/// * `Result<T, Error>` - A result containing the options or an error.
+///
+/// # Errors
+///
+/// This function will return an error if an option is unknown or has the wrong type.
+pub fn parse_options<T: serde::de::DeserializeOwned>(options: &toml::Table) -> Result<T, Error> {
+    Ok(toml::Value::Table(options.clone()).try_into()?)
+}
+
+/// The converters known to the bridge, by name and by the ROS types they handle.
+#[derive(Default)]
+pub struct ConverterRegistry {
+    converters: HashMap<String, Arc<dyn Converter>>,
+    by_type: HashMap<String, String>,
+}
+
+impl ConverterRegistry {
+    /// Creates a new `ConverterRegistry` instance holding the converters of the bridge.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ConverterRegistry` instance.
+    pub fn new() -> Self {
+        let mut registry = Self::default();
+        registry.register(
+            TextDocumentConverter::NAME,
+            &[],
+            Arc::new(TextDocumentConverter::default()),
+        );
+        registry.register(
+            ScalarConverter::NAME,
+            &ScalarConverter::ROS_TYPES,
+            Arc::new(ScalarConverter),
+        );
+        registry.register(
+            TextLogConverter::NAME,
+            &TextLogConverter::ROS_TYPES,
+            Arc::new(TextLogConverter),
+        );
+        registry.register(
+            ColorConverter::NAME,
+            &ColorConverter::ROS_TYPES,
+            Arc::new(ColorConverter),
+        );
+        registry.register(
+            TensorConverter::NAME,
+            &TensorConverter::ROS_TYPES,
+            Arc::new(TensorConverter),
+        );
+        registry.register(
+            ImageConverter::NAME,
+            &ImageConverter::ROS_TYPES,
+            Arc::new(ImageConverter::default()),
+        );
+        registry.register(
+            CompressedImageConverter::NAME,
+            &CompressedImageConverter::ROS_TYPES,
+            Arc::new(CompressedImageConverter::default()),
+        );
+        registry.register(
+            PointCloudConverter::NAME,
+            &PointCloudConverter::ROS_TYPES,
+            Arc::new(PointCloudConverter::default()),
+        );
+        registry.register(
+            CameraInfoConverter::NAME,
+            &CameraInfoConverter::ROS_TYPES,
+            Arc::new(CameraInfoConverter),
+        );
+        registry.register(
+            LaserScanConverter::NAME,
+            &LaserScanConverter::ROS_TYPES,
+            Arc::new(LaserScanConverter::default()),
+        );
+        registry.register(
+            MultiEchoLaserScanConverter::NAME,
+            &MultiEchoLaserScanConverter::ROS_TYPES,
+            Arc::new(MultiEchoLaserScanConverter::default()),
+        );
+        registry
+    }
+
+    /// Registers a converter, replacing any converter of the same name, and makes it the
+    /// converter of the given ROS types.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the converter, by which the
+    ///   configuration may select it.
+    /// * `ros_types` - The ROS types the converter handles, such as `std_msgs/msg/String`.
+    /// * `converter` - The converter.
+    pub fn register(&mut self, name: &str, ros_types: &[&str], converter: Arc<dyn Converter>) {
+        self.converters.insert(name.to_owned(), converter);
+        for ros_type in ros_types {
+            self.by_type
+                .insert(normalize_type_name(ros_type), name.to_owned());
+        }
+    }
+
+    /// Returns the names of the registered converters, sorted.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&str>` - The names of the converters.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.converters.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns the name of the converter a ROS type resolves to.
+    ///
+    /// # Arguments
+    ///
+    /// * `ros_type` - A string slice that holds the ROS type, such as `std_msgs/msg/String`.
+    /// * `name` - The name of the converter to use instead of the one registered for the type,
+    ///   if any.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The given name, the name of the converter registered for the type, or
+    ///   [`FALLBACK_CONVERTER`] if the type has none.
+    pub fn resolve_name<'a>(&'a self, ros_type: &str, name: Option<&'a str>) -> &'a str {
+        name.or_else(|| {
+            self.by_type
+                .get(&normalize_type_name(ros_type))
+                .map(String::as_str)
+        })
+        .unwrap_or(FALLBACK_CONVERTER)
+    }
+
+    /// Looks up the converter of a ROS type, and configures it.
+    ///
+    /// # Arguments
+    ///
+    /// * `ros_type` - A string slice that holds the ROS type, such as `std_msgs/msg/String`.
+    /// * `name` - The name of the converter to use instead of the one registered for the type,
+    ///   if any.
+    /// * `options` - A reference to the options table of the conversion.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<dyn Converter>, Error>` - A result containing the converter, the
+    ///   [`FALLBACK_CONVERTER`] if the type has none, or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if there is no converter of the given name, or if
+    /// it does not accept the options.
+    pub fn resolve(
+        &self,
+        ros_type: &str,
+        name: Option<&str>,
+        options: &toml::Table,
+    ) -> Result<Arc<dyn Converter>, Error> {
+        let name = self.resolve_name(ros_type, name);
+        let converter = self.converters.get(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown converter `{name}` for {ros_type}, expected one of: {}",
+                self.names().join(", ")
+            )
+        })?;
+        if options.is_empty() {
+            return Ok(Arc::clone(converter));
+        }
+        converter
+            .configure(options)
+            .map_err(|err| anyhow!("Invalid options for converter `{name}`: {err}"))
+    }
+}
+
+/// Converts a type name such as `std_msgs/msg/String` into the `std_msgs/String` form, so that
+/// both spellings find the same converter.
+fn normalize_type_name(ros_type: &str) -> String {
+    match Type::new(ros_type) {
+        Ok(parsed) if !parsed.pkg_name().is_empty() => {
+            format!("{}/{}", parsed.pkg_name(), parsed.msg_name())
+        }
+        _ => ros_type.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    struct NullConverter;
+
+    impl Converter for NullConverter {
+        fn convert(
+            &self,
+            _stream: &rerun::RecordingStream,
+            _context: &ConversionContext,
+            _message: &CdrMessage<'_>,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut registry = ConverterRegistry::new();
+        let options = toml::Table::new();
+        let null: Arc<dyn Converter> = Arc::new(NullConverter);
+        registry.register("null", &["std_msgs/msg/Empty"], Arc::clone(&null));
+
+        assert!(registry.names().contains(&"null"));
+        for ros_type in ["std_msgs/msg/Empty", "std_msgs/Empty"] {
+            let converter = registry.resolve(ros_type, None, &options).unwrap();
+            assert!(Arc::ptr_eq(&converter, &null));
+        }
+
+        let fallback = registry
+            .resolve("std_msgs/msg/Header", None, &options)
+            .unwrap();
+        assert!(!Arc::ptr_eq(&fallback, &null));
+        let overridden = registry
+            .resolve("std_msgs/msg/Header", Some("null"), &options)
+            .unwrap();
+        assert!(Arc::ptr_eq(&overridden, &null));
+        assert_eq!(registry.resolve_name("std_msgs/Empty", None), "null");
+        assert_eq!(
+            registry.resolve_name("std_msgs/msg/Header", None),
+            FALLBACK_CONVERTER
+        );
+        assert_eq!(
+            registry.resolve_name("std_msgs/msg/Empty", Some("text_document")),
+            "text_document"
+        );
+
+        let err = registry
+            .resolve("std_msgs/msg/Header", Some("nope"), &options)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Unknown converter `nope` for std_msgs/msg/Header, expected one of: {}",
+                registry.names().join(", ")
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_with_options() {
+        let mut registry = ConverterRegistry::new();
+        registry.register("null", &["std_msgs/msg/Empty"], Arc::new(NullConverter));
+
+        let mut options = toml::Table::new();
+        options.insert("scale".to_owned(), toml::Value::Float(2.0));
+        let err = registry
+            .resolve("std_msgs/msg/Empty", None, &options)
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "Invalid options for converter `null`: The converter takes no options, found: scale"
+        );
+    }
+
+    #[test]
+    fn test_header_stamp() {
+        let stamped = |nanosec_name: &str| {
+            Value::Message(vec![(
+                "header".to_owned(),
+                Value::Message(vec![(
+                    "stamp".to_owned(),
+                    Value::Message(vec![
+                        ("sec".to_owned(), Value::Int32(12)),
+                        (nanosec_name.to_owned(), Value::Uint32(5)),
+                    ]),
+                )]),
+            )])
+        };
+        assert_eq!(header_stamp(&stamped("nanosec")), Some(12_000_000_005));
+        assert_eq!(header_stamp(&stamped("nsec")), Some(12_000_000_005));
+        assert_eq!(header_stamp(&Value::Message(vec![])), None);
+    }
+
+    #[test]
+    fn test_cdr_message_stamp() {
+        let message = TestMessage::new(
+            "geometry_msgs/msg/PoseStamped",
+            &json!({"header": {"stamp": {"sec": 12, "nanosec": 5}}}),
+        );
+        assert_eq!(message.message().stamp(), Some(12_000_000_005));
+
+        let message = TestMessage::new("std_msgs/msg/String", &json!({"data": "hello"}));
+        assert_eq!(message.message().stamp(), None);
+    }
+
+    #[test]
+    fn test_cdr_message_projection() {
+        let message = TestMessage::new(
+            "geometry_msgs/msg/PoseStamped",
+            &json!({
+                "header": {"stamp": {"sec": 12, "nanosec": 5}, "frame_id": "map"},
+                "pose": {"position": {"x": 1.5}},
+            }),
+        );
+
+        let value = message.projected_value(&["header.stamp", "pose.position.x"]);
+        assert_eq!(header_stamp(&value), Some(12_000_000_005));
+        assert_eq!(value.get_f64("pose.position.x").unwrap(), 1.5);
+        assert!(value.get("header.frame_id").is_err());
+        assert!(value.get("pose.orientation").is_err());
+    }
+}
@@ -0,0 +1,476 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Error, Result};
+use serde::Deserialize;
+
+use crate::cdr::MessageView;
+use crate::converters::{parse_options, CdrMessage, Colormap, ConversionContext, Converter};
+use crate::value::Value;
+
+/// The options of the point cloud converter, from the options table of a conversion.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PointCloudOptions {
+    color_field: Option<String>,
+    #[serde(default)]
+    colormap: Colormap,
+    color_range: Option<[f64; 2]>,
+}
+
+impl PointCloudOptions {
+    /// Creates a new `PointCloudOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `color_field` - The name of a scalar field to color the points by, such as
+    ///   `intensity`, or `None` to use the packed `rgb` or `rgba` field if there is one.
+    /// * `colormap` - The `Colormap` the scalar field is mapped through.
+    /// * `color_range` - The values of the scalar field mapped to both ends of the colormap, or
+    ///   `None` for the smallest and largest values of each cloud.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `PointCloudOptions` instance.
+    pub fn new(
+        color_field: Option<&str>,
+        colormap: Colormap,
+        color_range: Option<[f64; 2]>,
+    ) -> Self {
+        Self {
+            color_field: color_field.map(ToOwned::to_owned),
+            colormap,
+            color_range,
+        }
+    }
+
+    /// Returns the name of the scalar field the points are colored by.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The name of the field, or `None` to use the packed colors.
+    pub fn color_field(&self) -> Option<&str> {
+        self.color_field.as_deref()
+    }
+
+    /// Returns the colormap the scalar field is mapped through.
+    ///
+    /// # Returns
+    ///
+    /// * `Colormap` - The colormap.
+    pub fn colormap(&self) -> Colormap {
+        self.colormap
+    }
+
+    /// Returns the values of the scalar field mapped to both ends of the colormap.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<[f64; 2]>` - The range, or `None` for the range of each cloud.
+    pub fn color_range(&self) -> Option<[f64; 2]> {
+        self.color_range
+    }
+}
+
+/// Logs `sensor_msgs/PointCloud2` messages as 3D points.
+#[derive(Debug, Clone, Default)]
+pub struct PointCloudConverter {
+    options: PointCloudOptions,
+}
+
+impl PointCloudConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "point_cloud";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["sensor_msgs/msg/PointCloud2"];
+
+    /// Creates a new `PointCloudConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The `PointCloudOptions` of the converter.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `PointCloudConverter` instance.
+    pub fn new(options: PointCloudOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Converter for PointCloudConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        stream.log(
+            context.entity_path(),
+            &points(&message.view()?, &self.options)?,
+        )?;
+        Ok(())
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(parse_options(options)?)))
+    }
+}
+
+/// A field of the points of a cloud, from its `sensor_msgs/PointField`.
+#[derive(Debug, Clone, Copy)]
+struct PointField {
+    offset: usize,
+    datatype: u8,
+}
+
+impl PointField {
+    /// Looks up a field by name among the decoded `fields` of a cloud, checking that it fits in
+    /// a point.
+    fn find(fields: &[Value], name: &str, point_step: usize) -> Result<Option<Self>, Error> {
+        for field in fields {
+            if field.get_str("name")? != name {
+                continue;
+            }
+            let offset = usize::try_from(field.get_u64("offset")?)?;
+            let datatype = u8::try_from(field.get_u64("datatype")?)?;
+            let size = datatype_size(datatype)
+                .ok_or_else(|| anyhow!("Field {name:?} has the unknown datatype {datatype}"))?;
+            if offset + size > point_step {
+                return Err(anyhow!(
+                    "Field {name:?} at offset {offset} does not fit in a point of {point_step} bytes"
+                ));
+            }
+            return Ok(Some(Self { offset, datatype }));
+        }
+        Ok(None)
+    }
+
+    /// Reads the first element of the field in a point.
+    fn read(self, point: &[u8], is_bigendian: bool) -> f64 {
+        let bytes = &point[self.offset..];
+        match self.datatype {
+            INT8 => f64::from(i8::from_ne_bytes([bytes[0]])),
+            UINT8 => f64::from(bytes[0]),
+            INT16 => f64::from(read_bytes(
+                bytes,
+                is_bigendian,
+                i16::from_le_bytes,
+                i16::from_be_bytes,
+            )),
+            UINT16 => f64::from(read_bytes(
+                bytes,
+                is_bigendian,
+                u16::from_le_bytes,
+                u16::from_be_bytes,
+            )),
+            INT32 => f64::from(read_bytes(
+                bytes,
+                is_bigendian,
+                i32::from_le_bytes,
+                i32::from_be_bytes,
+            )),
+            UINT32 => f64::from(self.read_bits(point, is_bigendian)),
+            FLOAT32 => f64::from(f32::from_bits(self.read_bits(point, is_bigendian))),
+            _ => read_bytes(bytes, is_bigendian, f64::from_le_bytes, f64::from_be_bytes),
+        }
+    }
+
+    /// Reads the first 4 bytes of the field in a point, such as the packed colors of the `rgb`
+    /// fields, which are declared as `FLOAT32` or `UINT32`.
+    fn read_bits(self, point: &[u8], is_bigendian: bool) -> u32 {
+        read_bytes(
+            &point[self.offset..],
+            is_bigendian,
+            u32::from_le_bytes,
+            u32::from_be_bytes,
+        )
+    }
+}
+
+const INT8: u8 = 1;
+const UINT8: u8 = 2;
+const INT16: u8 = 3;
+const UINT16: u8 = 4;
+const INT32: u8 = 5;
+const UINT32: u8 = 6;
+const FLOAT32: u8 = 7;
+const FLOAT64: u8 = 8;
+
+fn datatype_size(datatype: u8) -> Option<usize> {
+    match datatype {
+        INT8 | UINT8 => Some(1),
+        INT16 | UINT16 => Some(2),
+        INT32 | UINT32 | FLOAT32 => Some(4),
+        FLOAT64 => Some(8),
+        _ => None,
+    }
+}
+
+/// Reads a number from the first `N` bytes of a slice, which must be long enough.
+fn read_bytes<T, const N: usize>(
+    bytes: &[u8],
+    is_bigendian: bool,
+    from_le_bytes: fn([u8; N]) -> T,
+    from_be_bytes: fn([u8; N]) -> T,
+) -> T {
+    let mut array = [0; N];
+    array.copy_from_slice(&bytes[..N]);
+    if is_bigendian {
+        from_be_bytes(array)
+    } else {
+        from_le_bytes(array)
+    }
+}
+
+/// The colors of the points, before the invalid points are dropped.
+enum PointColors {
+    None,
+    Packed { field: PointField, alpha: bool },
+    Scalar(PointField, Vec<f64>),
+}
+
+/// Converts a `sensor_msgs/PointCloud2` message into 3D points, dropping the points whose
+/// position is not finite.
+///
+/// Organized clouds are read row by row, and unorganized ones have a single row. The points
+/// are read from the serialized message, without copying its data.
+#[allow(clippy::cast_possible_truncation)]
+fn points(view: &MessageView<'_>, options: &PointCloudOptions) -> Result<rerun::Points3D, Error> {
+    let height = usize::try_from(view.field("height")?.decode()?.as_u64()?)?;
+    let width = usize::try_from(view.field("width")?.decode()?.as_u64()?)?;
+    let fields = view.field("fields")?.decode()?;
+    let fields = fields.as_array()?;
+    let is_bigendian = view.field("is_bigendian")?.decode()?.as_bool()?;
+    let point_step = usize::try_from(view.field("point_step")?.decode()?.as_u64()?)?;
+    let row_step = usize::try_from(view.field("row_step")?.decode()?.as_u64()?)?;
+    let data = view.field("data")?.bytes()?;
+
+    let mut axes = Vec::with_capacity(3);
+    for name in ["x", "y", "z"] {
+        axes.push(
+            PointField::find(fields, name, point_step)?
+                .ok_or_else(|| anyhow!("The points have no {name:?} field"))?,
+        );
+    }
+    let mut colors = if let Some(name) = options.color_field() {
+        let field = PointField::find(fields, name, point_step)?
+            .ok_or_else(|| anyhow!("The points have no {name:?} field to color them by"))?;
+        PointColors::Scalar(field, Vec::new())
+    } else if let Some(field) = PointField::find(fields, "rgba", point_step)? {
+        PointColors::Packed { field, alpha: true }
+    } else if let Some(field) = PointField::find(fields, "rgb", point_step)? {
+        PointColors::Packed {
+            field,
+            alpha: false,
+        }
+    } else {
+        PointColors::None
+    };
+
+    // The rows must not overlap, so that the number of points is bounded by the size of the
+    // data, whatever the dimensions claim
+    let row_size = width
+        .checked_mul(point_step)
+        .ok_or_else(|| anyhow!("The rows of {width} points are too large"))?;
+    if height > 1 && row_step < row_size {
+        return Err(anyhow!(
+            "The row step of {row_step} bytes is smaller than a row of {row_size} bytes"
+        ));
+    }
+    if height > 0 && width > 0 {
+        let size = row_step
+            .checked_mul(height - 1)
+            .and_then(|size| size.checked_add(row_size));
+        if size.map_or(true, |size| size > data.len()) {
+            return Err(anyhow!(
+                "The data of {} bytes is too short for {height} rows of {width} points of \
+                 {point_step} bytes",
+                data.len()
+            ));
+        }
+    }
+
+    let mut positions = Vec::new();
+    let mut packed_colors = Vec::new();
+    for row in 0..height {
+        let row_start = row * row_step;
+        for column in 0..width {
+            let point = &data[row_start + column * point_step..][..point_step];
+            let position = [
+                axes[0].read(point, is_bigendian),
+                axes[1].read(point, is_bigendian),
+                axes[2].read(point, is_bigendian),
+            ];
+            if !position.iter().all(|coordinate| coordinate.is_finite()) {
+                continue;
+            }
+            positions.push(position.map(|coordinate| coordinate as f32));
+
+            match &mut colors {
+                PointColors::None => {}
+                PointColors::Packed { field, alpha } => {
+                    let [a, r, g, b] = field.read_bits(point, is_bigendian).to_be_bytes();
+                    packed_colors.push(if *alpha {
+                        rerun::Color::from_unmultiplied_rgba(r, g, b, a)
+                    } else {
+                        rerun::Color::from_rgb(r, g, b)
+                    });
+                }
+                PointColors::Scalar(field, values) => values.push(field.read(point, is_bigendian)),
+            }
+        }
+    }
+
+    let points = rerun::Points3D::new(positions);
+    Ok(match colors {
+        PointColors::None => points,
+        PointColors::Packed { .. } => points.with_colors(packed_colors),
+        PointColors::Scalar(_, values) => {
+            points.with_colors(options.colormap().colors(&values, options.color_range()))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[test]
+    fn test_packed_colors_and_nan() {
+        // x, y, z as float32, then rgb, in an organized cloud of 2x2 points with a padded row
+        let mut data = Vec::new();
+        for (index, position) in [
+            [1.0, 2.0, 3.0],
+            [f32::NAN, 0.0, 0.0],
+            [4.0, 5.0, 6.0],
+            [7.0, 8.0, 9.0],
+        ]
+        .iter()
+        .enumerate()
+        {
+            for coordinate in position {
+                data.extend_from_slice(&coordinate.to_le_bytes());
+            }
+            data.extend_from_slice(&0x00_10_20_30u32.to_le_bytes());
+            if index % 2 == 1 {
+                data.extend_from_slice(&[0; 4]);
+            }
+        }
+        let message = TestMessage::new(
+            "sensor_msgs/msg/PointCloud2",
+            &json!({
+                "height": 2,
+                "width": 2,
+                "fields": [
+                    {"name": "x", "offset": 0, "datatype": FLOAT32, "count": 1},
+                    {"name": "y", "offset": 4, "datatype": FLOAT32, "count": 1},
+                    {"name": "z", "offset": 8, "datatype": FLOAT32, "count": 1},
+                    {"name": "rgb", "offset": 12, "datatype": FLOAT32, "count": 1},
+                ],
+                "point_step": 16,
+                "row_step": 36,
+                "data": data,
+            }),
+        );
+
+        let color = rerun::Color::from_rgb(0x10, 0x20, 0x30);
+        assert_eq!(
+            points(&message.view(), &PointCloudOptions::default()).unwrap(),
+            rerun::Points3D::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.0]])
+                .with_colors([color, color, color])
+        );
+    }
+
+    #[test]
+    fn test_scalar_colors_and_big_endian() {
+        // x, y, z as int16, then an uint8 intensity, in an unorganized cloud
+        let message = TestMessage::new(
+            "sensor_msgs/msg/PointCloud2",
+            &json!({
+                "height": 1,
+                "width": 2,
+                "fields": [
+                    {"name": "x", "offset": 0, "datatype": INT16, "count": 1},
+                    {"name": "y", "offset": 2, "datatype": INT16, "count": 1},
+                    {"name": "z", "offset": 4, "datatype": INT16, "count": 1},
+                    {"name": "intensity", "offset": 6, "datatype": UINT8, "count": 1},
+                ],
+                "is_bigendian": true,
+                "point_step": 8,
+                "row_step": 16,
+                "data": [0, 1, 0, 2, 0xFF, 0xFD, 10, 0, 0, 0, 0, 0, 0, 0, 20, 0],
+            }),
+        );
+
+        let options = PointCloudOptions::new(Some("intensity"), Colormap::Grayscale, None);
+        assert_eq!(
+            points(&message.view(), &options).unwrap(),
+            rerun::Points3D::new([[1.0, 2.0, -3.0], [0.0, 0.0, 0.0]]).with_colors([
+                rerun::Color::from_rgb(0, 0, 0),
+                rerun::Color::from_rgb(255, 255, 255)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_invalid_clouds() {
+        let options = PointCloudOptions::default();
+        let fields = json!([
+            {"name": "x", "offset": 0, "datatype": FLOAT32, "count": 1},
+            {"name": "y", "offset": 4, "datatype": FLOAT32, "count": 1},
+            {"name": "z", "offset": 8, "datatype": FLOAT64, "count": 1},
+        ]);
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/PointCloud2",
+            &json!({
+                "height": 1,
+                "width": 1,
+                "fields": fields,
+                "point_step": 12,
+                "row_step": 12,
+                "data": vec![0; 12],
+            }),
+        );
+        assert_eq!(
+            points(&message.view(), &options).err().unwrap().to_string(),
+            "Field \"z\" at offset 8 does not fit in a point of 12 bytes"
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/PointCloud2",
+            &json!({
+                "height": 1,
+                "width": 2,
+                "fields": fields,
+                "point_step": 16,
+                "row_step": 32,
+                "data": vec![0; 31],
+            }),
+        );
+        assert_eq!(
+            points(&message.view(), &options).err().unwrap().to_string(),
+            "The data of 31 bytes is too short for 1 rows of 2 points of 16 bytes"
+        );
+
+        let message = TestMessage::new(
+            "sensor_msgs/msg/PointCloud2",
+            &json!({
+                "height": u32::MAX,
+                "width": 1,
+                "fields": fields,
+                "point_step": 16,
+                "row_step": 0,
+                "data": vec![0; 16],
+            }),
+        );
+        assert_eq!(
+            points(&message.view(), &options).err().unwrap().to_string(),
+            "The row step of 0 bytes is smaller than a row of 16 bytes"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use anyhow::{anyhow, Error, Result};
+use rerun::datatypes::{TensorBuffer, TensorData, TensorDimension};
+
+use crate::converters::colormap::unit_to_u8;
+use crate::converters::{CdrMessage, ConversionContext, Converter};
+use crate::ros_introspection::{BuiltinType, MsgSpec};
+use crate::value::Value;
+
+/// Logs the `data` of the numeric and boolean `std_msgs` wrappers as scalars, so that they are
+/// plotted over time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScalarConverter;
+
+impl ScalarConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "scalar";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 13] = [
+        "std_msgs/msg/Bool",
+        "std_msgs/msg/Byte",
+        "std_msgs/msg/Char",
+        "std_msgs/msg/Float32",
+        "std_msgs/msg/Float64",
+        "std_msgs/msg/Int8",
+        "std_msgs/msg/Int16",
+        "std_msgs/msg/Int32",
+        "std_msgs/msg/Int64",
+        "std_msgs/msg/UInt8",
+        "std_msgs/msg/UInt16",
+        "std_msgs/msg/UInt32",
+        "std_msgs/msg/UInt64",
+    ];
+}
+
+impl Converter for ScalarConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        stream.log(context.entity_path(), &scalar(value)?)?;
+        Ok(())
+    }
+}
+
+/// Logs `std_msgs/String` messages as text log entries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextLogConverter;
+
+impl TextLogConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "text_log";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["std_msgs/msg/String"];
+}
+
+impl Converter for TextLogConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        stream.log(context.entity_path(), &text_log(value)?)?;
+        Ok(())
+    }
+}
+
+/// Logs `std_msgs/ColorRGBA` messages as a color component, which the archetypes logged at the
+/// same entity path are drawn with.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ColorConverter;
+
+impl ColorConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "color";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["std_msgs/msg/ColorRGBA"];
+}
+
+impl Converter for ColorConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        let color = color(value)?;
+        stream.log_component_batches(
+            context.entity_path(),
+            false,
+            [&color as &dyn rerun::ComponentBatch],
+        )?;
+        Ok(())
+    }
+}
+
+/// Logs the `std_msgs` multi-dimensional arrays as tensors, shaped and labeled after their
+/// `MultiArrayLayout`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TensorConverter;
+
+impl TensorConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "tensor";
+
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 11] = [
+        "std_msgs/msg/ByteMultiArray",
+        "std_msgs/msg/Float32MultiArray",
+        "std_msgs/msg/Float64MultiArray",
+        "std_msgs/msg/Int8MultiArray",
+        "std_msgs/msg/Int16MultiArray",
+        "std_msgs/msg/Int32MultiArray",
+        "std_msgs/msg/Int64MultiArray",
+        "std_msgs/msg/UInt8MultiArray",
+        "std_msgs/msg/UInt16MultiArray",
+        "std_msgs/msg/UInt32MultiArray",
+        "std_msgs/msg/UInt64MultiArray",
+    ];
+}
+
+impl Converter for TensorConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let spec = message.spec();
+        let value = &message.decode()?;
+        stream.log(context.entity_path(), &tensor(spec, value)?)?;
+        Ok(())
+    }
+}
+
+fn scalar(value: &Value) -> Result<rerun::Scalar, Error> {
+    Ok(rerun::Scalar::new(value.get_f64("data")?))
+}
+
+fn text_log(value: &Value) -> Result<rerun::TextLog, Error> {
+    Ok(rerun::TextLog::new(value.get_str("data")?))
+}
+
+fn color(value: &Value) -> Result<rerun::Color, Error> {
+    Ok(rerun::Color::from_unmultiplied_rgba(
+        unit_to_u8(value.get_f64("r")?),
+        unit_to_u8(value.get_f64("g")?),
+        unit_to_u8(value.get_f64("b")?),
+        unit_to_u8(value.get_f64("a")?),
+    ))
+}
+
+fn tensor(spec: &MsgSpec, value: &Value) -> Result<rerun::Tensor, Error> {
+    let data = value.get("data")?;
+    let len = match data {
+        Value::Bytes(bytes) => bytes.len(),
+        _ => data.as_array()?.len(),
+    };
+    let data_offset = usize::try_from(value.get_u64("layout.data_offset")?)?;
+
+    let mut shape = Vec::new();
+    let mut sizes = Vec::new();
+    let mut strides = Vec::new();
+    let dims = value.get_array("layout.dim")?;
+    for (index, dim) in dims.iter().enumerate() {
+        let size = dim.get_u64("size")?;
+        let label = dim.get_str("label")?;
+        shape.push(if label.is_empty() {
+            TensorDimension::unnamed(size)
+        } else {
+            TensorDimension::named(size, label)
+        });
+        sizes.push(usize::try_from(size)?);
+        // The stride of a dimension spans all the dimensions that follow it, hence the
+        // elements of a dimension are the stride of the next one apart
+        strides.push(match dims.get(index + 1) {
+            Some(next) => usize::try_from(next.get_u64("stride")?)?,
+            None => 1,
+        });
+    }
+    if dims.is_empty() {
+        let size = len.saturating_sub(data_offset);
+        shape.push(TensorDimension::unnamed(size as u64));
+        sizes.push(size);
+        strides.push(1);
+    }
+
+    // Check the number of elements before listing them, so that a malicious layout cannot make
+    // the list arbitrarily large
+    let count = sizes
+        .iter()
+        .try_fold(1usize, |count, size| count.checked_mul(*size))
+        .filter(|count| *count <= len)
+        .ok_or_else(|| anyhow!("The layout has more elements than the {len} of the data"))?;
+    let mut indices = Vec::with_capacity(count);
+    indices.push(data_offset);
+    for (size, stride) in sizes.iter().zip(&strides) {
+        indices = indices
+            .iter()
+            .flat_map(|base| {
+                (0..*size).map(move |index| base.saturating_add(index.saturating_mul(*stride)))
+            })
+            .collect();
+    }
+    if let Some(last) = indices.iter().max().filter(|last| **last >= len) {
+        return Err(anyhow!(
+            "The layout addresses element {last}, but the data has {len}"
+        ));
+    }
+
+    let buffer = if let Value::Bytes(bytes) = data {
+        TensorBuffer::U8(
+            indices
+                .iter()
+                .map(|index| bytes[*index])
+                .collect::<Vec<_>>()
+                .into(),
+        )
+    } else {
+        tensor_buffer(element_type(spec)?, data.as_array()?, &indices)?
+    };
+    Ok(rerun::Tensor::new(TensorData::new(shape, buffer)))
+}
+
+/// Returns the builtin type of the `data` of a multi-dimensional array.
+fn element_type(spec: &MsgSpec) -> Result<&BuiltinType, Error> {
+    spec.data()
+        .fields()
+        .iter()
+        .find(|field| field.name() == "data" && !field.is_constant())
+        .map(|field| field.type_().id())
+        .ok_or_else(|| anyhow!("{} has no data field", spec.data().type_().name()))
+}
+
+/// Gathers the elements at the given indices into a tensor buffer of the element type.
+fn tensor_buffer(
+    element_type: &BuiltinType,
+    elements: &[Value],
+    indices: &[usize],
+) -> Result<TensorBuffer, Error> {
+    macro_rules! gather {
+        ($variant:ident, $buffer:ident) => {
+            TensorBuffer::$buffer(
+                indices
+                    .iter()
+                    .map(|index| match elements[*index] {
+                        Value::$variant(element) => Ok(element),
+                        ref other => Err(anyhow!(
+                            "Expected {} elements, found {}",
+                            Value::$variant(Default::default()).kind(),
+                            other.kind()
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?
+                    .into(),
+            )
+        };
+    }
+
+    Ok(match element_type {
+        BuiltinType::Int8 => gather!(Int8, I8),
+        BuiltinType::Byte | BuiltinType::Char | BuiltinType::Uint8 => gather!(Uint8, U8),
+        BuiltinType::Int16 => gather!(Int16, I16),
+        BuiltinType::Uint16 => gather!(Uint16, U16),
+        BuiltinType::Int32 => gather!(Int32, I32),
+        BuiltinType::Uint32 => gather!(Uint32, U32),
+        BuiltinType::Int64 => gather!(Int64, I64),
+        BuiltinType::Uint64 => gather!(Uint64, U64),
+        BuiltinType::Float32 => gather!(Float32, F32),
+        BuiltinType::Float64 => gather!(Float64, F64),
+        other => return Err(anyhow!("Tensors of {other:?} are not supported")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[test]
+    fn test_scalar_text_log_and_color() {
+        let int32 = TestMessage::new("std_msgs/msg/Int32", &json!({"data": -4})).value();
+        assert_eq!(scalar(&int32).unwrap(), rerun::Scalar::new(-4.0));
+        let boolean = TestMessage::new("std_msgs/msg/Bool", &json!({"data": true})).value();
+        assert_eq!(scalar(&boolean).unwrap(), rerun::Scalar::new(1.0));
+        let string = TestMessage::new("std_msgs/msg/String", &json!({"data": "hello"})).value();
+        assert_eq!(text_log(&string).unwrap(), rerun::TextLog::new("hello"));
+        assert!(text_log(&int32).is_err());
+
+        let rgba = TestMessage::new(
+            "std_msgs/msg/ColorRGBA",
+            &json!({"r": 1.0, "g": 0.5, "b": -1.0, "a": 2.0}),
+        )
+        .value();
+        assert_eq!(
+            color(&rgba).unwrap(),
+            rerun::Color::from_unmultiplied_rgba(255, 128, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_tensor_with_padding() {
+        // Two rows of three elements, each padded to four, after an offset of one
+        let message = TestMessage::new(
+            "std_msgs/msg/Float32MultiArray",
+            &json!({
+                "layout": {
+                    "dim": [
+                        {"label": "rows", "size": 2, "stride": 8},
+                        {"label": "cols", "size": 3, "stride": 4},
+                    ],
+                    "data_offset": 1,
+                },
+                "data": [9.0, 0.0, 1.0, 2.0, 9.0, 3.0, 4.0, 5.0, 9.0],
+            }),
+        );
+
+        assert_eq!(
+            tensor(message.spec(), &message.value()).unwrap(),
+            rerun::Tensor::new(TensorData::new(
+                vec![
+                    TensorDimension::named(2, "rows"),
+                    TensorDimension::named(3, "cols")
+                ],
+                TensorBuffer::F32(vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0].into()),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tensor_without_layout() {
+        let message = TestMessage::new("std_msgs/msg/UInt8MultiArray", &json!({"data": [1, 2, 3]}));
+        assert_eq!(
+            tensor(message.spec(), &message.value()).unwrap(),
+            rerun::Tensor::new(TensorData::new(
+                vec![TensorDimension::unnamed(3)],
+                TensorBuffer::U8(vec![1, 2, 3].into()),
+            ))
+        );
+
+        let message = TestMessage::new("std_msgs/msg/Int64MultiArray", &json!({}));
+        assert_eq!(
+            tensor(message.spec(), &message.value()).unwrap(),
+            rerun::Tensor::new(TensorData::new(
+                vec![TensorDimension::unnamed(0)],
+                TensorBuffer::I64(vec![].into()),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tensor_invalid_layout() {
+        let too_large = TestMessage::new(
+            "std_msgs/msg/Int32MultiArray",
+            &json!({
+                "layout": {
+                    "dim": [
+                        {"label": "x", "size": u32::MAX, "stride": u32::MAX},
+                        {"label": "y", "size": 4, "stride": 1},
+                    ],
+                },
+                "data": [1, 2],
+            }),
+        );
+        assert_eq!(
+            tensor(too_large.spec(), &too_large.value())
+                .err()
+                .unwrap()
+                .to_string(),
+            "The layout has more elements than the 2 of the data"
+        );
+
+        let out_of_range = TestMessage::new(
+            "std_msgs/msg/Int32MultiArray",
+            &json!({
+                "layout": {"dim": [{"label": "x", "size": 2, "stride": 2}], "data_offset": 1},
+                "data": [1, 2],
+            }),
+        );
+        assert_eq!(
+            tensor(out_of_range.spec(), &out_of_range.value())
+                .err()
+                .unwrap()
+                .to_string(),
+            "The layout addresses element 2, but the data has 2"
+        );
+    }
+}
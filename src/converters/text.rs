@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use serde::Deserialize;
+
+use crate::converters::{parse_options, CdrMessage, ConversionContext, Converter};
+use crate::render::{render, RenderOptions, TextFormat};
+
+/// The options of the text document converter, from the options table of a conversion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TextDocumentOptions {
+    fields: Option<Vec<String>>,
+}
+
+impl TextDocumentOptions {
+    /// Creates a new `TextDocumentOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The paths of the fields to show, such as `header.stamp`, or `None` to show
+    ///   whole messages.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `TextDocumentOptions` instance.
+    pub fn new(fields: Option<Vec<String>>) -> Self {
+        Self { fields }
+    }
+
+    /// Returns the paths of the fields to show.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&[String]>` - The paths of the fields, or `None` if whole messages are shown.
+    pub fn fields(&self) -> Option<&[String]> {
+        self.fields.as_deref()
+    }
+}
+
+/// Logs messages of any type as YAML text documents, for the types that have no converter of
+/// their own.
+///
+/// Only the fields chosen in the options are decoded and shown, if any.
+#[derive(Debug, Clone)]
+pub struct TextDocumentConverter {
+    render_options: RenderOptions,
+    options: TextDocumentOptions,
+}
+
+impl TextDocumentConverter {
+    /// The name of the converter in the configuration.
+    pub const NAME: &'static str = "text_document";
+
+    /// Creates a new `TextDocumentConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `render_options` - The `RenderOptions` of the documents.
+    /// * `options` - The `TextDocumentOptions` of the converter.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `TextDocumentConverter` instance.
+    pub fn new(render_options: RenderOptions, options: TextDocumentOptions) -> Self {
+        Self {
+            render_options,
+            options,
+        }
+    }
+}
+
+impl Default for TextDocumentConverter {
+    fn default() -> Self {
+        Self::new(
+            RenderOptions::new(TextFormat::Yaml, Some(128)),
+            TextDocumentOptions::default(),
+        )
+    }
+}
+
+impl Converter for TextDocumentConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let spec = message.spec();
+        let value = &message.decode()?;
+        let text = render(spec, value, &self.render_options)?;
+        stream.log(context.entity_path(), &rerun::TextDocument::new(text))?;
+        Ok(())
+    }
+
+    fn field_paths(&self) -> Option<Vec<String>> {
+        self.options.fields().map(<[String]>::to_vec)
+    }
+
+    fn configure(&self, options: &toml::Table) -> Result<Arc<dyn Converter>, Error> {
+        Ok(Arc::new(Self::new(
+            self.render_options,
+            parse_options(options)?,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[test]
+    fn test_configure() {
+        let converter = TextDocumentConverter::default();
+        assert_eq!(converter.field_paths(), None);
+
+        let options: toml::Table = toml::from_str(r#"fields = ["header.stamp"]"#).unwrap();
+        let configured = converter.configure(&options).unwrap();
+        assert_eq!(
+            configured.field_paths(),
+            Some(vec!["header.stamp".to_owned()])
+        );
+
+        let options: toml::Table = toml::from_str("depth = 2").unwrap();
+        assert!(converter.configure(&options).is_err());
+    }
+
+    #[test]
+    fn test_render_selected_fields() {
+        let message = TestMessage::new(
+            "geometry_msgs/msg/PoseStamped",
+            &json!({
+                "header": {"stamp": {"sec": 12, "nanosec": 5}, "frame_id": "map"},
+                "pose": {"position": {"x": 1.5}},
+            }),
+        );
+        let value = message.projected_value(&["header.stamp"]);
+
+        let text = render(
+            message.spec(),
+            &value,
+            &TextDocumentConverter::default().render_options,
+        )
+        .unwrap();
+        assert_eq!(text, "header:\n  stamp:\n    sec: 12\n    nanosec: 5\n");
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use parking_lot::Mutex;
+
+use crate::converters::{
+    header_stamp, normalize_type_name, CdrMessage, ConversionContext, Converter,
+};
+use crate::value::Value;
+
+/// The topic of the transforms that change over time.
+pub const TF_TOPIC: &str = "/tf";
+
+/// The topic of the transforms that never change, published once with a transient local
+/// durability.
+pub const TF_STATIC_TOPIC: &str = "/tf_static";
+
+/// Tells whether a topic carries transforms, and whether they are static.
+///
+/// Topic names are compared once fully qualified in the root namespace, so that `tf` is the
+/// same topic as `/tf`.
+///
+/// # Arguments
+///
+/// * `topic_name` - A string slice that holds the name of the topic.
+///
+/// # Returns
+///
+/// * `Option<bool>` - `Some(true)` for [`TF_STATIC_TOPIC`], `Some(false)` for [`TF_TOPIC`], and
+///   `None` for any other topic.
+pub fn tf_topic_is_static(topic_name: &str) -> Option<bool> {
+    let topic_name = format!("/{}", topic_name.trim_start_matches('/'));
+    match topic_name.as_str() {
+        TF_TOPIC => Some(false),
+        TF_STATIC_TOPIC => Some(true),
+        _ => None,
+    }
+}
+
+/// The latest transform of a frame into its parent, kept to log it again when the entity path
+/// of the frame changes.
+#[derive(Debug, Clone)]
+struct FrameTransform {
+    archetype: rerun::Transform3D,
+    is_static: bool,
+}
+
+impl FrameTransform {
+    fn log(&self, stream: &rerun::RecordingStream, entity_path: &str) -> Result<()> {
+        if self.is_static {
+            stream.log_static(entity_path, &self.archetype)?;
+        } else {
+            stream.log(entity_path, &self.archetype)?;
+        }
+        Ok(())
+    }
+}
+
+/// The parent and latest transform of each frame seen in the transforms, from which the entity
+/// paths of the frames are built.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTree {
+    parents: BTreeMap<String, String>,
+    transforms: HashMap<String, FrameTransform>,
+}
+
+impl FrameTree {
+    /// Records the transform of a frame into its parent, replacing its previous parent and
+    /// transform if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The ID of the parent frame.
+    /// * `child` - The ID of the child frame.
+    /// * `transform` - The transform from the child frame into the parent frame.
+    /// * `is_static` - Whether the transform never changes.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The previous entity path of the child frame if the new parent
+    ///   moved it, along with all of its descendants, or `None` if it did not move.
+    pub fn insert(
+        &mut self,
+        parent: &str,
+        child: &str,
+        transform: rerun::Transform3D,
+        is_static: bool,
+    ) -> Option<String> {
+        let previous_path = self.entity_path(child);
+        let child = frame_name(child);
+        self.parents
+            .insert(child.to_owned(), frame_name(parent).to_owned());
+        self.transforms.insert(
+            child.to_owned(),
+            FrameTransform {
+                archetype: transform,
+                is_static,
+            },
+        );
+        (self.entity_path(child) != previous_path).then_some(previous_path)
+    }
+
+    /// Returns the entity path of a frame, which is the path of its parent followed by its ID,
+    /// up to a frame without a known parent.
+    ///
+    /// The path of a frame changes if the parent of one of its ancestors is only learned later,
+    /// as is the case when a static transform is received after a dynamic one, which
+    /// [`Self::insert`] reports.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_id` - The ID of the frame.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The entity path of the frame, such as `map/odom/base_link`.
+    pub fn entity_path(&self, frame_id: &str) -> String {
+        let mut frames = vec![frame_name(frame_id)];
+        while let Some(parent) = frames.last().and_then(|frame| self.parents.get(*frame)) {
+            // A cycle would never reach a root
+            if frames.contains(&parent.as_str()) {
+                break;
+            }
+            frames.push(parent);
+        }
+        frames.reverse();
+        frames.join("/")
+    }
+
+    /// Returns the entity path of data in a frame, under the entity path of the frame so that
+    /// the data is placed by the transforms of the frame and its ancestors.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_id` - The ID of the frame of the data, or an empty string if it has none.
+    /// * `entity_path` - The entity path of the data, relative to its frame.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The entity path, such as `map/odom/base_link/laser/scan`.
+    pub fn data_entity_path(&self, frame_id: &str, entity_path: &str) -> String {
+        if frame_name(frame_id).is_empty() {
+            return entity_path.to_owned();
+        }
+        format!(
+            "{}/{}",
+            self.entity_path(frame_id),
+            entity_path.trim_start_matches('/')
+        )
+    }
+
+    /// Returns the context of a message whose data is logged under the entity of its frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - A reference to the `ConversionContext` of the message, whose entity path
+    ///   is relative to its frame.
+    ///
+    /// # Returns
+    ///
+    /// * `ConversionContext` - The context of the message, at its entity path under the frame.
+    pub fn frame_context(&self, context: &ConversionContext) -> ConversionContext {
+        ConversionContext::new(
+            &self.data_entity_path(context.frame_id(), context.entity_path()),
+            context.frame_id(),
+            context.stamp(),
+        )
+    }
+
+    /// Returns a frame and its descendants, with their entity paths, parents first.
+    fn subtree<'a>(&'a self, frame_id: &'a str) -> Vec<(&'a str, String)> {
+        let root = frame_name(frame_id);
+        let mut frames = vec![root];
+        let mut index = 0;
+        while let Some(&frame) = frames.get(index) {
+            for (child, parent) in &self.parents {
+                // A cycle would lead back to a frame already visited
+                if parent == frame && !frames.contains(&child.as_str()) {
+                    frames.push(child);
+                }
+            }
+            index += 1;
+        }
+        frames
+            .into_iter()
+            .map(|frame| (frame, self.entity_path(frame)))
+            .collect()
+    }
+
+    /// Moves the entities of a frame and its descendants from the previous entity path of the
+    /// frame to their current ones.
+    ///
+    /// The previous entities are cleared, along with the data logged under them, and the
+    /// latest transforms of the frames are logged again at their current paths. The clear is
+    /// static if any of the transforms moved was, since only a static clear hides static data.
+    fn relocate(
+        &self,
+        stream: &rerun::RecordingStream,
+        frame_id: &str,
+        previous_path: &str,
+    ) -> Result<()> {
+        let subtree = self.subtree(frame_id);
+        let transforms: Vec<_> = subtree
+            .iter()
+            .filter_map(|(frame, path)| Some((self.transforms.get(*frame)?, path)))
+            .collect();
+
+        if transforms.iter().any(|(transform, _)| transform.is_static) {
+            stream.log_static(previous_path, &rerun::Clear::recursive())?;
+        } else {
+            stream.log(previous_path, &rerun::Clear::recursive())?;
+        }
+        for (transform, path) in transforms {
+            transform.log(stream, path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Strips the leading slash of the frame IDs of ROS 1, so that both spellings are the same frame.
+fn frame_name(frame_id: &str) -> &str {
+    frame_id.trim_start_matches('/')
+}
+
+/// Logs `tf2_msgs/TFMessage` messages as 3D transforms, at entity paths that mirror the tree of
+/// the frames.
+///
+/// The converter is not in the `ConverterRegistry`, since the converters of [`TF_TOPIC`] and
+/// [`TF_STATIC_TOPIC`] must share their tree.
+#[derive(Debug, Clone)]
+pub struct TfConverter {
+    tree: Arc<Mutex<FrameTree>>,
+    is_static: bool,
+}
+
+impl TfConverter {
+    /// The ROS types the converter handles by default.
+    pub const ROS_TYPES: [&'static str; 1] = ["tf2_msgs/msg/TFMessage"];
+
+    /// Creates a new `TfConverter` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The `FrameTree` the entity paths are built from, shared by the converters of
+    ///   the dynamic and static transforms.
+    /// * `is_static` - Whether the transforms never change, in which case they are logged as
+    ///   static.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `TfConverter` instance.
+    pub fn new(tree: Arc<Mutex<FrameTree>>, is_static: bool) -> Self {
+        Self { tree, is_static }
+    }
+
+    /// Tells whether the converter handles a ROS type.
+    ///
+    /// # Arguments
+    ///
+    /// * `ros_type` - A string slice that holds the ROS type, such as `tf2_msgs/msg/TFMessage`.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the type is one of [`Self::ROS_TYPES`].
+    pub fn handles(ros_type: &str) -> bool {
+        let ros_type = normalize_type_name(ros_type);
+        Self::ROS_TYPES
+            .iter()
+            .any(|candidate| normalize_type_name(candidate) == ros_type)
+    }
+
+    /// Returns whether the transforms are logged as static.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the transforms are logged as static.
+    pub fn is_static(&self) -> bool {
+        self.is_static
+    }
+}
+
+impl Converter for TfConverter {
+    fn convert(
+        &self,
+        stream: &rerun::RecordingStream,
+        _context: &ConversionContext,
+        message: &CdrMessage<'_>,
+    ) -> Result<(), Error> {
+        let value = &message.decode()?;
+        for transform in value.get_array("transforms")? {
+            let (parent, child, archetype) = transform_stamped(transform)?;
+            if !self.is_static {
+                // Each transform has its own stamp
+                ConversionContext::new("", parent, header_stamp(transform)).set_time(stream);
+            }
+
+            let mut tree = self.tree.lock();
+            match tree.insert(parent, child, archetype, self.is_static) {
+                Some(previous_path) => tree.relocate(stream, child, &previous_path)?,
+                None => tree.transforms[frame_name(child)].log(stream, &tree.entity_path(child))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts a `geometry_msgs/TransformStamped` into the IDs of its parent and child frames, and
+/// the transform from the child frame into the parent frame.
+#[allow(clippy::cast_possible_truncation)]
+fn transform_stamped(value: &Value) -> Result<(&str, &str, rerun::Transform3D), Error> {
+    let parent = value.get_str("header.frame_id")?;
+    let child = value.get_str("child_frame_id")?;
+    let translation =
+        ["x", "y", "z"].map(|axis| value.get_f64(&format!("transform.translation.{axis}")));
+    let rotation =
+        ["x", "y", "z", "w"].map(|axis| value.get_f64(&format!("transform.rotation.{axis}")));
+    let [x, y, z] = translation;
+    let [qx, qy, qz, qw] = rotation;
+    let archetype = rerun::Transform3D::from_translation_rotation(
+        [x? as f32, y? as f32, z? as f32],
+        rerun::Quaternion::from_xyzw([qx? as f32, qy? as f32, qz? as f32, qw? as f32]),
+    );
+    Ok((parent, child, archetype))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::converters::fixtures::TestMessage;
+
+    #[test]
+    fn test_transform_stamped() {
+        let value = TestMessage::new(
+            "tf2_msgs/msg/TFMessage",
+            &json!({
+                "transforms": [{
+                    "header": {"stamp": {"sec": 1, "nanosec": 5}, "frame_id": "odom"},
+                    "child_frame_id": "base_link",
+                    "transform": {
+                        "translation": {"x": 1.0, "y": 2.0, "z": 3.0},
+                        "rotation": {"x": 0.0, "y": 0.0, "z": 0.5, "w": 0.75},
+                    },
+                }],
+            }),
+        )
+        .value();
+        let transform = &value.get_array("transforms").unwrap()[0];
+        assert_eq!(
+            transform_stamped(transform).unwrap(),
+            (
+                "odom",
+                "base_link",
+                rerun::Transform3D::from_translation_rotation(
+                    [1.0, 2.0, 3.0],
+                    rerun::Quaternion::from_xyzw([0.0, 0.0, 0.5, 0.75])
+                )
+            )
+        );
+        assert_eq!(header_stamp(transform), Some(1_000_000_005));
+    }
+
+    fn identity() -> rerun::Transform3D {
+        rerun::Transform3D::from_translation_rotation(
+            [0.0, 0.0, 0.0],
+            rerun::Quaternion::from_xyzw([0.0, 0.0, 0.0, 1.0]),
+        )
+    }
+
+    #[test]
+    fn test_handles() {
+        assert!(TfConverter::handles("tf2_msgs/msg/TFMessage"));
+        assert!(TfConverter::handles("tf2_msgs/TFMessage"));
+        assert!(!TfConverter::handles("geometry_msgs/msg/TransformStamped"));
+    }
+
+    #[test]
+    fn test_tf_topic_is_static() {
+        assert_eq!(tf_topic_is_static("/tf"), Some(false));
+        assert_eq!(tf_topic_is_static("tf"), Some(false));
+        assert_eq!(tf_topic_is_static("tf_static"), Some(true));
+        assert_eq!(tf_topic_is_static("/robot/tf"), None);
+    }
+
+    #[test]
+    fn test_frame_tree() {
+        let mut tree = FrameTree::default();
+        assert_eq!(tree.entity_path("base_link"), "base_link");
+
+        tree.insert("odom", "base_link", identity(), false);
+        tree.insert("/map", "odom", identity(), true);
+        tree.insert("base_link", "laser", identity(), true);
+        assert_eq!(tree.entity_path("laser"), "map/odom/base_link/laser");
+        assert_eq!(tree.entity_path("/odom"), "map/odom");
+
+        // A cycle stops at the first repeated frame
+        tree.insert("laser", "map", identity(), false);
+        assert_eq!(tree.entity_path("odom"), "base_link/laser/map/odom");
+    }
+
+    #[test]
+    fn test_frame_tree_moves() {
+        let mut tree = FrameTree::default();
+        assert_eq!(
+            tree.insert("base_link", "laser", identity(), true),
+            Some("laser".to_owned())
+        );
+        assert_eq!(tree.insert("base_link", "laser", identity(), true), None);
+
+        // The parent of base_link is only learned later, which moves it along with laser
+        assert_eq!(
+            tree.insert("odom", "base_link", identity(), false),
+            Some("base_link".to_owned())
+        );
+        assert_eq!(
+            tree.subtree("base_link"),
+            [
+                ("base_link", "odom/base_link".to_owned()),
+                ("laser", "odom/base_link/laser".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_under_its_frame() {
+        let mut tree = FrameTree::default();
+        let value = TestMessage::new(
+            "tf2_msgs/msg/TFMessage",
+            &json!({
+                "transforms": [
+                    {"header": {"frame_id": "map"}, "child_frame_id": "odom"},
+                    {"header": {"frame_id": "odom"}, "child_frame_id": "base_link"},
+                    {"header": {"frame_id": "base_link"}, "child_frame_id": "laser"},
+                ],
+            }),
+        )
+        .value();
+        for transform in value.get_array("transforms").unwrap() {
+            let (parent, child, archetype) = transform_stamped(transform).unwrap();
+            tree.insert(parent, child, archetype, false);
+        }
+
+        let scan = TestMessage::new(
+            "sensor_msgs/msg/LaserScan",
+            &json!({"header": {"stamp": {"sec": 2}, "frame_id": "laser"}, "ranges": [1.0]}),
+        );
+        let context = ConversionContext::new("/scan", "laser", scan.message().stamp());
+        let context = tree.frame_context(&context);
+        assert_eq!(context.entity_path(), "map/odom/base_link/laser/scan");
+        assert_eq!(
+            context.entity_path(),
+            format!("{}/scan", tree.entity_path("laser"))
+        );
+        assert_eq!(context.stamp(), Some(2_000_000_000));
+
+        // Data without a frame stays where it is
+        let context = tree.frame_context(&ConversionContext::new("/scan", "", None));
+        assert_eq!(context.entity_path(), "/scan");
+    }
+}
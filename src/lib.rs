@@ -1,3 +1,10 @@
 //! This module provides functionalities for parsing ROS messages.
+pub mod cdr;
 pub mod config;
+pub mod converters;
+pub mod render;
 pub mod ros_introspection;
+pub mod sink;
+#[cfg(feature = "type-description")]
+pub mod type_description_client;
+pub mod value;
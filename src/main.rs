@@ -1,49 +1,477 @@
-use anyhow::{Error, Result};
-use clap::Parser;
+use anyhow::{anyhow, Error, Result};
+use clap::{Parser, Subcommand};
+use parking_lot::Mutex;
+use rerun_ros::cdr::{decode, DecodePlan, Projection};
 use rerun_ros::config::ConfigParser;
+use rerun_ros::converters::{
+    camera_entity_path, tf_topic_is_static, CdrMessage, ConversionContext, Converter,
+    ConverterRegistry, FrameTree, TfConverter, TF_STATIC_TOPIC, TF_TOPIC,
+};
+use rerun_ros::render::{render, RenderOptions, TextFormat};
+use rerun_ros::ros_introspection::{list_interfaces, MsgSpec};
+use rerun_ros::sink::{RecordingOptions, Sink, DEFAULT_APPLICATION_ID, DEFAULT_SERVE_BIND_IP};
+#[cfg(feature = "type-description")]
+use rerun_ros::type_description_client;
 use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// How long to wait for a publisher to answer a type description request.
+#[cfg(feature = "type-description")]
+const TYPE_DESCRIPTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
 /// A bridge between rerun and ROS
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct BridgeArgs {
     /// Path to the configuration file in TOML format
     #[arg(short, long)]
-    config_file: String,
+    config_file: Option<String>,
+
+    /// Spawn a viewer, or reuse the one already running; this is the default
+    #[arg(long, group = "sink")]
+    spawn: bool,
+
+    /// Stream to a viewer listening at this address, 127.0.0.1:9876 by default
+    #[arg(
+        long,
+        group = "sink",
+        value_name = "ADDR",
+        num_args = 0..=1,
+        default_missing_value = "127.0.0.1:9876"
+    )]
+    connect: Option<SocketAddr>,
+
+    /// Serve a web viewer, and stream to it over WebSocket
+    #[arg(long, group = "sink")]
+    serve: bool,
+
+    /// Save to an `.rrd` file
+    #[arg(long, group = "sink", value_name = "PATH")]
+    save: Option<PathBuf>,
+
+    /// Memory the server keeps for the viewers that connect later when serving, such as `25%`
+    /// or `2GB`
+    #[arg(long, default_value = "25%")]
+    server_memory_limit: String,
+
+    /// Address the web viewer and its WebSocket server listen on when serving
+    #[arg(long, value_name = "IP", default_value_t = DEFAULT_SERVE_BIND_IP)]
+    bind_ip: IpAddr,
+
+    /// Open the web viewer in a browser when serving
+    #[arg(long)]
+    open_browser: bool,
+
+    /// Application ID of the recording
+    #[arg(long, default_value = DEFAULT_APPLICATION_ID)]
+    application_id: String,
+
+    /// Recording ID, to merge the data of several bridges into a single recording
+    #[arg(long)]
+    recording_id: Option<String>,
+
+    /// Do not subscribe to the transforms on /tf and /tf_static
+    #[arg(long)]
+    no_tf: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// List the message, service and action interfaces of every package in the ament index,
+    /// and check that they parse
+    ListInterfaces {
+        /// Only list the interfaces that fail to parse
+        #[arg(long)]
+        errors_only: bool,
+    },
+
+    /// Print the messages published on a topic, like `ros2 topic echo`, to debug their decoding
+    Echo {
+        /// Name of the topic
+        topic: String,
+
+        /// Type of the messages, such as `sensor_msgs/msg/Imu`
+        message_type: String,
+
+        /// Output format, `yaml` or `json`
+        #[arg(long, default_value_t = TextFormat::Yaml)]
+        format: TextFormat,
+
+        /// Number of elements or characters after which arrays and strings are truncated
+        #[arg(long, default_value_t = 128)]
+        truncate_length: usize,
+
+        /// Print arrays and strings in full
+        #[arg(long)]
+        full_length: bool,
+
+        /// Exit after printing the first message
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+impl BridgeArgs {
+    /// Returns the configuration of the recording, spawning a viewer unless another sink is
+    /// chosen.
+    fn recording_options(&self) -> RecordingOptions {
+        let sink = if let Some(addr) = self.connect {
+            Sink::Connect(addr)
+        } else if self.serve {
+            Sink::Serve {
+                bind_ip: self.bind_ip,
+                memory_limit: self.server_memory_limit.clone(),
+                open_browser: self.open_browser,
+            }
+        } else if let Some(path) = &self.save {
+            Sink::Save(path.clone())
+        } else {
+            Sink::Spawn
+        };
+        RecordingOptions::new(&self.application_id, self.recording_id.as_deref(), sink)
+    }
 }
 
 fn main() -> Result<(), Error> {
     let bridge_args = BridgeArgs::parse();
 
-    if bridge_args.config_file.is_empty() {
+    match bridge_args.command {
+        Some(Command::ListInterfaces { errors_only }) => return run_list_interfaces(errors_only),
+        Some(Command::Echo {
+            topic,
+            message_type,
+            format,
+            truncate_length,
+            full_length,
+            once,
+        }) => {
+            let truncate_length = (!full_length).then_some(truncate_length);
+            let options = RenderOptions::new(format, truncate_length);
+            return run_echo(&topic, &message_type, options, once);
+        }
+        None => {}
+    }
+
+    let recording_options = bridge_args.recording_options();
+    let config_file = bridge_args
+        .config_file
+        .ok_or(anyhow!("A configuration file is required, see --help"))?;
+    run_bridge(&config_file, &recording_options, !bridge_args.no_tf)
+}
+
+/// Prints every interface in the ament index, grouped by package.
+///
+/// # Errors
+///
+/// This function will return an error if the ament index cannot be read, or if any package
+/// has no share directory or any interface fails to parse.
+fn run_list_interfaces(errors_only: bool) -> Result<(), Error> {
+    let mut num_errors = 0;
+
+    for package in list_interfaces()? {
+        if let Some(error) = package.error() {
+            num_errors += 1;
+            println!("{} [error]", package.name());
+            println!("      {error}");
+            continue;
+        }
+
+        let interfaces: Vec<_> = package
+            .interfaces()
+            .iter()
+            .filter(|interface| !errors_only || !interface.is_valid())
+            .collect();
+        if interfaces.is_empty() {
+            continue;
+        }
+
+        println!("{}", package.name());
+        for interface in interfaces {
+            match interface.error() {
+                None => println!("  {}/{}", interface.kind(), interface.name()),
+                Some(error) => {
+                    num_errors += 1;
+                    println!("  {}/{} [error]", interface.kind(), interface.name());
+                    for line in error.lines() {
+                        println!("      {line}");
+                    }
+                }
+            }
+        }
+    }
+
+    if num_errors > 0 {
+        return Err(anyhow!(
+            "{num_errors} package(s) or interface(s) failed to load"
+        ));
+    }
+    Ok(())
+}
+
+/// Prints every message published on a topic, until interrupted.
+///
+/// Messages that cannot be decoded are reported on stderr, without stopping.
+///
+/// # Errors
+///
+/// This function will return an error if the message type cannot be resolved, or the ROS node
+/// cannot be set up.
+fn run_echo(
+    topic: &str,
+    message_type: &str,
+    options: RenderOptions,
+    once: bool,
+) -> Result<(), Error> {
+    let msg_spec = MsgSpec::new(message_type)?;
+
+    let context = rclrs::Context::new(env::args())?;
+    let node = rclrs::create_node(&context, "rerun_ros_echo")?;
+
+    let received = Arc::new(AtomicBool::new(false));
+    let callback_received = Arc::clone(&received);
+    let _subscription = node.create_generic_subscription(
+        topic,
+        message_type,
+        rclrs::QOS_PROFILE_DEFAULT,
+        move |msg: rclrs::SerializedMessage| {
+            let rendered = decode(&msg_spec, msg.as_slice())
+                .and_then(|value| render(&msg_spec, &value, &options));
+            match rendered {
+                Ok(text) => println!("{text}---"),
+                Err(err) => eprintln!("{err:#}"),
+            }
+            callback_received.store(true, Ordering::Relaxed);
+        },
+    )?;
+
+    if once {
+        while !received.load(Ordering::Relaxed) {
+            rclrs::spin_once(Arc::clone(&node), None)?;
+        }
+        return Ok(());
+    }
+    rclrs::spin(node)?;
+    Ok(())
+}
+
+/// Returns the quality of service of the transforms, static transforms being published once and
+/// kept for the subscribers that join later.
+fn tf_qos(is_static: bool) -> rclrs::QoSProfile {
+    if is_static {
+        rclrs::QoSProfile {
+            durability: rclrs::QoSDurabilityPolicy::TransientLocal,
+            ..rclrs::QOS_PROFILE_DEFAULT
+        }
+    } else {
+        rclrs::QOS_PROFILE_DEFAULT
+    }
+}
+
+/// Subscribes to the topics of the configuration file and forwards them to rerun, until
+/// interrupted.
+///
+/// Messages are logged at the entity path of their topic by the converter of their type, or the
+/// one chosen in the configuration. Camera infos logged as pinholes are logged at the entity path
+/// of the images that share their frame ID. When the transforms are subscribed to, these entity paths are relative
+/// to the entity of the frame ID, and the transforms on `/tf` and `/tf_static` always go to the
+/// converters sharing the tree of the frames. Messages that cannot be decoded or logged are
+/// reported on stderr, without stopping.
+///
+/// # Errors
+///
+/// This function will return an error if the configuration cannot be read, a message type or
+/// converter cannot be resolved, the recording cannot be created, or the ROS node cannot be set
+/// up.
+fn run_bridge(
+    config_file: &str,
+    recording_options: &RecordingOptions,
+    subscribe_tf: bool,
+) -> Result<(), Error> {
+    if config_file.is_empty() {
         return Ok(());
     }
 
     println!("Starting bridge");
-    let config_parser = ConfigParser::new(&bridge_args.config_file)?;
+    let config_parser = ConfigParser::new(config_file)?;
 
     let context = rclrs::Context::new(env::args())?;
     let node = rclrs::create_node(&context, "rerun_ros_bridge")?;
     // Clippy does not like iterating over the keys of a HashMap, so we collect it into a Vec
     let config_entries: Vec<_> = config_parser.conversions().iter().collect();
 
+    let registry = ConverterRegistry::new();
+    // The dynamic and static transforms share the tree their entity paths are built from, under
+    // which the other messages are logged
+    let tree = Arc::new(Mutex::new(FrameTree::default()));
+    let tf_converter = |is_static| -> Arc<dyn Converter> {
+        Arc::new(TfConverter::new(Arc::clone(&tree), is_static))
+    };
+    let configures_tf = config_parser
+        .conversions()
+        .keys()
+        .any(|(topic_name, _)| tf_topic_is_static(topic_name).is_some());
+    let log_under_frames = subscribe_tf || configures_tf;
+
+    // Resolve every type up front, so that all missing definitions are reported at once
+    let mut msg_specs = Vec::new();
+    let mut reports = Vec::new();
+    for (key, (ros_type, entity_path)) in config_entries {
+        let (topic_name, frame_id) = key;
+        let settings = config_parser
+            .settings()
+            .get(key)
+            .cloned()
+            .unwrap_or_default();
+        let is_static_tf = tf_topic_is_static(topic_name);
+        let converter = match is_static_tf {
+            Some(_) if !TfConverter::handles(ros_type) => Err(anyhow!(
+                "The transforms must be of type {}, not {ros_type}",
+                TfConverter::ROS_TYPES[0]
+            )),
+            Some(is_static) => Ok(tf_converter(is_static)),
+            None => registry.resolve(ros_type, settings.converter(), settings.options()),
+        };
+        let converter = match converter {
+            Ok(converter) => converter,
+            Err(err) => {
+                reports.push(format!("{topic_name}: {err}"));
+                continue;
+            }
+        };
+
+        let msg_spec = MsgSpec::new_with_report(ros_type).map_err(|report| report.to_string());
+
+        // Definitions that are not installed locally may still be known to their publishers.
+        // They are only used for decoding, subscribing still needs the typesupport of the type
+        #[cfg(feature = "type-description")]
+        let msg_spec = msg_spec.or_else(|report| {
+            type_description_client::fetch_msg_spec(
+                &node,
+                topic_name,
+                ros_type,
+                TYPE_DESCRIPTION_TIMEOUT,
+            )
+            .map_err(|err| format!("{report}\n  Fetching the type description failed: {err}"))
+        });
+
+        // Camera infos are logged at the entity path of the images of the same frame, so that
+        // the images are projected by the pinhole, unless the configuration logs them otherwise
+        let converter_name = registry.resolve_name(ros_type, settings.converter());
+        let entity_path = camera_entity_path(config_parser.conversions(), converter_name, frame_id)
+            .unwrap_or(entity_path.as_str());
+
+        // The transforms are logged at the entity paths of their frames, and the other messages
+        // under the entity of their frame
+        let (conversion, qos, under_frame) = match is_static_tf {
+            Some(is_static) => (
+                ConversionContext::new("", "", None),
+                tf_qos(is_static),
+                false,
+            ),
+            None => (
+                ConversionContext::new(entity_path, frame_id, None),
+                rclrs::QOS_PROFILE_DEFAULT,
+                log_under_frames,
+            ),
+        };
+        match msg_spec {
+            Ok(msg_spec) => msg_specs.push((
+                topic_name.clone(),
+                ros_type.clone(),
+                conversion,
+                converter,
+                msg_spec,
+                qos,
+                under_frame,
+            )),
+            Err(report) => reports.push(report),
+        }
+    }
+
+    if subscribe_tf {
+        let tf_type = TfConverter::ROS_TYPES[0];
+        for (topic_name, is_static) in [(TF_TOPIC, false), (TF_STATIC_TOPIC, true)] {
+            let configured = config_parser
+                .conversions()
+                .keys()
+                .any(|(topic, _)| tf_topic_is_static(topic) == Some(is_static));
+            if configured {
+                continue;
+            }
+            let msg_spec = match MsgSpec::new_with_report(tf_type) {
+                Ok(msg_spec) => msg_spec,
+                Err(report) => {
+                    reports.push(report.to_string());
+                    break;
+                }
+            };
+            msg_specs.push((
+                topic_name.to_owned(),
+                tf_type.to_owned(),
+                ConversionContext::new("", "", None),
+                tf_converter(is_static),
+                msg_spec,
+                tf_qos(is_static),
+                false,
+            ));
+        }
+    }
+    if !reports.is_empty() {
+        return Err(anyhow!(reports.join("\n")));
+    }
+
+    let stream = recording_options.create_stream()?;
+
     // Prevent the subscriptions from being dropped
     let mut _subscriptions = Vec::new();
-    for ((topic_name, _frame_id), (ros_type, _entity_path)) in config_entries {
-        let msg_spec = rerun_ros::ros_introspection::MsgSpec::new(ros_type)?;
-
+    for (topic_name, ros_type, conversion, converter, msg_spec, qos, under_frame) in msg_specs {
         println!("Subscribing to topic: {topic_name} with type: {ros_type}");
-        let _generic_subscription = node.create_generic_subscription(
-            topic_name,
-            ros_type,
-            rclrs::QOS_PROFILE_DEFAULT,
-            move |_msg: rclrs::SerializedMessage| {
-                let _msg_spec = Arc::new(&msg_spec);
-                // Process message and pass it to rerun
+        let stream = stream.clone();
+        let tree = under_frame.then(|| Arc::clone(&tree));
+        let topic = topic_name.clone();
+        // Compiled once, so that each message is decoded without walking its specification
+        let plan = DecodePlan::new(&msg_spec)?;
+        // Only the fields the converter reads are decoded, when it declares them
+        let projection = converter
+            .field_paths()
+            .map(|paths| Projection::new(&msg_spec, &paths))
+            .transpose()
+            .map_err(|err| anyhow!("{topic_name}: {err:#}"))?;
+        let generic_subscription = node.create_generic_subscription(
+            &topic_name,
+            &ros_type,
+            qos,
+            move |msg: rclrs::SerializedMessage| {
+                let message = CdrMessage::new_with_projection(
+                    &msg_spec,
+                    &plan,
+                    projection.as_ref(),
+                    msg.as_slice(),
+                );
+                let context = ConversionContext::new(
+                    conversion.entity_path(),
+                    conversion.frame_id(),
+                    message.stamp(),
+                );
+                let context = match &tree {
+                    Some(tree) => tree.lock().frame_context(&context),
+                    None => context,
+                };
+                context.set_time(&stream);
+                if let Err(err) = converter.convert(&stream, &context, &message) {
+                    eprintln!("{topic}: {err:#}");
+                }
             },
         )?;
-        _subscriptions.push(_generic_subscription);
+        _subscriptions.push(generic_subscription);
     }
+
+    rclrs::spin(node)?;
     Ok(())
 }
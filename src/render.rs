@@ -0,0 +1,572 @@
+//! Text rendering of decoded messages, as YAML laid out like `ros2 topic echo`, or as JSON.
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error, Result};
+use base64::Engine as _;
+
+use crate::ros_introspection::MsgSpec;
+use crate::value::Value;
+
+/// The text format of a rendered message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextFormat {
+    /// Block-style YAML, as printed by `ros2 topic echo`.
+    Yaml,
+
+    /// Indented JSON, with byte arrays encoded as base64 strings.
+    Json,
+}
+
+impl FromStr for TextFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yaml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!("Unknown text format {s}, expected yaml or json")),
+        }
+    }
+}
+
+impl fmt::Display for TextFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Yaml => write!(f, "yaml"),
+            Self::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Options for rendering decoded messages as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderOptions {
+    format: TextFormat,
+    truncate_length: Option<usize>,
+}
+
+impl RenderOptions {
+    /// Creates a new `RenderOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The text format to render messages in.
+    /// * `truncate_length` - The number of elements or characters after which arrays and
+    ///   strings are cut short and followed by `...`, or `None` to render them in full.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `RenderOptions` instance.
+    pub fn new(format: TextFormat, truncate_length: Option<usize>) -> Self {
+        Self {
+            format,
+            truncate_length,
+        }
+    }
+
+    /// Returns the text format to render messages in.
+    ///
+    /// # Returns
+    ///
+    /// * `TextFormat` - The text format.
+    pub fn format(&self) -> TextFormat {
+        self.format
+    }
+
+    /// Returns the length after which arrays and strings are truncated.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The number of elements or characters to keep, or `None` to keep all of them.
+    pub fn truncate_length(&self) -> Option<usize> {
+        self.truncate_length
+    }
+}
+
+/// Renders a decoded message as text.
+///
+/// Fields are rendered in the order of the message definition. Fields missing from the value,
+/// such as those left out by a [`Projection`](crate::cdr::Projection), are skipped.
+///
+/// # Arguments
+///
+/// * `spec` - A reference to the `MsgSpec` of the message type.
+/// * `value` - A reference to the decoded message.
+/// * `options` - A reference to the rendering options.
+///
+/// # Returns
+///
+/// * `Result<String, Error>` - A result containing the rendered message, ending with a newline, or an error.
+///
+/// # Errors
+///
+/// This function will return an error if the value is not a message.
+pub fn render(spec: &MsgSpec, value: &Value, options: &RenderOptions) -> Result<String, Error> {
+    let Value::Message(fields) = value else {
+        return Err(anyhow!(
+            "Cannot render a {} as a {}",
+            value.kind(),
+            spec.data().type_().name()
+        ));
+    };
+
+    let mut renderer = Renderer {
+        truncate_length: options.truncate_length,
+        out: String::new(),
+    };
+    match options.format {
+        TextFormat::Yaml if fields.is_empty() => renderer.out.push_str("{}\n"),
+        TextFormat::Yaml => renderer.yaml_fields(Some(spec), fields, 0)?,
+        TextFormat::Json => {
+            renderer.json_value(value, Some(spec), 0)?;
+            renderer.out.push('\n');
+        }
+    }
+    Ok(renderer.out)
+}
+
+/// Pairs the fields of a message value with their nested specifications, in definition order.
+fn ordered<'v>(
+    spec: Option<&'v MsgSpec>,
+    fields: &'v [(String, Value)],
+) -> Vec<(&'v str, &'v Value, Option<&'v MsgSpec>)> {
+    let Some(spec) = spec else {
+        return fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value, None))
+            .collect();
+    };
+
+    spec.serialized_fields()
+        .filter_map(|(field, child)| {
+            fields
+                .iter()
+                .find(|(name, _)| name == field.name())
+                .map(|(name, value)| (name.as_str(), value, child))
+        })
+        .collect()
+}
+
+struct Renderer {
+    truncate_length: Option<usize>,
+    out: String,
+}
+
+impl Renderer {
+    /// Returns the number of elements of an array of `len` to render, and whether it is truncated.
+    fn kept(&self, len: usize) -> (usize, bool) {
+        match self.truncate_length {
+            Some(limit) if len > limit => (limit, true),
+            _ => (len, false),
+        }
+    }
+
+    fn truncated_str<'s>(&self, string: &'s str) -> std::borrow::Cow<'s, str> {
+        match self.truncate_length {
+            Some(limit) if string.chars().count() > limit => {
+                let mut truncated: String = string.chars().take(limit).collect();
+                truncated.push_str("...");
+                truncated.into()
+            }
+            _ => string.into(),
+        }
+    }
+
+    fn yaml_fields(
+        &mut self,
+        spec: Option<&MsgSpec>,
+        fields: &[(String, Value)],
+        indent: usize,
+    ) -> fmt::Result {
+        for (name, value, child) in ordered(spec, fields) {
+            let pad = " ".repeat(indent);
+            match value {
+                Value::Message(fields) if !fields.is_empty() => {
+                    writeln!(self.out, "{pad}{name}:")?;
+                    self.yaml_fields(child, fields, indent + 2)?;
+                }
+                Value::Array(elements) if !elements.is_empty() => {
+                    writeln!(self.out, "{pad}{name}:")?;
+                    self.yaml_elements(elements, child, indent)?;
+                }
+                Value::Bytes(bytes) if !bytes.is_empty() => {
+                    writeln!(self.out, "{pad}{name}:")?;
+                    let (kept, truncated) = self.kept(bytes.len());
+                    for byte in &bytes[..kept] {
+                        writeln!(self.out, "{pad}- {byte}")?;
+                    }
+                    if truncated {
+                        writeln!(self.out, "{pad}- '...'")?;
+                    }
+                }
+                _ => {
+                    let scalar = self.yaml_scalar(value);
+                    writeln!(self.out, "{pad}{name}: {scalar}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the elements of a non-empty array, at the indentation of its field.
+    fn yaml_elements(
+        &mut self,
+        elements: &[Value],
+        child: Option<&MsgSpec>,
+        indent: usize,
+    ) -> fmt::Result {
+        let pad = " ".repeat(indent);
+        let (kept, truncated) = self.kept(elements.len());
+
+        for element in &elements[..kept] {
+            match element {
+                Value::Message(fields) if !fields.is_empty() => {
+                    // The first field of the message shares the line of the dash
+                    let start = self.out.len();
+                    self.yaml_fields(child, fields, indent + 2)?;
+                    if self.out.len() > start {
+                        self.out
+                            .replace_range(start..start + indent + 2, &format!("{pad}- "));
+                    } else {
+                        writeln!(self.out, "{pad}- {{}}")?;
+                    }
+                }
+                _ => {
+                    let scalar = self.yaml_scalar(element);
+                    writeln!(self.out, "{pad}- {scalar}")?;
+                }
+            }
+        }
+        if truncated {
+            writeln!(self.out, "{pad}- '...'")?;
+        }
+        Ok(())
+    }
+
+    /// Renders a value that fits on a single line.
+    fn yaml_scalar(&self, value: &Value) -> String {
+        match value {
+            Value::Float32(value) => yaml_float(f64::from(*value), format!("{value:?}")),
+            Value::Float64(value) => yaml_float(*value, format!("{value:?}")),
+            Value::String(string) => yaml_string(&self.truncated_str(string)),
+            Value::Bytes(_) | Value::Array(_) => "[]".to_owned(),
+            Value::Message(_) => "{}".to_owned(),
+            _ => plain_scalar(value),
+        }
+    }
+
+    fn json_value(&mut self, value: &Value, spec: Option<&MsgSpec>, indent: usize) -> fmt::Result {
+        match value {
+            Value::Message(fields) => {
+                let fields = ordered(spec, fields);
+                if fields.is_empty() {
+                    self.out.push_str("{}");
+                    return Ok(());
+                }
+                self.out.push('{');
+                for (index, (name, value, child)) in fields.into_iter().enumerate() {
+                    if index > 0 {
+                        self.out.push(',');
+                    }
+                    write!(
+                        self.out,
+                        "\n{}{}: ",
+                        " ".repeat(indent + 2),
+                        json_string(name)
+                    )?;
+                    self.json_value(value, child, indent + 2)?;
+                }
+                write!(self.out, "\n{}}}", " ".repeat(indent))?;
+            }
+            Value::Array(elements) => {
+                if elements.is_empty() {
+                    self.out.push_str("[]");
+                    return Ok(());
+                }
+                let (kept, truncated) = self.kept(elements.len());
+                self.out.push('[');
+                for (index, element) in elements[..kept].iter().enumerate() {
+                    if index > 0 {
+                        self.out.push(',');
+                    }
+                    write!(self.out, "\n{}", " ".repeat(indent + 2))?;
+                    self.json_value(element, spec, indent + 2)?;
+                }
+                if truncated {
+                    write!(self.out, ",\n{}\"...\"", " ".repeat(indent + 2))?;
+                }
+                write!(self.out, "\n{}]", " ".repeat(indent))?;
+            }
+            Value::Bytes(bytes) => {
+                let (kept, truncated) = self.kept(bytes.len());
+                let mut encoded = base64::engine::general_purpose::STANDARD.encode(&bytes[..kept]);
+                if truncated {
+                    encoded.push_str("...");
+                }
+                self.out.push_str(&json_string(&encoded));
+            }
+            Value::String(string) => {
+                let string = json_string(&self.truncated_str(string));
+                self.out.push_str(&string);
+            }
+            Value::Float32(value) => {
+                self.out
+                    .push_str(&json_float(f64::from(*value), format!("{value:?}")));
+            }
+            Value::Float64(value) => self.out.push_str(&json_float(*value, format!("{value:?}"))),
+            _ => self.out.push_str(&plain_scalar(value)),
+        }
+        Ok(())
+    }
+}
+
+/// Renders a boolean or an integer, which read the same in YAML and JSON.
+fn plain_scalar(value: &Value) -> String {
+    match value {
+        Value::Bool(value) => value.to_string(),
+        Value::Int8(value) => value.to_string(),
+        Value::Uint8(value) => value.to_string(),
+        Value::Int16(value) => value.to_string(),
+        Value::Uint16(value) => value.to_string(),
+        Value::Int32(value) => value.to_string(),
+        Value::Uint32(value) => value.to_string(),
+        Value::Int64(value) => value.to_string(),
+        Value::Uint64(value) => value.to_string(),
+        _ => value.kind().to_owned(),
+    }
+}
+
+/// Renders a float from its shortest representation, which always has a `.` or an exponent.
+fn yaml_float(value: f64, shortest: String) -> String {
+    if value.is_nan() {
+        ".nan".to_owned()
+    } else if value.is_infinite() {
+        if value > 0.0 { ".inf" } else { "-.inf" }.to_owned()
+    } else {
+        shortest
+    }
+}
+
+/// Renders a float, with `null` for values that JSON cannot represent.
+fn json_float(value: f64, shortest: String) -> String {
+    if value.is_finite() {
+        shortest
+    } else {
+        "null".to_owned()
+    }
+}
+
+/// Renders a string as a plain YAML scalar when it cannot be mistaken for anything else, and
+/// quotes it otherwise.
+fn yaml_string(string: &str) -> String {
+    if string.chars().any(char::is_control) {
+        // JSON escapes are valid in double-quoted YAML scalars
+        return json_string(string);
+    }
+
+    let ambiguous = string.is_empty()
+        || string.starts_with(|c: char| c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`.".contains(c))
+        || string.ends_with(|c: char| c.is_whitespace() || c == ':')
+        || string.contains(": ")
+        || string.contains(" #")
+        || string.parse::<f64>().is_ok()
+        || matches!(
+            string.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "y" | "n" | "on" | "off" | "null" | "~"
+        );
+    if ambiguous {
+        format!("'{}'", string.replace('\'', "''"))
+    } else {
+        string.to_owned()
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut out = String::with_capacity(string.len() + 2);
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ros_introspection::Dialect;
+
+    fn spec() -> MsgSpec {
+        MsgSpec::new_from_definitions(
+            "test_msgs/Scan",
+            r#"
+            std_msgs/Header header
+            float32[] ranges
+            uint8[] data
+            test_msgs/Tag[] tags
+            string note
+            ================================================================================
+            MSG: std_msgs/Header
+            builtin_interfaces/Time stamp
+            string frame_id
+            ================================================================================
+            MSG: builtin_interfaces/Time
+            int32 sec
+            uint32 nanosec
+            ================================================================================
+            MSG: test_msgs/Tag
+            string name
+            float64 score
+            "#,
+            Dialect::Ros2,
+        )
+        .unwrap()
+    }
+
+    fn value() -> Value {
+        let message = |fields: Vec<(&str, Value)>| {
+            Value::Message(
+                fields
+                    .into_iter()
+                    .map(|(name, value)| (name.to_owned(), value))
+                    .collect(),
+            )
+        };
+
+        // Out of definition order, as when built by hand
+        message(vec![
+            ("note", Value::String("yes".to_owned())),
+            (
+                "header",
+                message(vec![
+                    (
+                        "stamp",
+                        message(vec![
+                            ("sec", Value::Int32(3)),
+                            ("nanosec", Value::Uint32(4)),
+                        ]),
+                    ),
+                    ("frame_id", Value::String("laser".to_owned())),
+                ]),
+            ),
+            (
+                "ranges",
+                Value::Array(vec![
+                    Value::Float32(0.5),
+                    Value::Float32(f32::NAN),
+                    Value::Float32(2.0),
+                ]),
+            ),
+            ("data", Value::Bytes(vec![1, 2, 3])),
+            (
+                "tags",
+                Value::Array(vec![message(vec![
+                    ("name", Value::String(String::new())),
+                    ("score", Value::Float64(1e-5)),
+                ])]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_yaml() {
+        let options = RenderOptions::new(TextFormat::Yaml, None);
+        assert_eq!(
+            render(&spec(), &value(), &options).unwrap(),
+            "\
+header:
+  stamp:
+    sec: 3
+    nanosec: 4
+  frame_id: laser
+ranges:
+- 0.5
+- .nan
+- 2.0
+data:
+- 1
+- 2
+- 3
+tags:
+- name: ''
+  score: 1e-5
+note: 'yes'
+"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let options = RenderOptions::new(TextFormat::Json, None);
+        let json = render(&spec(), &value(), &options).unwrap();
+        assert_eq!(
+            json,
+            r#"{
+  "header": {
+    "stamp": {
+      "sec": 3,
+      "nanosec": 4
+    },
+    "frame_id": "laser"
+  },
+  "ranges": [
+    0.5,
+    null,
+    2.0
+  ],
+  "data": "AQID",
+  "tags": [
+    {
+      "name": "",
+      "score": 1e-5
+    }
+  ],
+  "note": "yes"
+}
+"#
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_truncation() {
+        let spec = spec();
+        let mut value = value();
+        if let Value::Message(fields) = &mut value {
+            fields[0].1 = Value::String("abcdef".to_owned());
+        }
+
+        let options = RenderOptions::new(TextFormat::Yaml, Some(2));
+        let yaml = render(&spec, &value, &options).unwrap();
+        assert!(yaml.contains("ranges:\n- 0.5\n- .nan\n- '...'\n"));
+        assert!(yaml.contains("data:\n- 1\n- 2\n- '...'\n"));
+        assert!(yaml.ends_with("note: ab...\n"));
+
+        let options = RenderOptions::new(TextFormat::Json, Some(2));
+        let json = render(&spec, &value, &options).unwrap();
+        assert!(json.contains("\"data\": \"AQI=...\""));
+        assert!(json.contains("    null,\n    \"...\"\n  ]"));
+    }
+
+    #[test]
+    fn test_yaml_strings() {
+        assert_eq!(yaml_string("map"), "map");
+        assert_eq!(yaml_string("base link"), "base link");
+        assert_eq!(yaml_string("12"), "'12'");
+        assert_eq!(yaml_string("True"), "'True'");
+        assert_eq!(yaml_string("it's: here"), "'it''s: here'");
+        assert_eq!(yaml_string("- item"), "'- item'");
+        assert_eq!(yaml_string("two\nlines"), "\"two\\nlines\"");
+        assert_eq!("json".parse::<TextFormat>().unwrap(), TextFormat::Json);
+        assert!("xml".parse::<TextFormat>().is_err());
+    }
+}
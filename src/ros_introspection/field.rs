@@ -1,14 +1,21 @@
-use crate::ros_introspection::Type;
+use crate::ros_introspection::{BuiltinType, Dialect, Type};
 use anyhow::Result;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Field {
+    #[serde(rename = "name")]
     fieldname: String,
+    #[serde(rename = "type")]
     field_type: Type,
     is_array: bool,
     array_size: isize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    array_bound: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    string_bound: Option<usize>,
     is_constant: bool,
     value: String,
 }
@@ -30,6 +37,8 @@ impl Field {
             field_type,
             is_array: false,
             array_size: 1,
+            array_bound: None,
+            string_bound: None,
             is_constant: false,
             value: String::new(),
         }
@@ -52,10 +61,33 @@ impl Field {
     /// - The type, field, or array size cannot be extracted from the definition.
     /// - The array size is not a valid integer.
     pub fn new_with_definition(definition: &str) -> Result<Self> {
-        let type_regex =
-            Regex::new(r"[a-zA-Z][a-zA-Z0-9_]*(/[a-zA-Z][a-zA-Z0-9_]*){0,1}(\[[0-9]*\]){0,1}")?;
+        Self::new_with_definition_and_dialect(definition, Dialect::Ros2)
+    }
+
+    /// Creates a new `Field` instance from a definition string written in the given dialect.
+    ///
+    /// # Arguments
+    ///
+    /// * `definition` - A string slice that holds the definition of the field.
+    /// * `dialect` - The `Dialect` the definition is written in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A result containing the new `Field` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The regular expression for parsing the type, field, or array fails to compile.
+    /// - The type, field, or array size cannot be extracted from the definition.
+    /// - The array size or a bound is not a valid integer.
+    pub fn new_with_definition_and_dialect(definition: &str, dialect: Dialect) -> Result<Self> {
+        let type_regex = Regex::new(
+            r"[a-zA-Z][a-zA-Z0-9_]*(/[a-zA-Z][a-zA-Z0-9_]*){0,1}(<=[0-9]+){0,1}(\[(<=){0,1}[0-9]*\]){0,1}",
+        )?;
         let field_regex = Regex::new(r"[a-zA-Z][a-zA-Z0-9_]*")?;
-        let array_regex = Regex::new(r"(.+)(\[(\d*)\])")?;
+        let array_regex = Regex::new(r"(.+)(\[(<=)?(\d*)\])")?;
+        let string_bound_regex = Regex::new(r"^(w?string)<=(\d+)$")?;
 
         let mut begin = definition;
 
@@ -80,22 +112,29 @@ impl Field {
         // Find array size
         // Clone type_ to avoid borrowing issues
         let temp_type = type_.clone();
-        let (is_array, array_size) = if let Some(what) = array_regex.captures(&temp_type) {
-            type_ = what[1].to_string();
-            if what.len() == 3 {
-                (true, -1)
-            } else if let Some(size) = what.get(3) {
-                let array_size = if size.as_str().is_empty() {
-                    -1
+        let (is_array, array_size, array_bound) =
+            if let Some(what) = array_regex.captures(&temp_type) {
+                type_ = what[1].to_string();
+                let size = what.get(4).map_or("", |size| size.as_str());
+                if what.get(3).is_some() {
+                    // Bounded sequences are length-prefixed like unbounded ones
+                    (true, -1, Some(usize::from_str(size)?))
+                } else if size.is_empty() {
+                    (true, -1, None)
                 } else {
-                    isize::from_str(size.as_str())?
-                };
-                (true, array_size)
+                    (true, isize::from_str(size)?, None)
+                }
             } else {
-                (true, -1)
-            }
+                (false, 1, None)
+            };
+
+        // Find string bound
+        let temp_type = type_.clone();
+        let string_bound = if let Some(what) = string_bound_regex.captures(&temp_type) {
+            type_ = what[1].to_string();
+            Some(usize::from_str(&what[2])?)
         } else {
-            (false, 1)
+            None
         };
 
         // Find if constant or comment
@@ -130,9 +169,11 @@ impl Field {
 
         Ok(Self {
             fieldname,
-            field_type: Type::new(type_.as_str())?,
+            field_type: Type::new_with_dialect(type_.as_str(), "", dialect)?,
             is_array,
             array_size,
+            array_bound,
+            string_bound,
             is_constant,
             value,
         })
@@ -192,6 +233,24 @@ impl Field {
         self.array_size
     }
 
+    /// Returns the maximum number of elements of a bounded sequence field.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The bound of the sequence, or `None` if the field is not a bounded sequence.
+    pub fn array_bound(&self) -> Option<usize> {
+        self.array_bound
+    }
+
+    /// Returns the maximum length of a bounded string field.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The bound of the string, or `None` if the field is not a bounded string.
+    pub fn string_bound(&self) -> Option<usize> {
+        self.string_bound
+    }
+
     /// Returns the value of the field.
     ///
     /// # Returns
@@ -200,12 +259,55 @@ impl Field {
     pub fn value(&self) -> &str {
         &self.value
     }
+
+    /// Converts the field back into a line of a message definition, such as
+    /// `string<=8[<=3] names`, `float64 x 0` or `int32 ANSWER=42`.
+    ///
+    /// Nested types are written with their package, so that the line does not depend on the
+    /// package of the message it belongs to.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The definition of the field.
+    pub fn definition(&self) -> String {
+        let mut definition = if self.field_type.id() == &BuiltinType::Other
+            && !self.field_type.pkg_name().is_empty()
+        {
+            format!(
+                "{}/{}",
+                self.field_type.pkg_name(),
+                self.field_type.msg_name()
+            )
+        } else {
+            self.field_type.name().to_owned()
+        };
+
+        if let Some(bound) = self.string_bound {
+            definition.push_str(&format!("<={bound}"));
+        }
+        if self.is_array {
+            match (self.array_bound, self.array_size) {
+                (Some(bound), _) => definition.push_str(&format!("[<={bound}]")),
+                (None, size) if size >= 0 => definition.push_str(&format!("[{size}]")),
+                (None, _) => definition.push_str("[]"),
+            }
+        }
+
+        let value = self.value.trim();
+        if self.is_constant {
+            definition.push_str(&format!(" {}={value}", self.fieldname));
+        } else if value.is_empty() {
+            definition.push_str(&format!(" {}", self.fieldname));
+        } else {
+            definition.push_str(&format!(" {} {value}", self.fieldname));
+        }
+        definition
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ros_introspection::Type;
 
     #[test]
     fn test_new_with_type() {
@@ -247,6 +349,59 @@ mod tests {
         assert_eq!(field.value, "3.14159");
     }
 
+    #[test]
+    fn test_new_with_definition_bounded() {
+        let field = Field::new_with_definition("int32[<=5] bounded").unwrap();
+        assert_eq!(field.fieldname, "bounded");
+        assert_eq!(field.field_type, Type::new("int32").unwrap());
+        assert!(field.is_array);
+        assert_eq!(field.array_size, -1);
+        assert_eq!(field.array_bound, Some(5));
+        assert_eq!(field.string_bound, None);
+
+        let field = Field::new_with_definition("string<=10 name").unwrap();
+        assert_eq!(field.fieldname, "name");
+        assert_eq!(field.field_type, Type::new("string").unwrap());
+        assert!(!field.is_array);
+        assert_eq!(field.array_bound, None);
+        assert_eq!(field.string_bound, Some(10));
+
+        let field = Field::new_with_definition("wstring<=8[<=3] names").unwrap();
+        assert_eq!(field.fieldname, "names");
+        assert_eq!(field.field_type, Type::new("wstring").unwrap());
+        assert!(field.is_array);
+        assert_eq!(field.array_bound, Some(3));
+        assert_eq!(field.string_bound, Some(8));
+
+        let field = Field::new_with_definition("string<=4[2] pair").unwrap();
+        assert!(field.is_array);
+        assert_eq!(field.array_size, 2);
+        assert_eq!(field.array_bound, None);
+        assert_eq!(field.string_bound, Some(4));
+
+        let field = Field::new_with_definition("string<=6 GREETING = hello").unwrap();
+        assert!(field.is_constant);
+        assert_eq!(field.value, "hello");
+    }
+
+    #[test]
+    fn test_new_with_definition_and_dialect() {
+        let field = Field::new_with_definition_and_dialect("Header header", Dialect::Ros1).unwrap();
+        assert_eq!(field.fieldname, "header");
+        assert_eq!(field.field_type, Type::new("std_msgs/Header").unwrap());
+
+        let field = Field::new_with_definition_and_dialect("time[] stamps", Dialect::Ros1).unwrap();
+        assert_eq!(field.fieldname, "stamps");
+        assert_eq!(field.field_type.id(), &BuiltinType::Time);
+        assert!(field.is_array);
+        assert_eq!(field.array_size, -1);
+
+        let field = Field::new_with_definition_and_dialect("byte X = 3", Dialect::Ros1).unwrap();
+        assert_eq!(field.field_type.id(), &BuiltinType::Int8);
+        assert!(field.is_constant);
+        assert_eq!(field.value, "3");
+    }
+
     #[test]
     fn test_getters() {
         let field = Field::new_with_type(Type::new("int32").unwrap(), "test_field");
@@ -259,6 +414,42 @@ mod tests {
         assert_eq!(field.value(), "");
     }
 
+    #[test]
+    fn test_serde_roundtrip() {
+        let field = Field::new_with_definition("float64[3] position").unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"position","type":{"name":"float64","pkg_name":"","msg_name":"float64","id":"float64"},"is_array":true,"array_size":3,"is_constant":false,"value":""}"#
+        );
+
+        let deserialized: Field = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.name(), "position");
+        assert_eq!(deserialized.type_(), field.type_());
+        assert!(deserialized.is_array());
+        assert_eq!(deserialized.array_size(), 3);
+    }
+
+    #[test]
+    fn test_definition() {
+        for definition in [
+            "int32 test_field",
+            "string[10] test_array",
+            "wstring<=8[<=3] names",
+            "uint8[] data",
+            "float64 x 0",
+            "string greeting \"Hello world!\"",
+            "float64 PI=3.14159",
+            "geometry_msgs/Point position",
+        ] {
+            let field = Field::new_with_definition(definition).unwrap();
+            assert_eq!(field.definition(), definition);
+        }
+
+        let field = Field::new_with_definition("float64 PI = 3.14159 # comment").unwrap();
+        assert_eq!(field.definition(), "float64 PI=3.14159");
+    }
+
     #[test]
     fn test_change_type() {
         let mut field = Field::new_with_type(Type::new("int32").unwrap(), "test_field");
@@ -0,0 +1,405 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Error, Result};
+
+use crate::ros_introspection::{DefinitionLoader, MsgSpec};
+
+/// The kind of a ROS interface definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InterfaceKind {
+    Message,
+    Service,
+    Action,
+}
+
+impl InterfaceKind {
+    /// Returns the directory of a package share directory that holds interfaces of this kind,
+    /// which is also the file extension of their definitions.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the directory name.
+    pub fn directory(&self) -> &'static str {
+        match self {
+            Self::Message => "msg",
+            Self::Service => "srv",
+            Self::Action => "action",
+        }
+    }
+
+    /// Returns the suffixes of the messages defined by each `---` separated section of a
+    /// definition of this kind.
+    fn section_suffixes(self) -> &'static [&'static str] {
+        match self {
+            Self::Message => &[""],
+            Self::Service => &["_Request", "_Response"],
+            Self::Action => &["_Goal", "_Result", "_Feedback"],
+        }
+    }
+}
+
+impl fmt::Display for InterfaceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.directory())
+    }
+}
+
+/// A message, service or action interface installed by a package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceInfo {
+    package: String,
+    name: String,
+    kind: InterfaceKind,
+    path: PathBuf,
+    error: Option<String>,
+}
+
+impl InterfaceInfo {
+    /// Returns the name of the package the interface belongs to.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the package name.
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// Returns the name of the interface, without package or kind.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the interface name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of the interface.
+    ///
+    /// # Returns
+    ///
+    /// * `InterfaceKind` - The kind of the interface.
+    pub fn kind(&self) -> InterfaceKind {
+        self.kind
+    }
+
+    /// Returns the path of the definition file.
+    ///
+    /// # Returns
+    ///
+    /// * `&Path` - The path of the definition file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the fully qualified name of the interface, e.g. `geometry_msgs/msg/Point`.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The fully qualified name.
+    pub fn full_name(&self) -> String {
+        format!("{}/{}/{}", self.package, self.kind, self.name)
+    }
+
+    /// Returns why the interface could not be parsed or resolved, if it could not.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The error, or `None` if the interface is valid.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns whether the interface and every type it depends on could be parsed.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the interface is valid, `false` otherwise.
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// A package of the ament index and the interfaces it installs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInterfaces {
+    name: String,
+    interfaces: Vec<InterfaceInfo>,
+    error: Option<String>,
+}
+
+impl PackageInterfaces {
+    /// Returns the name of the package.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the package name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the interfaces installed by the package, messages first, then services, then
+    /// actions, each sorted by name.
+    ///
+    /// # Returns
+    ///
+    /// * `&[InterfaceInfo]` - The interfaces of the package.
+    pub fn interfaces(&self) -> &[InterfaceInfo] {
+        &self.interfaces
+    }
+
+    /// Returns why the interfaces of the package could not be listed, if they could not.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The error, or `None` if the share directory of the package was found.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/// Lists every package in the ament index with its message, service and action interfaces,
+/// and checks that each interface and the types it depends on can be parsed.
+///
+/// Every definition is parsed only once, however many interfaces depend on it.
+///
+/// # Returns
+///
+/// * `Result<Vec<PackageInterfaces>, Error>` - A result containing the packages sorted by name, or an error.
+///
+/// # Errors
+///
+/// This function will return an error if the ament index cannot be read.
+pub fn list_interfaces() -> Result<Vec<PackageInterfaces>, Error> {
+    let ament_index = ament_rs::Ament::new()?;
+    let mut loader = DefinitionLoader::new(&ament_index);
+
+    let mut packages: Vec<_> = ament_index
+        .get_packages_with_prefixes()
+        .into_keys()
+        .collect();
+    packages.sort();
+
+    let packages = packages
+        .into_iter()
+        .map(|package| {
+            let Some(share_directory) = ament_index.get_package_share_directory(&package) else {
+                return PackageInterfaces {
+                    error: Some(format!(
+                        "Could not find package share directory for package: {package}"
+                    )),
+                    name: package,
+                    interfaces: Vec::new(),
+                };
+            };
+            let interfaces =
+                list_package_interfaces(&package, &share_directory, &mut |type_name, def| {
+                    MsgSpec::new_from_definition_with_loader(type_name, def, &mut loader)
+                        .map(drop)
+                        .map_err(|report| report.to_string())
+                });
+            PackageInterfaces {
+                name: package,
+                interfaces,
+                error: None,
+            }
+        })
+        .collect();
+
+    Ok(packages)
+}
+
+/// Lists the interfaces installed in a package share directory.
+///
+/// # Arguments
+///
+/// * `package` - A string slice that holds the name of the package.
+/// * `share_directory` - The share directory of the package.
+/// * `resolve` - A function that checks a single message definition, given its type name.
+///
+/// # Returns
+///
+/// * `Vec<InterfaceInfo>` - The interfaces of the package.
+fn list_package_interfaces(
+    package: &str,
+    share_directory: &Path,
+    resolve: &mut dyn FnMut(&str, &str) -> Result<(), String>,
+) -> Vec<InterfaceInfo> {
+    let mut interfaces = Vec::new();
+
+    for kind in [
+        InterfaceKind::Message,
+        InterfaceKind::Service,
+        InterfaceKind::Action,
+    ] {
+        let Ok(entries) = fs::read_dir(share_directory.join(kind.directory())) else {
+            continue;
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == kind.directory())
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let error = check_interface(package, name, kind, &path, resolve).err();
+            interfaces.push(InterfaceInfo {
+                package: package.to_owned(),
+                name: name.to_owned(),
+                kind,
+                path: path.clone(),
+                error,
+            });
+        }
+    }
+
+    interfaces
+}
+
+/// Checks that every section of an interface definition can be parsed and resolved.
+///
+/// # Errors
+///
+/// This function will return an error if the file cannot be read, if it does not have the
+/// number of sections its kind requires, or if a section cannot be resolved.
+fn check_interface(
+    package: &str,
+    name: &str,
+    kind: InterfaceKind,
+    path: &Path,
+    resolve: &mut dyn FnMut(&str, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let sections = split_sections(&contents);
+
+    let suffixes = kind.section_suffixes();
+    if sections.len() != suffixes.len() {
+        return Err(format!(
+            "Expected {} section(s) separated by ---, found {}",
+            suffixes.len(),
+            sections.len()
+        ));
+    }
+
+    for (section, suffix) in sections.iter().zip(suffixes) {
+        resolve(&format!("{package}/{name}{suffix}"), section)?;
+    }
+    Ok(())
+}
+
+/// Splits a service or action definition into its `---` separated sections.
+fn split_sections(def: &str) -> Vec<String> {
+    let mut sections = vec![String::new()];
+
+    for line in def.lines() {
+        if line.trim() == "---" {
+            sections.push(String::new());
+        } else if let Some(section) = sections.last_mut() {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ros_introspection::{parse_message_definitions, Type};
+    use tempfile::tempdir;
+
+    fn parse(type_name: &str, def: &str) -> Result<(), String> {
+        let root_type = Type::new(type_name).map_err(|err| err.to_string())?;
+        parse_message_definitions(def, &root_type)
+            .map(drop)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_split_sections() {
+        let sections = split_sections("int32 a\n---\nint32 b\n  ---  \nint32 c\n");
+        assert_eq!(sections, ["int32 a\n", "int32 b\n", "int32 c\n"]);
+
+        let sections = split_sections("int32 a\n");
+        assert_eq!(sections, ["int32 a\n"]);
+    }
+
+    #[test]
+    fn test_list_package_interfaces() {
+        let dir = tempdir().unwrap();
+        for kind in ["msg", "srv", "action"] {
+            fs::create_dir(dir.path().join(kind)).unwrap();
+        }
+        fs::write(dir.path().join("msg/Point.msg"), "float64 x\nfloat64 y\n").unwrap();
+        fs::write(dir.path().join("msg/Bad.msg"), "float64 [\n").unwrap();
+        fs::write(dir.path().join("msg/Point.idl"), "module test_msgs {};").unwrap();
+        fs::write(
+            dir.path().join("srv/Add.srv"),
+            "int32 a\nint32 b\n---\nint32 sum\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("srv/Broken.srv"), "int32 a\n").unwrap();
+        fs::write(
+            dir.path().join("action/Move.action"),
+            "float64 goal\n---\nbool done\n---\nfloat64 progress\n",
+        )
+        .unwrap();
+
+        let mut resolved = Vec::new();
+        let interfaces = list_package_interfaces("test_msgs", dir.path(), &mut |type_name, def| {
+            resolved.push(type_name.to_owned());
+            parse(type_name, def)
+        });
+
+        let names: Vec<_> = interfaces.iter().map(InterfaceInfo::full_name).collect();
+        assert_eq!(
+            names,
+            [
+                "test_msgs/msg/Bad",
+                "test_msgs/msg/Point",
+                "test_msgs/srv/Add",
+                "test_msgs/srv/Broken",
+                "test_msgs/action/Move",
+            ]
+        );
+
+        assert!(!interfaces[0].is_valid());
+        assert!(interfaces[1].is_valid());
+        assert!(interfaces[2].is_valid());
+        assert_eq!(
+            interfaces[3].error(),
+            Some("Expected 2 section(s) separated by ---, found 1")
+        );
+        assert!(interfaces[4].is_valid());
+        assert_eq!(interfaces[4].kind(), InterfaceKind::Action);
+
+        assert_eq!(
+            resolved,
+            [
+                "test_msgs/Bad",
+                "test_msgs/Point",
+                "test_msgs/Add_Request",
+                "test_msgs/Add_Response",
+                "test_msgs/Move_Goal",
+                "test_msgs/Move_Result",
+                "test_msgs/Move_Feedback",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_package_interfaces_without_interfaces() {
+        let dir = tempdir().unwrap();
+        let interfaces = list_package_interfaces("empty_pkg", dir.path(), &mut |_, _| Ok(()));
+        assert!(interfaces.is_empty());
+    }
+}
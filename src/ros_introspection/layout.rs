@@ -0,0 +1,327 @@
+use crate::ros_introspection::{BuiltinType, Field, MsgSpec};
+
+/// Size in bytes of the widest `wstring` characters in CDR, those of Fast-CDR 1, which bounds
+/// the size of the narrower ones of Fast-CDR 2.
+pub const WCHAR_SIZE: usize = 4;
+
+/// Size in bytes of the length prefix of strings and sequences in CDR.
+pub const LENGTH_PREFIX_SIZE: usize = 4;
+
+/// Size in bytes of the placeholder CDR serializes in place of a structure without fields.
+pub const EMPTY_STRUCT_SIZE: usize = 1;
+
+/// The static CDR layout of a message type.
+///
+/// Sizes are those of the message payload, without the encapsulation header, when the message
+/// starts at an offset that is a multiple of its alignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// No string or sequence appears anywhere in the type, so every message has the same size
+    /// and the same padding.
+    Fixed { size: usize, align: usize },
+
+    /// The type contains strings or sequences, all of them bounded.
+    ///
+    /// `max_size` is an upper bound on the serialized size.
+    Bounded { max_size: usize },
+
+    /// The type contains unbounded strings or sequences.
+    Unbounded,
+}
+
+impl Layout {
+    /// Computes the layout of the given message specification.
+    ///
+    /// # Arguments
+    ///
+    /// * `spec` - A reference to the `MsgSpec` to analyze.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The layout of the message.
+    pub fn new(spec: &MsgSpec) -> Self {
+        let mut cursor = Cursor::default();
+
+        if cursor.message(spec).is_none() {
+            Self::Unbounded
+        } else if !cursor.variable {
+            Self::Fixed {
+                size: cursor.offset,
+                align: cursor.max_align,
+            }
+        } else {
+            Self::Bounded {
+                max_size: cursor.offset,
+            }
+        }
+    }
+
+    /// Returns whether every message of this type has the same serialized size.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the layout is fixed, `false` otherwise.
+    pub fn is_fixed_size(&self) -> bool {
+        matches!(self, Self::Fixed { .. })
+    }
+
+    /// Returns the maximum serialized size of a message of this type.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<usize>` - The maximum size in bytes, or `None` if the type is unbounded.
+    pub fn max_serialized_size(&self) -> Option<usize> {
+        match *self {
+            Self::Fixed { size, .. } => Some(size),
+            Self::Bounded { max_size } => Some(max_size),
+            Self::Unbounded => None,
+        }
+    }
+}
+
+/// Returns the size and alignment in bytes of a fixed-size builtin type.
+///
+/// # Arguments
+///
+/// * `id` - A reference to the `BuiltinType`.
+///
+/// # Returns
+///
+/// * `Option<(usize, usize)>` - The size and alignment, or `None` for strings and non-builtin types.
+pub fn primitive_layout(id: &BuiltinType) -> Option<(usize, usize)> {
+    match id {
+        BuiltinType::Bool
+        | BuiltinType::Byte
+        | BuiltinType::Char
+        | BuiltinType::Int8
+        | BuiltinType::Uint8 => Some((1, 1)),
+        BuiltinType::Int16 | BuiltinType::Uint16 => Some((2, 2)),
+        BuiltinType::Int32 | BuiltinType::Uint32 | BuiltinType::Float32 => Some((4, 4)),
+        BuiltinType::Int64 | BuiltinType::Uint64 | BuiltinType::Float64 => Some((8, 8)),
+        // Two 32-bit integers
+        BuiltinType::Time | BuiltinType::Duration => Some((8, 4)),
+        BuiltinType::String | BuiltinType::WString | BuiltinType::Other => None,
+    }
+}
+
+/// Tracks the end offset of a message while walking its specification.
+///
+/// Variable-length content is walked at its maximum length. Once `variable` is set, `offset`
+/// is an upper bound rather than the exact offset; aligning it stays an upper bound, as
+/// rounding up to a multiple of the alignment is monotonic.
+struct Cursor {
+    offset: usize,
+    variable: bool,
+    max_align: usize,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            variable: false,
+            max_align: 1,
+        }
+    }
+}
+
+impl Cursor {
+    fn align(&mut self, align: usize) {
+        self.max_align = self.max_align.max(align);
+        self.offset = self.offset.next_multiple_of(align);
+    }
+
+    /// Walks a message, returning `None` if it is unbounded.
+    fn message(&mut self, spec: &MsgSpec) -> Option<()> {
+        for (field, child) in spec.serialized_fields() {
+            self.field(field, child)?;
+        }
+        if spec.is_empty_struct() {
+            self.offset += EMPTY_STRUCT_SIZE;
+        }
+        Some(())
+    }
+
+    fn field(&mut self, field: &Field, child: Option<&MsgSpec>) -> Option<()> {
+        let count = if !field.is_array() {
+            1
+        } else if let Ok(size) = usize::try_from(field.array_size()) {
+            size
+        } else {
+            let bound = field.array_bound()?;
+            self.align(LENGTH_PREFIX_SIZE);
+            self.offset += LENGTH_PREFIX_SIZE;
+            self.variable = true;
+            bound
+        };
+
+        if let Some((size, align)) = primitive_layout(field.type_().id()) {
+            if count > 0 {
+                self.align(align);
+            }
+            self.offset += size * count;
+            return Some(());
+        }
+
+        for _ in 0..count {
+            match child {
+                Some(child) => self.message(child)?,
+                None => self.string(field)?,
+            }
+        }
+        Some(())
+    }
+
+    fn string(&mut self, field: &Field) -> Option<()> {
+        let bound = field.string_bound()?;
+        self.align(LENGTH_PREFIX_SIZE);
+        self.offset += LENGTH_PREFIX_SIZE;
+        self.offset += if field.type_().id() == &BuiltinType::WString {
+            bound * WCHAR_SIZE
+        } else {
+            // Strings are null-terminated
+            bound + 1
+        };
+        self.variable = true;
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ros_introspection::Dialect;
+
+    fn spec(definitions: &str) -> MsgSpec {
+        MsgSpec::new_from_definitions("test_msgs/Test", definitions, Dialect::Ros2).unwrap()
+    }
+
+    #[test]
+    fn test_fixed_size() {
+        let pose = spec(
+            r#"
+            geometry_msgs/Point position
+            geometry_msgs/Quaternion orientation
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+            ================================================================================
+            MSG: geometry_msgs/Quaternion
+            float64 x
+            float64 y
+            float64 z
+            float64 w
+            "#,
+        );
+        let layout = pose.layout();
+        assert_eq!(layout, Layout::Fixed { size: 56, align: 8 });
+        assert!(layout.is_fixed_size());
+        assert_eq!(layout.max_serialized_size(), Some(56));
+
+        let padded = spec(
+            r#"
+            uint8 a
+            float64 b
+            int32 CONSTANT = 3
+            "#,
+        );
+        assert_eq!(padded.layout(), Layout::Fixed { size: 16, align: 8 });
+    }
+
+    #[test]
+    fn test_fixed_size_array_of_messages() {
+        // The padding inside each element depends on where the element starts
+        let nested = spec(
+            r#"
+            test_msgs/Inner[2] inner
+            ================================================================================
+            MSG: test_msgs/Inner
+            uint8 a
+            uint32 b
+            uint8 c
+            "#,
+        );
+        assert_eq!(nested.layout(), Layout::Fixed { size: 17, align: 4 });
+    }
+
+    #[test]
+    fn test_fixed_size_empty() {
+        // Empty arrays are not aligned, and empty structures take a placeholder byte
+        let empty = spec(
+            r#"
+            uint8 a
+            float64[0] none
+            test_msgs/Empty[2] empties
+            ================================================================================
+            MSG: test_msgs/Empty
+            "#,
+        );
+        assert_eq!(empty.layout(), Layout::Fixed { size: 3, align: 1 });
+    }
+
+    #[test]
+    fn test_bounded() {
+        let bounded = spec(
+            r#"
+            int32[<=3] values
+            "#,
+        );
+        assert_eq!(bounded.layout(), Layout::Bounded { max_size: 16 });
+        assert!(!bounded.layout().is_fixed_size());
+
+        let bounded = spec(
+            r#"
+            string<=5 name
+            float64 value
+            "#,
+        );
+        assert_eq!(bounded.layout(), Layout::Bounded { max_size: 24 });
+
+        let bounded = spec(
+            r#"
+            wstring<=2[<=2] names
+            "#,
+        );
+        assert_eq!(
+            bounded.layout().max_serialized_size(),
+            Some(4 + 2 * (4 + 8))
+        );
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let header = spec(
+            r#"
+            int32 sec
+            uint32 nanosec
+            string frame_id
+            "#,
+        );
+        assert_eq!(header.layout(), Layout::Unbounded);
+        assert_eq!(header.layout().max_serialized_size(), None);
+
+        let sequence = spec(
+            r#"
+            float32[] data
+            "#,
+        );
+        assert_eq!(sequence.layout(), Layout::Unbounded);
+    }
+
+    #[test]
+    fn test_ros1_time() {
+        let stamped = MsgSpec::new_from_definitions(
+            "test_msgs/Stamped",
+            r#"
+            uint8 flag
+            time stamp
+            duration timeout
+            "#,
+            Dialect::Ros1,
+        )
+        .unwrap();
+        assert_eq!(stamped.layout(), Layout::Fixed { size: 20, align: 4 });
+    }
+}
@@ -2,12 +2,15 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Error};
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 
+use crate::ros_introspection::Dialect;
 use crate::ros_introspection::Field;
 use crate::ros_introspection::Type;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
+    #[serde(rename = "type")]
     msg_type: Type,
     fields: Vec<Field>,
 }
@@ -30,6 +33,27 @@ impl Message {
     /// - The message type cannot be extracted from the definition.
     /// - A field cannot be created from the definition.
     pub fn new(def: &str) -> Result<Self, Error> {
+        Self::new_with_dialect(def, Dialect::Ros2)
+    }
+
+    /// Creates a new `Message` instance from a definition string written in the given dialect.
+    ///
+    /// # Arguments
+    ///
+    /// * `def` - A string slice that holds the definition of the message.
+    /// * `dialect` - The `Dialect` the definition is written in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `Message` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The regular expression for parsing the message definition fails to compile.
+    /// - The message type cannot be extracted from the definition.
+    /// - A field cannot be created from the definition.
+    pub fn new_with_dialect(def: &str, dialect: Dialect) -> Result<Self, Error> {
         let mut msg_type = Type::new("")?;
         let mut fields = Vec::new();
 
@@ -45,9 +69,9 @@ impl Message {
 
             if line.starts_with("MSG:") {
                 let line = &line[("MSG:".len() + 1)..];
-                msg_type = Type::new(line)?;
+                msg_type = Type::new_with_dialect(line, "", dialect)?;
             } else {
-                let new_field = Field::new_with_definition(line)?;
+                let new_field = Field::new_with_definition_and_dialect(line, dialect)?;
                 fields.push(new_field);
             }
         }
@@ -90,6 +114,20 @@ impl Message {
     pub fn fields_mut(&mut self) -> &mut Vec<Field> {
         &mut self.fields
     }
+
+    /// Converts the message back into a definition, one field per line, without its type.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The definition of the message.
+    pub fn definition(&self) -> String {
+        let mut definition = String::new();
+        for field in &self.fields {
+            definition.push_str(&field.definition());
+            definition.push('\n');
+        }
+        definition
+    }
 }
 
 /// Splits a string containing multiple message definitions into individual message definitions.
@@ -140,6 +178,36 @@ fn split_multiple_message_definitions(multi_def: &str) -> Vec<String> {
 pub fn parse_message_definitions(
     multi_def: &str,
     root_type: &Type,
+) -> Result<Vec<Arc<Message>>, Error> {
+    parse_message_definitions_with_dialect(multi_def, root_type, Dialect::Ros2)
+}
+
+/// Parses multiple message definitions written in the given dialect and returns a vector of
+/// `Message` instances.
+///
+/// This is the entry point for definitions that do not come from the ament index, such as the
+/// concatenated definitions stored in ROS 1 bags.
+///
+/// # Arguments
+///
+/// * `multi_def` - A string slice that holds the multiple message definitions.
+/// * `root_type` - A reference to the root `Type`.
+/// * `dialect` - The `Dialect` the definitions are written in.
+///
+/// # Returns
+///
+/// * `Result<Vec<Arc<Message>>, Error>` - A result containing a vector of `Message` instances or an error.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The message type is invalid.
+/// - A mutable reference to a message cannot be obtained.
+/// - The message type is unspecified.
+pub fn parse_message_definitions_with_dialect(
+    multi_def: &str,
+    root_type: &Type,
+    dialect: Dialect,
 ) -> Result<Vec<Arc<Message>>, Error> {
     let parts = split_multiple_message_definitions(multi_def);
     let mut known_type = Vec::new();
@@ -148,7 +216,7 @@ pub fn parse_message_definitions(
     let no_type = Type::new("")?;
 
     for i in (0..parts.len()).rev() {
-        let mut msg = Arc::new(Message::new(&parts[i])?);
+        let mut msg = Arc::new(Message::new_with_dialect(&parts[i], dialect)?);
 
         if i == 0 {
             if msg.type_() == &no_type && root_type != &no_type {
@@ -203,7 +271,7 @@ pub fn parse_message_definitions(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ros_introspection::Type;
+    use crate::ros_introspection::{BuiltinType, Type};
 
     #[test]
     fn test_new() -> Result<(), Error> {
@@ -287,4 +355,37 @@ mod tests {
         assert_eq!(msgs[1].fields()[0].name(), "data");
         assert_eq!(msgs[1].fields()[0].type_().name(), "int32");
     }
+
+    #[test]
+    fn test_parse_message_definitions_ros1() {
+        let multi_def = r#"
+            Header header
+            time stamp
+            duration timeout
+            byte small
+            char letter
+            ================================================================================
+            MSG: std_msgs/Header
+            uint32 seq
+            time stamp
+            string frame_id
+        "#;
+        let root_type = Type::new("my_msgs/Stamped").unwrap();
+        let msgs =
+            parse_message_definitions_with_dialect(multi_def, &root_type, Dialect::Ros1).unwrap();
+
+        assert_eq!(msgs.len(), 2);
+        assert_eq!(msgs[0].type_().name(), "my_msgs/Stamped");
+
+        let fields = msgs[0].fields();
+        assert_eq!(fields[0].type_().pkg_name(), "std_msgs");
+        assert_eq!(fields[0].type_().msg_name(), "Header");
+        assert_eq!(fields[1].type_().id(), &BuiltinType::Time);
+        assert_eq!(fields[2].type_().id(), &BuiltinType::Duration);
+        assert_eq!(fields[3].type_().id(), &BuiltinType::Int8);
+        assert_eq!(fields[4].type_().id(), &BuiltinType::Uint8);
+
+        assert_eq!(msgs[1].type_().name(), "std_msgs/Header");
+        assert_eq!(msgs[1].fields()[1].type_().id(), &BuiltinType::Time);
+    }
 }
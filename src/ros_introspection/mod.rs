@@ -1,9 +1,17 @@
 pub mod field;
+pub mod interfaces;
+pub mod layout;
 pub mod message;
 pub mod msgspec;
+pub mod resolution;
 pub mod r#type;
+pub mod type_description;
 
 pub use field::*;
+pub use interfaces::*;
+pub use layout::*;
 pub use message::*;
 pub use msgspec::*;
 pub use r#type::*;
+pub use resolution::*;
+pub use type_description::*;
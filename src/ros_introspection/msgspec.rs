@@ -1,9 +1,18 @@
-use crate::ros_introspection::{self, BuiltinType, Message, Type};
+use crate::ros_introspection::{
+    self, BuiltinType, Dialect, Field, Layout, Message, ResolutionIssue, ResolutionIssueKind,
+    ResolutionReport, Type,
+};
 use anyhow::{anyhow, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::sync::Arc;
 
 /// Represents a ROS message specification.
+///
+/// A `MsgSpec` can be serialized with serde, which allows a type database resolved on one
+/// machine to be reloaded on another one that has no ROS installation.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct MsgSpec {
     data: Arc<Message>,
     children: Vec<Arc<MsgSpec>>,
@@ -27,6 +36,193 @@ impl MsgSpec {
         Self::new_with_parent_package(topic_type, "")
     }
 
+    /// Creates a new `MsgSpec` instance for the given topic type, resolving its whole dependency
+    /// graph before reporting errors.
+    ///
+    /// Unlike [`MsgSpec::new`], which stops at the first type it cannot find, this reports every
+    /// missing package, missing file and parse error at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the topic.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ResolutionReport>` - A result containing the new `MsgSpec` instance or every issue found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the ament index cannot be read, or if any type in
+    /// the dependency graph cannot be resolved.
+    pub fn new_with_report(topic_type: &str) -> Result<Self, ResolutionReport> {
+        let ament_index = ament_rs::Ament::new().map_err(|err| {
+            ResolutionReport::for_root(
+                topic_type,
+                ResolutionIssueKind::AmentIndexUnavailable {
+                    error: err.to_string(),
+                },
+            )
+        })?;
+
+        Self::new_with_loader(topic_type, |message_type| {
+            Self::load_message_definition(&ament_index, message_type)
+        })
+    }
+
+    /// Creates a new `MsgSpec` instance from the definition of a single message, resolving the
+    /// types it depends on in the ament index and reporting every issue at once.
+    ///
+    /// This is used for definitions that are not stored in their own `.msg` file, such as the
+    /// sections of a service or action definition.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the message.
+    /// * `definition` - A string slice that holds the definition of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ResolutionReport>` - A result containing the new `MsgSpec` instance or every issue found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the definition cannot be parsed, the ament index
+    /// cannot be read, or if any type it depends on cannot be resolved.
+    pub fn new_from_definition_with_report(
+        topic_type: &str,
+        definition: &str,
+    ) -> Result<Self, ResolutionReport> {
+        let ament_index = ament_rs::Ament::new().map_err(|err| {
+            ResolutionReport::for_root(
+                topic_type,
+                ResolutionIssueKind::AmentIndexUnavailable {
+                    error: err.to_string(),
+                },
+            )
+        })?;
+
+        Self::new_from_definition_with_loader(
+            topic_type,
+            definition,
+            &mut DefinitionLoader::new(&ament_index),
+        )
+    }
+
+    /// Creates a new `MsgSpec` instance from the definition of a single message, loading the
+    /// types it depends on through a `DefinitionLoader` that may be shared with other calls.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the message.
+    /// * `definition` - A string slice that holds the definition of the message.
+    /// * `loader` - The loader of the definitions of the types the message depends on.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ResolutionReport>` - A result containing the new `MsgSpec` instance or every issue found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `ResolutionReport` listing every type that could not be
+    /// resolved, or a parse error of the definition itself.
+    pub fn new_from_definition_with_loader(
+        topic_type: &str,
+        definition: &str,
+        loader: &mut DefinitionLoader<'_>,
+    ) -> Result<Self, ResolutionReport> {
+        let root_type = Self::resolve_type(topic_type, "").map_err(|err| {
+            ResolutionReport::for_root(
+                topic_type,
+                ResolutionIssueKind::ParseError {
+                    error: err.to_string(),
+                },
+            )
+        })?;
+
+        Self::new_with_loader(topic_type, |message_type| {
+            if message_type == &root_type {
+                let msg_parsed =
+                    ros_introspection::parse_message_definitions(definition, message_type)
+                        .map_err(|err| ResolutionIssueKind::ParseError {
+                            error: err.to_string(),
+                        })?;
+                Ok(Arc::clone(&msg_parsed[0]))
+            } else {
+                loader.load(message_type)
+            }
+        })
+    }
+
+    /// Creates a new `MsgSpec` instance from concatenated message definitions.
+    ///
+    /// The definitions use the format stored in bags and MCAP files: the root message first,
+    /// followed by every message it depends on, each introduced by a `MSG: <type>` line and
+    /// separated by lines of `=`. No lookup in the ament index is performed.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the topic.
+    /// * `definitions` - A string slice that holds the concatenated message definitions.
+    /// * `dialect` - The `Dialect` the definitions are written in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `MsgSpec` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the definitions cannot be parsed, or if a nested
+    /// type is not part of the definitions.
+    pub fn new_from_definitions(
+        topic_type: &str,
+        definitions: &str,
+        dialect: Dialect,
+    ) -> Result<Self, Error> {
+        let root_type = Type::new_with_dialect(topic_type, "", dialect)?;
+        let messages = ros_introspection::parse_message_definitions_with_dialect(
+            definitions,
+            &root_type,
+            dialect,
+        )?;
+        let root = messages
+            .first()
+            .ok_or(anyhow!("No message definition found for {topic_type}"))?;
+
+        Self::new_from_parsed(root, &messages)
+    }
+
+    /// Creates a new `MsgSpec` instance for an already parsed message, looking up the
+    /// definitions of its nested types in `messages`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a nested type is not part of `messages`.
+    fn new_from_parsed(msg_def: &Arc<Message>, messages: &[Arc<Message>]) -> Result<Self, Error> {
+        let mut children = Vec::new();
+
+        for field in msg_def.fields() {
+            if field.type_().id() == &BuiltinType::Other {
+                let child_def = messages
+                    .iter()
+                    .find(|msg| {
+                        msg.type_().pkg_name() == field.type_().pkg_name()
+                            && msg.type_().msg_name() == field.type_().msg_name()
+                    })
+                    .ok_or(anyhow!(
+                        "Could not find definition of {} used by {}",
+                        field.type_(),
+                        msg_def.type_(),
+                    ))?;
+                children.push(Arc::new(Self::new_from_parsed(child_def, messages)?));
+            }
+        }
+
+        Ok(Self {
+            data: Arc::clone(msg_def),
+            children,
+        })
+    }
+
     /// Creates a new `MsgSpec` instance for the given topic type and parent package.
     ///
     /// # Arguments
@@ -79,32 +275,195 @@ impl MsgSpec {
         topic_type: &str,
         parent_package: &str,
     ) -> Result<Arc<Message>, Error> {
-        let message_type = {
-            let message_type = Type::new(topic_type)?;
-            if message_type.pkg_name().is_empty() && message_type.id() == &BuiltinType::Other {
-                Type::new_with_parent_package(topic_type, parent_package)?
-            } else {
-                message_type
-            }
-        };
-
+        let message_type = Self::resolve_type(topic_type, parent_package)?;
         let ament_index = ament_rs::Ament::new()?;
+
+        Self::load_message_definition(&ament_index, &message_type).map_err(|kind| {
+            ResolutionIssue::new(&short_type_name(&message_type), Vec::new(), kind).into()
+        })
+    }
+
+    /// Resolves the package of a type name that may not be fully qualified.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the topic.
+    /// * `parent_package` - A string slice that holds the name of the parent package.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Type, Error>` - A result containing the resolved type or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the message type is invalid.
+    fn resolve_type(topic_type: &str, parent_package: &str) -> Result<Type, Error> {
+        let message_type = Type::new(topic_type)?;
+        if message_type.pkg_name().is_empty() && message_type.id() == &BuiltinType::Other {
+            Type::new_with_parent_package(topic_type, parent_package)
+        } else {
+            Ok(message_type)
+        }
+    }
+
+    /// Reads and parses the definition of a message type from the ament index.
+    ///
+    /// # Arguments
+    ///
+    /// * `ament_index` - A reference to the ament index to look the package up in.
+    /// * `message_type` - A reference to the `Type` of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<Message>, ResolutionIssueKind>` - A result containing the message definition or the reason it could not be loaded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the package share directory cannot be found, the message file cannot be read, or its contents cannot be parsed.
+    fn load_message_definition(
+        ament_index: &ament_rs::Ament,
+        message_type: &Type,
+    ) -> Result<Arc<Message>, ResolutionIssueKind> {
         let mut msg_file_path = ament_index
             .get_package_share_directory(message_type.pkg_name())
-            .ok_or(anyhow!(
-                "Could not find package share directory for package: {}",
-                message_type.pkg_name(),
-            ))?;
+            .ok_or_else(|| ResolutionIssueKind::MissingPackage {
+                package: message_type.pkg_name().to_owned(),
+            })?;
 
         msg_file_path.push("msg");
         msg_file_path.push(format!("{}.msg", message_type.msg_name()));
 
-        let contents = fs::read_to_string(msg_file_path)?;
+        let contents =
+            fs::read_to_string(&msg_file_path).map_err(|err| ResolutionIssueKind::MissingFile {
+                path: msg_file_path.clone(),
+                error: err.to_string(),
+            })?;
 
-        let msg_parsed = ros_introspection::parse_message_definitions(&contents, &message_type)?;
+        let msg_parsed = ros_introspection::parse_message_definitions(&contents, message_type)
+            .map_err(|err| ResolutionIssueKind::ParseError {
+                error: err.to_string(),
+            })?;
 
-        let msg_def = Arc::clone(&msg_parsed[0]);
-        Ok(msg_def)
+        Ok(Arc::clone(&msg_parsed[0]))
+    }
+
+    /// Walks the whole dependency graph of a type, collecting every issue instead of stopping
+    /// at the first one.
+    ///
+    /// Types that appear several times in the graph are loaded, and reported, only once.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_type` - A string slice that holds the type of the topic.
+    /// * `load` - A function that loads the definition of a single type.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ResolutionReport>` - A result containing the new `MsgSpec` instance or every issue found.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if any type in the graph could not be resolved.
+    fn new_with_loader(
+        topic_type: &str,
+        mut load: impl FnMut(&Type) -> Result<Arc<Message>, ResolutionIssueKind>,
+    ) -> Result<Self, ResolutionReport> {
+        type Resolved = HashMap<(String, String), Option<Arc<MsgSpec>>>;
+
+        fn resolve(
+            topic_type: &str,
+            parent_package: &str,
+            dependency_path: &mut Vec<String>,
+            resolved: &mut Resolved,
+            issues: &mut Vec<ResolutionIssue>,
+            load: &mut dyn FnMut(&Type) -> Result<Arc<Message>, ResolutionIssueKind>,
+        ) -> Option<Arc<MsgSpec>> {
+            // Types of the same package may be written without it, as in `Pose`
+            let message_type = match MsgSpec::resolve_type(topic_type, parent_package) {
+                Ok(message_type) => message_type,
+                Err(err) => {
+                    issues.push(ResolutionIssue::new(
+                        topic_type,
+                        dependency_path.clone(),
+                        ResolutionIssueKind::ParseError {
+                            error: err.to_string(),
+                        },
+                    ));
+                    return None;
+                }
+            };
+
+            let key = (
+                message_type.pkg_name().to_owned(),
+                message_type.msg_name().to_owned(),
+            );
+            if let Some(spec) = resolved.get(&key) {
+                return spec.clone();
+            }
+
+            let spec = match load(&message_type) {
+                Ok(msg_def) => {
+                    dependency_path.push(short_type_name(&message_type));
+                    let mut children = Some(Vec::new());
+                    for field in msg_def.fields() {
+                        if field.type_().id() == &BuiltinType::Other {
+                            let child = resolve(
+                                field.type_().name(),
+                                msg_def.type_().pkg_name(),
+                                dependency_path,
+                                resolved,
+                                issues,
+                                load,
+                            );
+                            // Keep walking to report the issues of the remaining fields
+                            match (child, children.as_mut()) {
+                                (Some(child), Some(children)) => children.push(child),
+                                _ => children = None,
+                            }
+                        }
+                    }
+                    dependency_path.pop();
+
+                    children.map(|children| {
+                        Arc::new(MsgSpec {
+                            data: msg_def,
+                            children,
+                        })
+                    })
+                }
+                Err(kind) => {
+                    issues.push(ResolutionIssue::new(
+                        &short_type_name(&message_type),
+                        dependency_path.clone(),
+                        kind,
+                    ));
+                    None
+                }
+            };
+
+            resolved.insert(key, spec.clone());
+            spec
+        }
+
+        let mut issues = Vec::new();
+        let spec = resolve(
+            topic_type,
+            "",
+            &mut Vec::new(),
+            &mut Resolved::new(),
+            &mut issues,
+            &mut load,
+        );
+
+        match spec {
+            Some(spec) if issues.is_empty() => {
+                Ok(Arc::try_unwrap(spec).unwrap_or_else(|spec| Self {
+                    data: Arc::clone(&spec.data),
+                    children: spec.children.clone(),
+                }))
+            }
+            _ => Err(ResolutionReport::new(topic_type, issues)),
+        }
     }
 
     /// Returns a reference to the message data.
@@ -124,4 +483,326 @@ impl MsgSpec {
     pub fn children(&self) -> &Vec<Arc<Self>> {
         &self.children
     }
+
+    /// Returns the fields that are part of the serialized message, in order, each paired with
+    /// the specification of its type if it is not a builtin type.
+    ///
+    /// Constants are skipped, as they are not serialized.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Iterator<Item = (&Field, Option<&Self>)>` - An iterator over the serialized fields.
+    pub fn serialized_fields(&self) -> impl Iterator<Item = (&Field, Option<&Self>)> {
+        let mut children = self.children.iter();
+        self.data.fields().iter().filter_map(move |field| {
+            let child = if field.type_().id() == &BuiltinType::Other {
+                children.next().map(AsRef::as_ref)
+            } else {
+                None
+            };
+            (!field.is_constant()).then_some((field, child))
+        })
+    }
+
+    /// Returns whether the message has no serialized field, in which case CDR serializes it as a
+    /// placeholder of [`EMPTY_STRUCT_SIZE`](crate::ros_introspection::EMPTY_STRUCT_SIZE) byte.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the message has no field other than constants.
+    pub fn is_empty_struct(&self) -> bool {
+        self.serialized_fields().next().is_none()
+    }
+
+    /// Computes the static CDR layout of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Layout` - The layout of the message.
+    pub fn layout(&self) -> Layout {
+        Layout::new(self)
+    }
+
+    /// Exports the full definition of the message, in the format accepted by
+    /// [`MsgSpec::new_from_definitions`] and stored in bags and MCAP files: the definition of
+    /// the message, followed by that of every type it depends on, once each, in depth-first
+    /// order.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The concatenated message definitions.
+    pub fn full_definition(&self) -> String {
+        let mut definitions = self.data.definition();
+        let mut exported = vec![short_type_name(self.data.type_())];
+        let mut pending: Vec<&Self> = self.children.iter().rev().map(AsRef::as_ref).collect();
+
+        while let Some(spec) = pending.pop() {
+            let type_name = short_type_name(spec.data.type_());
+            if exported.contains(&type_name) {
+                continue;
+            }
+            definitions.push_str(&"=".repeat(80));
+            definitions.push('\n');
+            definitions.push_str(&format!("MSG: {type_name}\n"));
+            definitions.push_str(&spec.data.definition());
+            exported.push(type_name);
+            pending.extend(spec.children.iter().rev().map(AsRef::as_ref));
+        }
+
+        definitions
+    }
+}
+
+/// Loads message definitions from the ament index, reading and parsing each type only once.
+///
+/// A single loader can be shared by the resolution of many types, such as every interface of
+/// the ament index, so that the messages they have in common are not parsed again each time.
+pub struct DefinitionLoader<'a> {
+    ament_index: &'a ament_rs::Ament,
+    loaded: HashMap<(String, String), Result<Arc<Message>, ResolutionIssueKind>>,
+}
+
+impl<'a> DefinitionLoader<'a> {
+    /// Creates a new `DefinitionLoader` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `ament_index` - A reference to the ament index to look the packages up in.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `DefinitionLoader` instance, with nothing loaded yet.
+    pub fn new(ament_index: &'a ament_rs::Ament) -> Self {
+        Self {
+            ament_index,
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// Returns the definition of a message type, loading it on the first request.
+    ///
+    /// # Arguments
+    ///
+    /// * `message_type` - A reference to the `Type` of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Arc<Message>, ResolutionIssueKind>` - A result containing the message definition or the reason it could not be loaded.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, the same one on every request, if the package share
+    /// directory cannot be found, the message file cannot be read, or its contents cannot be
+    /// parsed.
+    pub fn load(&mut self, message_type: &Type) -> Result<Arc<Message>, ResolutionIssueKind> {
+        let key = (
+            message_type.pkg_name().to_owned(),
+            message_type.msg_name().to_owned(),
+        );
+        self.loaded
+            .entry(key)
+            .or_insert_with(|| MsgSpec::load_message_definition(self.ament_index, message_type))
+            .clone()
+    }
+}
+
+/// Formats a message type as `pkg/Name`, the form used by `MSG:` lines.
+fn short_type_name(message_type: &Type) -> String {
+    format!("{}/{}", message_type.pkg_name(), message_type.msg_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_from_definitions_ros1() {
+        let definitions = r#"
+            Header header
+            geometry_msgs/Point point
+            ================================================================================
+            MSG: std_msgs/Header
+            uint32 seq
+            time stamp
+            string frame_id
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+        "#;
+        let spec =
+            MsgSpec::new_from_definitions("geometry_msgs/PointStamped", definitions, Dialect::Ros1)
+                .unwrap();
+
+        assert_eq!(spec.data().type_().name(), "geometry_msgs/PointStamped");
+        assert_eq!(spec.children().len(), 2);
+        assert_eq!(spec.children()[0].data().type_().name(), "std_msgs/Header");
+        assert!(spec.children()[0].children().is_empty());
+        assert_eq!(
+            spec.children()[1].data().type_().name(),
+            "geometry_msgs/Point"
+        );
+        assert_eq!(spec.children()[1].data().fields().len(), 3);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let definitions = r#"
+            geometry_msgs/Point point
+            ================================================================================
+            MSG: geometry_msgs/Point
+            float64 x
+            float64 y
+            float64 z
+        "#;
+        let spec =
+            MsgSpec::new_from_definitions("my_msgs/Wrapper", definitions, Dialect::Ros2).unwrap();
+
+        let json = serde_json::to_string(&spec).unwrap();
+        let deserialized: MsgSpec = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(deserialized.data().type_(), spec.data().type_());
+        assert_eq!(deserialized.data().fields().len(), 1);
+        assert_eq!(deserialized.children().len(), 1);
+        assert_eq!(
+            deserialized.children()[0].data().type_().name(),
+            "geometry_msgs/Point"
+        );
+        assert_eq!(deserialized.children()[0].data().fields()[2].name(), "z");
+    }
+
+    #[test]
+    fn test_full_definition() {
+        let definitions = r#"
+            std_msgs/Header header
+            Point start
+            Point[<=2] waypoints
+            ================================================================================
+            MSG: std_msgs/Header
+            builtin_interfaces/Time stamp
+            string frame_id
+            ================================================================================
+            MSG: builtin_interfaces/Time
+            int32 sec
+            uint32 nanosec
+            ================================================================================
+            MSG: geometry_msgs/Point
+            uint8 FRAME = 1 # A constant
+            float64 x
+            float64 y 0.5
+        "#;
+        let spec = MsgSpec::new_from_definitions("geometry_msgs/Path", definitions, Dialect::Ros2)
+            .unwrap();
+
+        let separator = "=".repeat(80);
+        let expected = format!(
+            "std_msgs/Header header\n\
+             geometry_msgs/Point start\n\
+             geometry_msgs/Point[<=2] waypoints\n\
+             {separator}\n\
+             MSG: std_msgs/Header\n\
+             builtin_interfaces/Time stamp\n\
+             string frame_id\n\
+             {separator}\n\
+             MSG: builtin_interfaces/Time\n\
+             int32 sec\n\
+             uint32 nanosec\n\
+             {separator}\n\
+             MSG: geometry_msgs/Point\n\
+             uint8 FRAME=1\n\
+             float64 x\n\
+             float64 y 0.5\n"
+        );
+        assert_eq!(spec.full_definition(), expected);
+
+        let reparsed =
+            MsgSpec::new_from_definitions("geometry_msgs/Path", &expected, Dialect::Ros2).unwrap();
+        assert_eq!(reparsed.full_definition(), expected);
+    }
+
+    #[test]
+    fn test_new_with_loader_reports_all_issues() {
+        let definitions = HashMap::from([
+            (
+                "nav_msgs/Odometry",
+                "std_msgs/Header header\ngeometry_msgs/PoseWithCovariance pose\nmissing_msgs/Twist twist\n",
+            ),
+            ("std_msgs/Header", "builtin_interfaces/Time stamp\nstring frame_id\n"),
+            ("builtin_interfaces/Time", "int32 sec\nuint32 nanosec\n"),
+            ("geometry_msgs/PoseWithCovariance", "Pose pose\nfloat64[36] covariance\n"),
+            ("geometry_msgs/Pose", "int32 bad-field\n"),
+        ]);
+
+        let report = MsgSpec::new_with_loader("nav_msgs/msg/Odometry", |message_type| {
+            let key = format!("{}/{}", message_type.pkg_name(), message_type.msg_name());
+            match definitions.get(key.as_str()) {
+                Some(def) if def.contains('-') => Err(ResolutionIssueKind::ParseError {
+                    error: "Bad field".to_owned(),
+                }),
+                Some(def) => {
+                    let mut msg_def = Message::new(def).unwrap();
+                    msg_def.set_type(message_type.clone());
+                    Ok(Arc::new(msg_def))
+                }
+                None => Err(ResolutionIssueKind::MissingPackage {
+                    package: message_type.pkg_name().to_owned(),
+                }),
+            }
+        })
+        .err()
+        .unwrap();
+
+        assert_eq!(report.root_type(), "nav_msgs/msg/Odometry");
+        assert_eq!(report.issues().len(), 2);
+
+        let issue = &report.issues()[0];
+        assert_eq!(issue.type_name(), "geometry_msgs/Pose");
+        assert_eq!(
+            issue.dependency_path(),
+            ["nav_msgs/Odometry", "geometry_msgs/PoseWithCovariance"]
+        );
+        assert!(matches!(
+            issue.kind(),
+            ResolutionIssueKind::ParseError { .. }
+        ));
+
+        let issue = &report.issues()[1];
+        assert_eq!(issue.type_name(), "missing_msgs/Twist");
+        assert_eq!(issue.dependency_path(), ["nav_msgs/Odometry"]);
+        assert_eq!(
+            issue.kind(),
+            &ResolutionIssueKind::MissingPackage {
+                package: "missing_msgs".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_new_with_loader_loads_shared_types_once() {
+        let mut loaded = Vec::new();
+        let spec = MsgSpec::new_with_loader("geometry_msgs/Polygon", |message_type| {
+            loaded.push(message_type.msg_name().to_owned());
+            let def = match message_type.msg_name() {
+                "Polygon" => "Point32 a\nPoint32 b\n",
+                _ => "float32 x\nfloat32 y\nfloat32 z\n",
+            };
+            Ok(Arc::new(Message::new(def).unwrap()))
+        })
+        .unwrap();
+
+        assert_eq!(loaded, ["Polygon", "Point32"]);
+        assert_eq!(spec.children().len(), 2);
+        assert_eq!(spec.children()[1].data().fields().len(), 3);
+    }
+
+    #[test]
+    fn test_new_from_definitions_missing_type() {
+        let definitions = r#"
+            geometry_msgs/Point point
+        "#;
+        let result =
+            MsgSpec::new_from_definitions("geometry_msgs/PointStamped", definitions, Dialect::Ros2);
+        assert!(result.is_err());
+    }
 }
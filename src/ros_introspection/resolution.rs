@@ -0,0 +1,227 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// The reason a message definition could not be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionIssueKind {
+    /// The ament index could not be read, typically because no workspace is sourced.
+    AmentIndexUnavailable { error: String },
+
+    /// The package of the type is not in the ament index.
+    MissingPackage { package: String },
+
+    /// The package is installed, but the definition file of the type could not be read.
+    MissingFile { path: PathBuf, error: String },
+
+    /// The definition file was read, but could not be parsed.
+    ParseError { error: String },
+}
+
+/// A message definition that could not be resolved, along with the chain of types that
+/// required it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionIssue {
+    type_name: String,
+    dependency_path: Vec<String>,
+    kind: ResolutionIssueKind,
+}
+
+impl ResolutionIssue {
+    /// Creates a new `ResolutionIssue` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - A string slice that holds the name of the type that could not be resolved.
+    /// * `dependency_path` - The names of the types that led to this one, starting from the root type.
+    /// * `kind` - The reason the type could not be resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ResolutionIssue` instance.
+    pub fn new(type_name: &str, dependency_path: Vec<String>, kind: ResolutionIssueKind) -> Self {
+        Self {
+            type_name: type_name.to_owned(),
+            dependency_path,
+            kind,
+        }
+    }
+
+    /// Returns the name of the type that could not be resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the name of the type.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// Returns the names of the types that led to this one, starting from the root type.
+    ///
+    /// # Returns
+    ///
+    /// * `&[String]` - The chain of dependent types, empty for the root type itself.
+    pub fn dependency_path(&self) -> &[String] {
+        &self.dependency_path
+    }
+
+    /// Returns the reason the type could not be resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `&ResolutionIssueKind` - A reference to the reason.
+    pub fn kind(&self) -> &ResolutionIssueKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ResolutionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ResolutionIssueKind::AmentIndexUnavailable { error } => {
+                write!(f, "Could not read the ament index: {error}")?;
+            }
+            ResolutionIssueKind::MissingPackage { package } => write!(
+                f,
+                "Could not find package share directory for package: {package} (type {})",
+                self.type_name
+            )?,
+            ResolutionIssueKind::MissingFile { path, error } => write!(
+                f,
+                "Could not read definition of {} from {}: {error}",
+                self.type_name,
+                path.display()
+            )?,
+            ResolutionIssueKind::ParseError { error } => {
+                write!(
+                    f,
+                    "Could not parse definition of {}: {error}",
+                    self.type_name
+                )?;
+            }
+        }
+
+        if !self.dependency_path.is_empty() {
+            write!(f, ", required by {}", self.dependency_path.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResolutionIssue {}
+
+/// Every issue found while resolving a message type and all of its dependencies.
+///
+/// Issues are ordered as the dependency graph is walked depth-first from the root type, so a
+/// type is always reported before the types nested in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolutionReport {
+    root_type: String,
+    issues: Vec<ResolutionIssue>,
+}
+
+impl ResolutionReport {
+    /// Creates a new `ResolutionReport` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `root_type` - A string slice that holds the name of the type being resolved.
+    /// * `issues` - The issues found while resolving it.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ResolutionReport` instance.
+    pub fn new(root_type: &str, issues: Vec<ResolutionIssue>) -> Self {
+        Self {
+            root_type: root_type.to_owned(),
+            issues,
+        }
+    }
+
+    /// Creates a new `ResolutionReport` instance with a single issue about the root type itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `root_type` - A string slice that holds the name of the type being resolved.
+    /// * `kind` - The reason the type could not be resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `ResolutionReport` instance.
+    pub fn for_root(root_type: &str, kind: ResolutionIssueKind) -> Self {
+        Self::new(
+            root_type,
+            vec![ResolutionIssue::new(root_type, Vec::new(), kind)],
+        )
+    }
+
+    /// Returns the name of the type being resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the name of the root type.
+    pub fn root_type(&self) -> &str {
+        &self.root_type
+    }
+
+    /// Returns the issues found while resolving the type.
+    ///
+    /// # Returns
+    ///
+    /// * `&[ResolutionIssue]` - The issues, in dependency order.
+    pub fn issues(&self) -> &[ResolutionIssue] {
+        &self.issues
+    }
+}
+
+impl fmt::Display for ResolutionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not resolve {} ({} issue(s)):",
+            self.root_type,
+            self.issues.len()
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ResolutionReport {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let report = ResolutionReport::new(
+            "nav_msgs/msg/Odometry",
+            vec![
+                ResolutionIssue::new(
+                    "geometry_msgs/PoseWithCovariance",
+                    vec!["nav_msgs/msg/Odometry".to_owned()],
+                    ResolutionIssueKind::MissingPackage {
+                        package: "geometry_msgs".to_owned(),
+                    },
+                ),
+                ResolutionIssue::new(
+                    "std_msgs/Header",
+                    vec!["nav_msgs/msg/Odometry".to_owned()],
+                    ResolutionIssueKind::ParseError {
+                        error: "Bad type".to_owned(),
+                    },
+                ),
+            ],
+        );
+
+        assert_eq!(
+            report.to_string(),
+            "Could not resolve nav_msgs/msg/Odometry (2 issue(s)):\n  \
+             - Could not find package share directory for package: geometry_msgs \
+             (type geometry_msgs/PoseWithCovariance), required by nav_msgs/msg/Odometry\n  \
+             - Could not parse definition of std_msgs/Header: Bad type, required by nav_msgs/msg/Odometry"
+        );
+    }
+}
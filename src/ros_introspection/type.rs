@@ -2,8 +2,10 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 
 use anyhow::{anyhow, Error, Result};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "TypeRepr", into = "TypeRepr")]
 pub struct Type {
     base_name: String,
     pkg_name: String,
@@ -30,11 +32,47 @@ impl Type {
     /// - The regular expression for parsing the message datatype fails to compile.
     /// - The message name cannot be extracted from the given name.
     pub fn new_with_parent_package(name: &str, parent_pkg_name: &str) -> Result<Self, Error> {
+        Self::new_with_dialect(name, parent_pkg_name, Dialect::Ros2)
+    }
+
+    /// Creates a new `Type` instance with the given name and parent package name,
+    /// interpreting builtin type names according to the given dialect.
+    ///
+    /// In the ROS 1 dialect, `time` and `duration` are builtin types, `byte` and `char`
+    /// are the deprecated aliases of `int8` and `uint8`, and a bare `Header` refers to
+    /// `std_msgs/Header`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the type.
+    /// * `parent_pkg_name` - A string slice that holds the name of the parent package.
+    /// * `dialect` - The `Dialect` the type name is written in.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `Type` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The regular expression for parsing the message datatype fails to compile.
+    /// - The message name cannot be extracted from the given name.
+    pub fn new_with_dialect(
+        name: &str,
+        parent_pkg_name: &str,
+        dialect: Dialect,
+    ) -> Result<Self, Error> {
         let msg_datatype_regex =
             regex::Regex::new(r"([a-zA-Z][a-zA-Z0-9_]+)/(msg/)?([a-zA-Z][a-zA-Z0-9_]+)")?;
 
+        // ROS 1 definitions refer to `std_msgs/Header` implicitly
+        let name = match (dialect, name) {
+            (Dialect::Ros1, "Header") => "std_msgs/Header",
+            _ => name,
+        };
+
         let (pkg_name, msg_name, id) = {
-            let id = to_builtin_type(name);
+            let id = to_builtin_type(name, dialect);
 
             if let Some(what) = msg_datatype_regex.captures(name) {
                 let pkg_name = what
@@ -141,6 +179,42 @@ impl std::fmt::Display for Type {
     }
 }
 
+/// On-disk representation of a `Type`.
+///
+/// The hash is not part of it, as `DefaultHasher` output is not stable across Rust releases;
+/// it is recomputed from the name on deserialization.
+#[derive(Serialize, Deserialize)]
+struct TypeRepr {
+    name: String,
+    pkg_name: String,
+    msg_name: String,
+    id: BuiltinType,
+}
+
+impl From<TypeRepr> for Type {
+    fn from(repr: TypeRepr) -> Self {
+        let hash = calculate_hash(&repr.name);
+        Self {
+            base_name: repr.name,
+            pkg_name: repr.pkg_name,
+            msg_name: repr.msg_name,
+            id: repr.id,
+            hash,
+        }
+    }
+}
+
+impl From<Type> for TypeRepr {
+    fn from(ros_type: Type) -> Self {
+        Self {
+            name: ros_type.base_name,
+            pkg_name: ros_type.pkg_name,
+            msg_name: ros_type.msg_name,
+            id: ros_type.id,
+        }
+    }
+}
+
 /// Calculates the hash value for a given string.
 ///
 /// # Arguments
@@ -156,7 +230,19 @@ fn calculate_hash(s: &str) -> u64 {
     hasher.finish()
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The flavour of the message definition language a definition is written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// ROS 1 definitions, as found in ROS 1 packages and bags.
+    Ros1,
+
+    /// ROS 2 definitions, as found in the ament index.
+    #[default]
+    Ros2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BuiltinType {
     Bool,
     Byte,
@@ -173,6 +259,10 @@ pub enum BuiltinType {
     Uint64,
     String,
     WString,
+    /// ROS 1 `time`: unsigned seconds and nanoseconds.
+    Time,
+    /// ROS 1 `duration`: signed seconds and nanoseconds.
+    Duration,
     Other,
 }
 
@@ -181,11 +271,20 @@ pub enum BuiltinType {
 /// # Arguments
 ///
 /// * `s` - A string slice that holds the input string.
+/// * `dialect` - The `Dialect` the input string is written in.
 ///
 /// # Returns
 ///
 /// * `BuiltinType` - The corresponding `BuiltinType` for the input string.
-fn to_builtin_type(s: &str) -> BuiltinType {
+fn to_builtin_type(s: &str, dialect: Dialect) -> BuiltinType {
+    match (dialect, s) {
+        (Dialect::Ros1, "byte") => return BuiltinType::Int8,
+        (Dialect::Ros1, "char") => return BuiltinType::Uint8,
+        (Dialect::Ros1, "time") => return BuiltinType::Time,
+        (Dialect::Ros1, "duration") => return BuiltinType::Duration,
+        _ => {}
+    }
+
     match s {
         "bool" => BuiltinType::Bool,
         "byte" => BuiltinType::Byte,
@@ -243,6 +342,39 @@ mod tests {
         assert_eq!(ros_type.name(), "int32");
     }
 
+    #[test]
+    fn test_new_with_dialect() {
+        let ros_type = Type::new_with_dialect("time", "", Dialect::Ros1).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Time);
+        assert_eq!(ros_type.pkg_name(), "");
+
+        let ros_type = Type::new_with_dialect("duration", "", Dialect::Ros1).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Duration);
+
+        let ros_type = Type::new_with_dialect("byte", "", Dialect::Ros1).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Int8);
+
+        let ros_type = Type::new_with_dialect("char", "", Dialect::Ros1).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Uint8);
+
+        let ros_type = Type::new_with_dialect("Header", "geometry_msgs", Dialect::Ros1).unwrap();
+        assert_eq!(ros_type.pkg_name(), "std_msgs");
+        assert_eq!(ros_type.msg_name(), "Header");
+        assert_eq!(ros_type.id(), &BuiltinType::Other);
+        assert_eq!(ros_type.name(), "std_msgs/Header");
+
+        // The ROS 2 dialect keeps the ROS 2 meaning of these names
+        let ros_type = Type::new_with_dialect("byte", "", Dialect::Ros2).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Byte);
+
+        let ros_type = Type::new_with_dialect("time", "", Dialect::Ros2).unwrap();
+        assert_eq!(ros_type.id(), &BuiltinType::Other);
+
+        let ros_type = Type::new_with_dialect("Header", "geometry_msgs", Dialect::Ros2).unwrap();
+        assert_eq!(ros_type.pkg_name(), "geometry_msgs");
+        assert_eq!(ros_type.name(), "Header");
+    }
+
     #[test]
     fn test_getters() {
         let ros_type = Type::new("std_msgs/msg/String").unwrap();
@@ -271,6 +403,25 @@ mod tests {
         assert_eq!(ros_type.hash, expected_hash);
     }
 
+    #[test]
+    fn test_serde_roundtrip() {
+        let ros_type = Type::new_with_parent_package("Point", "geometry_msgs").unwrap();
+        let json = serde_json::to_string(&ros_type).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"Point","pkg_name":"geometry_msgs","msg_name":"Point","id":"other"}"#
+        );
+
+        let deserialized: Type = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, ros_type);
+        assert_eq!(deserialized.pkg_name(), "geometry_msgs");
+        assert_eq!(deserialized.hash, ros_type.hash);
+
+        let ros_type = Type::new("wstring").unwrap();
+        let json = serde_json::to_string(&ros_type).unwrap();
+        assert!(json.contains(r#""id":"wstring""#));
+    }
+
     #[test]
     fn test_display() {
         let ros_type = Type::new("std_msgs/msg/String").unwrap();
@@ -0,0 +1,285 @@
+use anyhow::{anyhow, Error, Result};
+
+use crate::ros_introspection::{Dialect, MsgSpec};
+
+/// Offset added to a base field type ID for fixed-size arrays.
+const ARRAY_OFFSET: u8 = 48;
+
+/// Mirrors `type_description_interfaces/msg/FieldType`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldType {
+    /// One of the `FIELD_TYPE_*` constants.
+    pub type_id: u8,
+
+    /// Size of fixed-size arrays, or bound of bounded sequences.
+    pub capacity: u64,
+
+    /// Bound of bounded strings.
+    pub string_capacity: u64,
+
+    /// Fully qualified name of the nested type, e.g. `std_msgs/msg/Header`.
+    pub nested_type_name: String,
+}
+
+/// Mirrors `type_description_interfaces/msg/Field`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldDescription {
+    pub name: String,
+    pub field_type: FieldType,
+    pub default_value: String,
+}
+
+/// Mirrors `type_description_interfaces/msg/IndividualTypeDescription`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndividualTypeDescription {
+    /// Fully qualified name of the type, e.g. `geometry_msgs/msg/PointStamped`.
+    pub type_name: String,
+    pub fields: Vec<FieldDescription>,
+}
+
+/// Mirrors `type_description_interfaces/msg/TypeDescription`, as returned by the
+/// `~/get_type_description` service of ROS 2 nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeDescription {
+    pub type_description: IndividualTypeDescription,
+    pub referenced_type_descriptions: Vec<IndividualTypeDescription>,
+}
+
+impl TypeDescription {
+    /// Converts the type description into concatenated message definitions, in the format
+    /// accepted by [`MsgSpec::new_from_definitions`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - A result containing the definitions or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a field has a type that cannot be expressed in a
+    /// message definition, such as `long double` or `wchar`.
+    pub fn to_definitions(&self) -> Result<String, Error> {
+        let mut definitions = individual_definition(&self.type_description)?;
+
+        for referenced in &self.referenced_type_descriptions {
+            definitions.push_str(&"=".repeat(80));
+            definitions.push('\n');
+            definitions.push_str(&format!(
+                "MSG: {}\n",
+                short_type_name(&referenced.type_name)
+            ));
+            definitions.push_str(&individual_definition(referenced)?);
+        }
+
+        Ok(definitions)
+    }
+}
+
+impl MsgSpec {
+    /// Creates a new `MsgSpec` instance from a type description, as served by the
+    /// `~/get_type_description` service of ROS 2 nodes since Iron.
+    ///
+    /// # Arguments
+    ///
+    /// * `description` - A reference to the `TypeDescription` of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `MsgSpec` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a field has an unsupported type, or if a nested
+    /// type is missing from the referenced type descriptions.
+    pub fn new_from_type_description(description: &TypeDescription) -> Result<Self, Error> {
+        Self::new_from_definitions(
+            &description.type_description.type_name,
+            &description.to_definitions()?,
+            Dialect::Ros2,
+        )
+    }
+}
+
+/// Converts a fully qualified type name such as `std_msgs/msg/Header` into the `std_msgs/Header`
+/// form used by message definitions.
+fn short_type_name(type_name: &str) -> String {
+    let mut parts = type_name.split('/');
+    match (parts.next(), parts.last()) {
+        (Some(package), Some(name)) => format!("{package}/{name}"),
+        _ => type_name.to_owned(),
+    }
+}
+
+/// Converts the fields of a single type into a message definition.
+fn individual_definition(description: &IndividualTypeDescription) -> Result<String, Error> {
+    let mut definition = String::new();
+    for field in &description.fields {
+        definition.push_str(
+            &field_definition(field)
+                .map_err(|err| anyhow!("Could not convert {}: {err}", description.type_name))?,
+        );
+        definition.push('\n');
+    }
+    Ok(definition)
+}
+
+/// Converts a single field into a line of a message definition.
+fn field_definition(field: &FieldDescription) -> Result<String, Error> {
+    let field_type = &field.field_type;
+
+    let base_type = match field_type.type_id % ARRAY_OFFSET {
+        1 => short_type_name(&field_type.nested_type_name),
+        2 => "int8".to_owned(),
+        3 => "uint8".to_owned(),
+        4 => "int16".to_owned(),
+        5 => "uint16".to_owned(),
+        6 => "int32".to_owned(),
+        7 => "uint32".to_owned(),
+        8 => "int64".to_owned(),
+        9 => "uint64".to_owned(),
+        10 => "float32".to_owned(),
+        11 => "float64".to_owned(),
+        13 => "char".to_owned(),
+        15 => "bool".to_owned(),
+        16 => "byte".to_owned(),
+        17 => "string".to_owned(),
+        18 => "wstring".to_owned(),
+        // Fixed-size strings are serialized like bounded ones
+        19 | 21 => format!("string<={}", field_type.string_capacity),
+        20 | 22 => format!("wstring<={}", field_type.string_capacity),
+        _ => {
+            return Err(anyhow!(
+                "Unsupported type {} for field {}",
+                field_type.type_id,
+                field.name
+            ))
+        }
+    };
+
+    let array = match field_type.type_id / ARRAY_OFFSET {
+        0 => String::new(),
+        1 => format!("[{}]", field_type.capacity),
+        2 => format!("[<={}]", field_type.capacity),
+        3 => "[]".to_owned(),
+        _ => {
+            return Err(anyhow!(
+                "Unsupported type {} for field {}",
+                field_type.type_id,
+                field.name
+            ))
+        }
+    };
+
+    Ok(format!("{base_type}{array} {}", field.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ros_introspection::BuiltinType;
+
+    fn field(name: &str, type_id: u8) -> FieldDescription {
+        FieldDescription {
+            name: name.to_owned(),
+            field_type: FieldType {
+                type_id,
+                ..FieldType::default()
+            },
+            default_value: String::new(),
+        }
+    }
+
+    fn nested(name: &str, type_id: u8, nested_type_name: &str) -> FieldDescription {
+        let mut field = field(name, type_id);
+        field.field_type.nested_type_name = nested_type_name.to_owned();
+        field
+    }
+
+    fn point_stamped() -> TypeDescription {
+        TypeDescription {
+            type_description: IndividualTypeDescription {
+                type_name: "geometry_msgs/msg/PointStamped".to_owned(),
+                fields: vec![
+                    nested("header", 1, "std_msgs/msg/Header"),
+                    nested("point", 1, "geometry_msgs/msg/Point"),
+                ],
+            },
+            referenced_type_descriptions: vec![
+                IndividualTypeDescription {
+                    type_name: "builtin_interfaces/msg/Time".to_owned(),
+                    fields: vec![field("sec", 6), field("nanosec", 7)],
+                },
+                IndividualTypeDescription {
+                    type_name: "geometry_msgs/msg/Point".to_owned(),
+                    fields: vec![field("x", 11), field("y", 11), field("z", 11)],
+                },
+                IndividualTypeDescription {
+                    type_name: "std_msgs/msg/Header".to_owned(),
+                    fields: vec![
+                        nested("stamp", 1, "builtin_interfaces/msg/Time"),
+                        field("frame_id", 17),
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_new_from_type_description() {
+        let spec = MsgSpec::new_from_type_description(&point_stamped()).unwrap();
+
+        assert_eq!(spec.data().type_().pkg_name(), "geometry_msgs");
+        assert_eq!(spec.data().type_().msg_name(), "PointStamped");
+        assert_eq!(spec.children().len(), 2);
+
+        let header = &spec.children()[0];
+        assert_eq!(header.data().type_().name(), "std_msgs/Header");
+        assert_eq!(header.children()[0].data().fields()[1].name(), "nanosec");
+        assert_eq!(header.data().fields()[1].type_().id(), &BuiltinType::String);
+
+        let point = &spec.children()[1];
+        assert_eq!(point.data().fields().len(), 3);
+        assert_eq!(point.data().fields()[0].type_().id(), &BuiltinType::Float64);
+    }
+
+    #[test]
+    fn test_field_definition_containers() {
+        let mut array = field("covariance", 11 + 48);
+        array.field_type.capacity = 36;
+        assert_eq!(field_definition(&array).unwrap(), "float64[36] covariance");
+
+        let mut bounded = field("values", 6 + 96);
+        bounded.field_type.capacity = 5;
+        assert_eq!(field_definition(&bounded).unwrap(), "int32[<=5] values");
+
+        let unbounded = field("data", 3 + 144);
+        assert_eq!(field_definition(&unbounded).unwrap(), "uint8[] data");
+
+        let mut bounded_string = field("name", 21 + 144);
+        bounded_string.field_type.string_capacity = 10;
+        assert_eq!(
+            field_definition(&bounded_string).unwrap(),
+            "string<=10[] name"
+        );
+
+        let poses = nested("poses", 1 + 144, "geometry_msgs/msg/Pose");
+        assert_eq!(
+            field_definition(&poses).unwrap(),
+            "geometry_msgs/Pose[] poses"
+        );
+    }
+
+    #[test]
+    fn test_field_definition_unsupported() {
+        assert!(field_definition(&field("value", 12)).is_err());
+        assert!(field_definition(&field("letter", 14)).is_err());
+        assert!(field_definition(&field("unset", 0)).is_err());
+        assert!(field_definition(&field("invalid", 200)).is_err());
+    }
+
+    #[test]
+    fn test_missing_referenced_type() {
+        let mut description = point_stamped();
+        description.referenced_type_descriptions.remove(1);
+        assert!(MsgSpec::new_from_type_description(&description).is_err());
+    }
+}
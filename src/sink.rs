@@ -0,0 +1,175 @@
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Error, Result};
+
+/// The application ID under which recordings are grouped in the viewer by default.
+pub const DEFAULT_APPLICATION_ID: &str = "rerun_ros";
+
+/// The address the web viewer and its WebSocket server listen on by default when serving.
+pub const DEFAULT_SERVE_BIND_IP: IpAddr = IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED);
+
+/// Where the logged data goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    /// Spawns a viewer, or reuses the one already listening on the default address.
+    Spawn,
+
+    /// Streams to a viewer listening at the given address.
+    Connect(SocketAddr),
+
+    /// Serves a web viewer, and streams to it over WebSocket, keeping at most `memory_limit`
+    /// of data, such as `25%` or `2GB`, for the viewers that connect later.
+    ///
+    /// The servers listen on `bind_ip`, and the web viewer is only opened in a browser if
+    /// `open_browser` is set, as robots usually have no display.
+    Serve {
+        bind_ip: IpAddr,
+        memory_limit: String,
+        open_browser: bool,
+    },
+
+    /// Saves to an `.rrd` file.
+    Save(PathBuf),
+}
+
+/// The configuration of the Rerun recording the bridge logs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordingOptions {
+    application_id: String,
+    recording_id: Option<String>,
+    sink: Sink,
+}
+
+impl RecordingOptions {
+    /// Creates a new `RecordingOptions` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `application_id` - A string slice that holds the application ID of the recording.
+    /// * `recording_id` - An optional string slice that holds the recording ID. Recordings
+    ///   sharing it, such as those of several bridges, are merged in the viewer. A random
+    ///   ID is used if `None`.
+    /// * `sink` - Where the logged data goes.
+    ///
+    /// # Returns
+    ///
+    /// * `Self` - The new `RecordingOptions` instance.
+    pub fn new(application_id: &str, recording_id: Option<&str>, sink: Sink) -> Self {
+        Self {
+            application_id: application_id.to_owned(),
+            recording_id: recording_id.map(ToOwned::to_owned),
+            sink,
+        }
+    }
+
+    /// Returns the application ID of the recording.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the application ID.
+    pub fn application_id(&self) -> &str {
+        &self.application_id
+    }
+
+    /// Returns the recording ID, if one was chosen.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The recording ID, or `None` for a random one.
+    pub fn recording_id(&self) -> Option<&str> {
+        self.recording_id.as_deref()
+    }
+
+    /// Returns where the logged data goes.
+    ///
+    /// # Returns
+    ///
+    /// * `&Sink` - A reference to the `Sink`.
+    pub fn sink(&self) -> &Sink {
+        &self.sink
+    }
+
+    /// Creates the recording stream, connected to its sink.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<rerun::RecordingStream, Error>` - A result containing the recording stream or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the viewer cannot be spawned, the web viewer
+    /// cannot be served, the memory limit cannot be parsed, or the `.rrd` file cannot be
+    /// created.
+    pub fn create_stream(&self) -> Result<rerun::RecordingStream, Error> {
+        let mut builder = rerun::RecordingStreamBuilder::new(self.application_id.as_str());
+        if let Some(recording_id) = &self.recording_id {
+            builder = builder.recording_id(recording_id.as_str());
+        }
+
+        let stream = match &self.sink {
+            Sink::Spawn => builder.spawn()?,
+            Sink::Connect(addr) => builder.connect_opts(*addr, rerun::default_flush_timeout())?,
+            Sink::Serve {
+                bind_ip,
+                memory_limit,
+                open_browser,
+            } => {
+                let memory_limit = rerun::MemoryLimit::parse(memory_limit)
+                    .map_err(|err| anyhow!("Invalid memory limit {memory_limit:?}: {err}"))?;
+                builder.serve(
+                    &bind_ip.to_string(),
+                    Default::default(),
+                    Default::default(),
+                    memory_limit,
+                    *open_browser,
+                )?
+            }
+            Sink::Save(path) => builder.save(path)?,
+        };
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("recording.rrd");
+
+        let options = RecordingOptions::new(
+            DEFAULT_APPLICATION_ID,
+            Some("run-1"),
+            Sink::Save(path.clone()),
+        );
+        assert_eq!(options.application_id(), "rerun_ros");
+        assert_eq!(options.recording_id(), Some("run-1"));
+
+        let stream = options.create_stream().unwrap();
+        stream
+            .log("topic/foo", &rerun::TextDocument::new("data: hello"))
+            .unwrap();
+        drop(stream);
+
+        assert!(path.metadata().unwrap().len() > 0);
+    }
+
+    #[test]
+    fn test_invalid_memory_limit() {
+        let options = RecordingOptions::new(
+            DEFAULT_APPLICATION_ID,
+            None,
+            Sink::Serve {
+                bind_ip: DEFAULT_SERVE_BIND_IP,
+                memory_limit: "lots".to_owned(),
+                open_browser: false,
+            },
+        );
+        let err = options.create_stream().err().unwrap();
+        assert!(err.to_string().starts_with("Invalid memory limit \"lots\""));
+    }
+}
@@ -0,0 +1,181 @@
+//! Fetches message definitions from the `~/get_type_description` service that ROS 2 nodes
+//! provide since Iron, for types whose definitions are not installed locally.
+//!
+//! The fetched definitions are only used to decode the messages. Subscribing to a topic still
+//! loads the typesupport library of its type, so the package of the type must be installed
+//! locally, if only without its `.msg` files.
+mod type_hash;
+
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Error, Result};
+use type_description_interfaces::msg as interfaces;
+use type_description_interfaces::srv::{
+    GetTypeDescription, GetTypeDescription_Request, GetTypeDescription_Response,
+};
+
+use crate::ros_introspection::{
+    FieldDescription, FieldType, IndividualTypeDescription, MsgSpec, Type, TypeDescription,
+};
+use crate::type_description_client::type_hash::GraphNode;
+
+/// Builds the `MsgSpec` of a topic's type from the type description served by its publishers.
+///
+/// Every publisher of the topic is asked in turn, until one of them answers. The request names
+/// the type along with the hash the publisher reports in the graph, which the service checks.
+///
+/// # Arguments
+///
+/// * `node` - The node used to query the ROS graph and call the services.
+/// * `topic_name` - A string slice that holds the name of the topic.
+/// * `ros_type` - A string slice that holds the type of the topic.
+/// * `timeout` - How long to wait for each publisher to be discovered, and then to answer.
+///
+/// # Returns
+///
+/// * `Result<MsgSpec, Error>` - A result containing the `MsgSpec` or an error.
+///
+/// # Errors
+///
+/// This function will return an error if the topic has no publisher, if the graph cannot be
+/// queried, if no publisher answers within the timeout, or if the type description cannot be
+/// converted.
+pub fn fetch_msg_spec(
+    node: &Arc<rclrs::Node>,
+    topic_name: &str,
+    ros_type: &str,
+    timeout: Duration,
+) -> Result<MsgSpec, Error> {
+    let message_type = Type::new(ros_type)?;
+    let type_name = format!(
+        "{}/msg/{}",
+        message_type.pkg_name(),
+        message_type.msg_name()
+    );
+
+    let publishers = node.get_publishers_info_by_topic(topic_name)?;
+    if publishers.is_empty() {
+        return Err(anyhow!("No publisher found for topic {topic_name}"));
+    }
+    let graph = GraphNode::new()?;
+
+    let mut errors = Vec::new();
+    for publisher in &publishers {
+        let service_name = format!(
+            "{}/{}/get_type_description",
+            publisher.node_namespace.trim_end_matches('/'),
+            publisher.node_name
+        );
+
+        let description = graph
+            .publisher_type_hash(
+                topic_name,
+                &publisher.node_name,
+                &publisher.node_namespace,
+                Instant::now() + timeout,
+            )
+            .and_then(|type_hash| {
+                request_type_description(node, &service_name, &type_name, &type_hash, timeout)
+            });
+        match description {
+            Ok(description) => return MsgSpec::new_from_type_description(&description),
+            Err(err) => errors.push(format!("{service_name}: {err}")),
+        }
+    }
+
+    Err(anyhow!(
+        "No publisher of {topic_name} served the type description of {type_name}: {}",
+        errors.join(", ")
+    ))
+}
+
+/// Calls a `~/get_type_description` service and waits for its response.
+///
+/// # Errors
+///
+/// This function will return an error if the request cannot be sent, if no response arrives
+/// within the timeout, or if the node reports a failure.
+fn request_type_description(
+    node: &Arc<rclrs::Node>,
+    service_name: &str,
+    type_name: &str,
+    type_hash: &str,
+    timeout: Duration,
+) -> Result<TypeDescription, Error> {
+    let client = node.create_client::<GetTypeDescription>(service_name)?;
+    let request = GetTypeDescription_Request {
+        type_name: type_name.to_owned(),
+        type_hash: type_hash.to_owned(),
+        include_type_sources: false,
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    client.async_send_request_with_callback(
+        &request,
+        move |response: GetTypeDescription_Response| {
+            // The receiver is gone if we already timed out
+            sender.send(response).ok();
+        },
+    )?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Ok(response) = receiver.try_recv() {
+            if !response.successful {
+                return Err(anyhow!(response.failure_reason));
+            }
+            return Ok(response.type_description.into());
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(anyhow!("Timed out after {timeout:?}"));
+        }
+        // A timeout while spinning is not an error here, the deadline is checked above
+        rclrs::spin_once(Arc::clone(node), Some(remaining)).ok();
+    }
+}
+
+impl From<interfaces::FieldType> for FieldType {
+    fn from(field_type: interfaces::FieldType) -> Self {
+        Self {
+            type_id: field_type.type_id,
+            capacity: field_type.capacity,
+            string_capacity: field_type.string_capacity,
+            nested_type_name: field_type.nested_type_name,
+        }
+    }
+}
+
+impl From<interfaces::Field> for FieldDescription {
+    fn from(field: interfaces::Field) -> Self {
+        Self {
+            name: field.name,
+            field_type: field.type_.into(),
+            default_value: field.default_value,
+        }
+    }
+}
+
+impl From<interfaces::IndividualTypeDescription> for IndividualTypeDescription {
+    fn from(description: interfaces::IndividualTypeDescription) -> Self {
+        Self {
+            type_name: description.type_name,
+            fields: description.fields.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<interfaces::TypeDescription> for TypeDescription {
+    fn from(description: interfaces::TypeDescription) -> Self {
+        Self {
+            type_description: description.type_description.into(),
+            referenced_type_descriptions: description
+                .referenced_type_descriptions
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
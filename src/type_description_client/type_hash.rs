@@ -0,0 +1,389 @@
+//! Reads the type hashes of the publishers of a topic through `rcl`, whose graph API reports
+//! them while the one of `rclrs` does not.
+//!
+//! `rclrs` keeps the handle of its node to itself, so the graph is queried through a node of
+//! our own. The structures below follow the layouts of ROS 2 Jazzy, which the type-description
+//! feature is built against.
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::fmt::Write as _;
+use std::ptr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Error, Result};
+
+/// The name of the node querying the graph, hidden from `ros2 node list` by its underscore.
+const NODE_NAME: &str = "_rerun_ros_type_hashes";
+
+/// How long to wait between two queries of the graph, while the publisher is being discovered.
+const DISCOVERY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The version of the `RIHS01_` hashes, the only one defined so far.
+const TYPE_HASH_VERSION: u8 = 1;
+
+type RclRet = i32;
+
+const RCL_RET_OK: RclRet = 0;
+
+/// `rcutils_allocator_t`, which is only handed back to `rcl`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Allocator {
+    allocate: *mut c_void,
+    deallocate: *mut c_void,
+    reallocate: *mut c_void,
+    zero_allocate: *mut c_void,
+    state: *mut c_void,
+}
+
+/// `rcl_init_options_t`.
+#[repr(C)]
+struct InitOptions {
+    impl_: *mut c_void,
+}
+
+/// `rcl_context_t`.
+#[repr(C)]
+struct Context {
+    global_arguments: *mut c_void,
+    impl_: *mut c_void,
+    instance_id_storage: u64,
+}
+
+/// `rcl_node_t`.
+#[repr(C)]
+struct Node {
+    context: *mut c_void,
+    impl_: *mut c_void,
+}
+
+/// `rcl_node_options_t`, which is only handed back to `rcl`.
+///
+/// It is larger than the 152 bytes of the C structure, which `rcl` writes its options into.
+#[repr(C, align(8))]
+struct NodeOptions([u8; 256]);
+
+/// `rosidl_type_hash_t`.
+#[repr(C)]
+struct TypeHash {
+    version: u8,
+    value: [u8; 32],
+}
+
+/// `rmw_topic_endpoint_info_t`.
+#[repr(C)]
+struct TopicEndpointInfo {
+    node_name: *const c_char,
+    node_namespace: *const c_char,
+    topic_type: *const c_char,
+    topic_type_hash: TypeHash,
+    endpoint_type: c_int,
+    endpoint_gid: [u8; 16],
+    // `rmw_qos_profile_t`
+    qos_profile: [u64; 11],
+}
+
+/// `rmw_topic_endpoint_info_array_t`.
+#[repr(C)]
+struct TopicEndpointInfoArray {
+    size: usize,
+    info_array: *mut TopicEndpointInfo,
+}
+
+/// `rcutils_error_string_t`.
+#[repr(C)]
+struct ErrorString {
+    str_: [c_char; 1024],
+}
+
+#[link(name = "rcutils")]
+extern "C" {
+    fn rcutils_get_default_allocator() -> Allocator;
+    fn rcutils_get_error_string() -> ErrorString;
+    fn rcutils_reset_error();
+}
+
+#[link(name = "rmw")]
+extern "C" {
+    fn rmw_topic_endpoint_info_array_fini(
+        info_array: *mut TopicEndpointInfoArray,
+        allocator: *mut Allocator,
+    ) -> RclRet;
+}
+
+#[link(name = "rcl")]
+extern "C" {
+    fn rcl_get_zero_initialized_init_options() -> InitOptions;
+    fn rcl_init_options_init(init_options: *mut InitOptions, allocator: Allocator) -> RclRet;
+    fn rcl_init_options_fini(init_options: *mut InitOptions) -> RclRet;
+    fn rcl_get_zero_initialized_context() -> Context;
+    fn rcl_init(
+        argc: c_int,
+        argv: *const *const c_char,
+        options: *const InitOptions,
+        context: *mut Context,
+    ) -> RclRet;
+    fn rcl_shutdown(context: *mut Context) -> RclRet;
+    fn rcl_context_fini(context: *mut Context) -> RclRet;
+    fn rcl_get_zero_initialized_node() -> Node;
+    fn rcl_node_get_default_options() -> NodeOptions;
+    fn rcl_node_options_fini(options: *mut NodeOptions) -> RclRet;
+    fn rcl_node_init(
+        node: *mut Node,
+        name: *const c_char,
+        namespace: *const c_char,
+        context: *mut Context,
+        options: *const NodeOptions,
+    ) -> RclRet;
+    fn rcl_node_fini(node: *mut Node) -> RclRet;
+    fn rcl_get_publishers_info_by_topic(
+        node: *const Node,
+        allocator: *mut Allocator,
+        topic_name: *const c_char,
+        no_mangle: bool,
+        publishers_info: *mut TopicEndpointInfoArray,
+    ) -> RclRet;
+}
+
+/// A node of its own, in a context of its own, through which the graph is queried.
+///
+/// The context and the node are boxed, since `rcl` keeps pointers to them.
+pub(super) struct GraphNode {
+    init_options: Box<InitOptions>,
+    context: Box<Context>,
+    node: Box<Node>,
+}
+
+impl GraphNode {
+    /// Creates a new `GraphNode` instance, in the domain given by the environment.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, Error>` - A result containing the new `GraphNode` instance or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rcl` fails to create the context or the node.
+    pub(super) fn new() -> Result<Self, Error> {
+        // SAFETY: The zero-initialized structures are plain values, and each one is initialized
+        // by `rcl` before use. They are finalized by `Drop`, which only finalizes the ones
+        // `rcl` initialized.
+        unsafe {
+            let mut graph = Self {
+                init_options: Box::new(rcl_get_zero_initialized_init_options()),
+                context: Box::new(rcl_get_zero_initialized_context()),
+                node: Box::new(rcl_get_zero_initialized_node()),
+            };
+
+            check(rcl_init_options_init(
+                &mut *graph.init_options,
+                rcutils_get_default_allocator(),
+            ))?;
+            check(rcl_init(
+                0,
+                ptr::null(),
+                &*graph.init_options,
+                &mut *graph.context,
+            ))?;
+
+            let name = CString::new(NODE_NAME)?;
+            let namespace = CString::new("/")?;
+            let mut options = rcl_node_get_default_options();
+            let result = rcl_node_init(
+                &mut *graph.node,
+                name.as_ptr(),
+                namespace.as_ptr(),
+                &mut *graph.context,
+                &options,
+            );
+            rcl_node_options_fini(&mut options);
+            check(result)?;
+
+            Ok(graph)
+        }
+    }
+
+    /// Returns the type hash of a publisher of a topic, waiting for it to be discovered.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic_name` - A string slice that holds the name of the topic, relative names being
+    ///   resolved in the root namespace.
+    /// * `node_name` - A string slice that holds the name of the node of the publisher.
+    /// * `node_namespace` - A string slice that holds the namespace of the node.
+    /// * `deadline` - When to stop waiting for the publisher.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, Error>` - A result containing the hash, such as `RIHS01_...`, or an
+    ///   error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the graph cannot be queried, if the publisher is
+    /// not discovered before the deadline, or if it reports no type hash.
+    pub(super) fn publisher_type_hash(
+        &self,
+        topic_name: &str,
+        node_name: &str,
+        node_namespace: &str,
+        deadline: Instant,
+    ) -> Result<String, Error> {
+        let topic_name = if topic_name.starts_with('/') {
+            CString::new(topic_name)?
+        } else {
+            CString::new(format!("/{topic_name}"))?
+        };
+
+        loop {
+            if let Some((version, value)) =
+                self.find_type_hash(&topic_name, node_name, node_namespace)?
+            {
+                return stringify_type_hash(version, &value);
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Publisher {node_namespace}/{node_name} not discovered in time"
+                ));
+            }
+            thread::sleep(DISCOVERY_POLL_INTERVAL);
+        }
+    }
+
+    /// Looks up the type hash of a publisher of a topic in the graph as currently discovered.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<(u8, [u8; 32])>, Error>` - A result containing the version and value
+    ///   of the hash, `None` if the publisher is not discovered yet, or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the graph cannot be queried.
+    fn find_type_hash(
+        &self,
+        topic_name: &CStr,
+        node_name: &str,
+        node_namespace: &str,
+    ) -> Result<Option<(u8, [u8; 32])>, Error> {
+        let mut publishers = TopicEndpointInfoArray {
+            size: 0,
+            info_array: ptr::null_mut(),
+        };
+
+        // SAFETY: The node is initialized, and the array is zero-initialized as `rcl` expects.
+        // The array is only read within the bounds `rcl` reports, and finalized with the
+        // allocator it was filled with.
+        unsafe {
+            let mut allocator = rcutils_get_default_allocator();
+            check(rcl_get_publishers_info_by_topic(
+                &*self.node,
+                &mut allocator,
+                topic_name.as_ptr(),
+                false,
+                &mut publishers,
+            ))?;
+
+            let infos: &[TopicEndpointInfo] = if publishers.info_array.is_null() {
+                &[]
+            } else {
+                std::slice::from_raw_parts(publishers.info_array, publishers.size)
+            };
+            let hash = infos
+                .iter()
+                .find(|info| {
+                    CStr::from_ptr(info.node_name).to_bytes() == node_name.as_bytes()
+                        && CStr::from_ptr(info.node_namespace).to_bytes()
+                            == node_namespace.as_bytes()
+                })
+                .map(|info| (info.topic_type_hash.version, info.topic_type_hash.value));
+
+            rmw_topic_endpoint_info_array_fini(&mut publishers, &mut allocator);
+            Ok(hash)
+        }
+    }
+}
+
+impl Drop for GraphNode {
+    fn drop(&mut self) {
+        // SAFETY: Finalizing zero-initialized structures is a no-op in `rcl`, so the ones that
+        // failed to initialize are left alone.
+        unsafe {
+            rcl_node_fini(&mut *self.node);
+            if !self.context.impl_.is_null() {
+                rcl_shutdown(&mut *self.context);
+                rcl_context_fini(&mut *self.context);
+            }
+            if !self.init_options.impl_.is_null() {
+                rcl_init_options_fini(&mut *self.init_options);
+            }
+        }
+    }
+}
+
+/// Formats a type hash as `rosidl_stringify_type_hash` does, such as `RIHS01_` followed by the
+/// hash in lowercase hexadecimal.
+///
+/// # Arguments
+///
+/// * `version` - The version of the hash, 0 if the publisher reported none.
+/// * `value` - The 32 bytes of the hash.
+///
+/// # Returns
+///
+/// * `Result<String, Error>` - A result containing the formatted hash or an error.
+///
+/// # Errors
+///
+/// This function will return an error if the hash is unset or of an unknown version.
+fn stringify_type_hash(version: u8, value: &[u8; 32]) -> Result<String, Error> {
+    match version {
+        0 => Err(anyhow!("The publisher reported no type hash")),
+        TYPE_HASH_VERSION => {
+            let mut hash = format!("RIHS{version:02}_");
+            for byte in value {
+                write!(hash, "{byte:02x}")?;
+            }
+            Ok(hash)
+        }
+        _ => Err(anyhow!("Unknown type hash version {version}")),
+    }
+}
+
+/// Turns the return code of an `rcl` function into an error holding the error message `rcl`
+/// set, and resets it.
+fn check(result: RclRet) -> Result<(), Error> {
+    if result == RCL_RET_OK {
+        return Ok(());
+    }
+
+    // SAFETY: The error string is copied out of the thread-local state of `rcutils`, which is
+    // a null-terminated string.
+    let message = unsafe {
+        let error = rcutils_get_error_string();
+        let message = CStr::from_ptr(error.str_.as_ptr())
+            .to_string_lossy()
+            .into_owned();
+        rcutils_reset_error();
+        message
+    };
+    Err(anyhow!("rcl error {result}: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stringify_type_hash() {
+        let mut value = [0; 32];
+        value[0] = 0xab;
+        value[31] = 0x05;
+        assert_eq!(
+            stringify_type_hash(1, &value).unwrap(),
+            format!("RIHS01_ab{}05", "00".repeat(30))
+        );
+        assert!(stringify_type_hash(0, &value).is_err());
+        assert!(stringify_type_hash(2, &value).is_err());
+    }
+}
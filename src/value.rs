@@ -0,0 +1,595 @@
+//! Generic representation of decoded ROS messages.
+use anyhow::{anyhow, Error, Result};
+use std::sync::OnceLock;
+
+/// A decoded ROS message, or a part of one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int8(i8),
+    Uint8(u8),
+    Int16(i16),
+    Uint16(u16),
+    Int32(i32),
+    Uint32(u32),
+    Int64(i64),
+    Uint64(u64),
+    Float32(f32),
+    Float64(f64),
+
+    /// A `string` or `wstring`.
+    String(String),
+
+    /// An array or sequence of `uint8`, `byte` or `char`, kept as raw bytes.
+    Bytes(Vec<u8>),
+
+    /// Any other array or sequence.
+    Array(Vec<Value>),
+
+    /// A nested message, with its fields in definition order.
+    Message(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Returns the name of the kind of value, for error messages.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - A string slice that holds the kind of the value.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "bool",
+            Self::Int8(_) => "int8",
+            Self::Uint8(_) => "uint8",
+            Self::Int16(_) => "int16",
+            Self::Uint16(_) => "uint16",
+            Self::Int32(_) => "int32",
+            Self::Uint32(_) => "uint32",
+            Self::Int64(_) => "int64",
+            Self::Uint64(_) => "uint64",
+            Self::Float32(_) => "float32",
+            Self::Float64(_) => "float64",
+            Self::String(_) => "string",
+            Self::Bytes(_) => "byte array",
+            Self::Array(_) => "array",
+            Self::Message(_) => "message",
+        }
+    }
+
+    /// Returns the value of a direct field of a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the name of the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&Value>` - The value of the field, or `None` if the value is not a message or has no such field.
+    pub fn field(&self, name: &str) -> Option<&Self> {
+        match self {
+            Self::Message(fields) => fields
+                .iter()
+                .find(|(field_name, _)| field_name == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Looks up a nested value by its path, such as `header.stamp.sec` or `poses[2].position.x`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A string slice that holds the path of the value.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<&Value, Error>` - A result containing the value or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if a field does not exist, an
+    /// index is out of bounds, or the path is malformed, such as `header.` or `a..b` with an
+    /// empty field name.
+    pub fn get(&self, path: &str) -> Result<&Self, Error> {
+        let mut value = self;
+
+        for segment in path.split('.') {
+            let (name, indices) = segment
+                .split_once('[')
+                .map_or((segment, ""), |(name, _)| (name, &segment[name.len()..]));
+
+            // A name may only be left out before an index, as in `[0]` for an array value
+            if name.is_empty() && indices.is_empty() {
+                return Err(anyhow!("Field `{path}`: empty field name"));
+            }
+            if !name.is_empty() {
+                value = value.field(name).ok_or_else(|| match value {
+                    Self::Message(_) => anyhow!("Field `{path}`: no field named `{name}`"),
+                    _ => anyhow!(
+                        "Field `{path}`: cannot look up `{name}` in a {}",
+                        value.kind()
+                    ),
+                })?;
+            }
+
+            let mut indices = indices;
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|indices| indices.split_once(']'))
+                    .ok_or_else(|| anyhow!("Field `{path}`: malformed index in `{segment}`"))?;
+                let index: usize = index
+                    .parse()
+                    .map_err(|err| anyhow!("Field `{path}`: invalid index `{index}`: {err}"))?;
+
+                let Ok(mut elements) = value.elements() else {
+                    return Err(anyhow!(
+                        "Field `{path}`: cannot index into a {}",
+                        value.kind()
+                    ));
+                };
+                let len = elements.len();
+                value = elements.nth(index).ok_or_else(|| {
+                    anyhow!("Field `{path}`: index {index} out of bounds for {len} element(s)")
+                })?;
+                indices = rest;
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Converts any numeric or boolean value into an `f64`.
+    ///
+    /// 64-bit integers that are too large are rounded to the nearest representable value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not numeric.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn as_f64(&self) -> Result<f64, Error> {
+        Ok(match *self {
+            Self::Bool(value) => f64::from(u8::from(value)),
+            Self::Int8(value) => f64::from(value),
+            Self::Uint8(value) => f64::from(value),
+            Self::Int16(value) => f64::from(value),
+            Self::Uint16(value) => f64::from(value),
+            Self::Int32(value) => f64::from(value),
+            Self::Uint32(value) => f64::from(value),
+            Self::Int64(value) => value as f64,
+            Self::Uint64(value) => value as f64,
+            Self::Float32(value) => f64::from(value),
+            Self::Float64(value) => value,
+            _ => return Err(self.mismatch("a number")),
+        })
+    }
+
+    /// Converts any integer or boolean value into an `i64`.
+    ///
+    /// Floating point values are accepted if they hold an integer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not an integer, or does not fit.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn as_i64(&self) -> Result<i64, Error> {
+        Ok(match *self {
+            Self::Bool(value) => i64::from(value),
+            Self::Int8(value) => i64::from(value),
+            Self::Uint8(value) => i64::from(value),
+            Self::Int16(value) => i64::from(value),
+            Self::Uint16(value) => i64::from(value),
+            Self::Int32(value) => i64::from(value),
+            Self::Uint32(value) => i64::from(value),
+            Self::Int64(value) => value,
+            Self::Uint64(value) => {
+                i64::try_from(value).map_err(|_err| anyhow!("{value} does not fit in an i64"))?
+            }
+            Self::Float32(_) | Self::Float64(_) => {
+                let value = self.as_f64()?;
+                if value.fract() != 0.0 || value < i64::MIN as f64 || value >= i64::MAX as f64 {
+                    return Err(anyhow!("{value} is not representable as an i64"));
+                }
+                value as i64
+            }
+            _ => return Err(self.mismatch("an integer")),
+        })
+    }
+
+    /// Converts any non-negative integer or boolean value into a `u64`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not an integer, or is negative.
+    pub fn as_u64(&self) -> Result<u64, Error> {
+        if let Self::Uint64(value) = *self {
+            return Ok(value);
+        }
+        let value = self.as_i64()?;
+        u64::try_from(value).map_err(|_err| anyhow!("{value} is negative"))
+    }
+
+    /// Returns the value of a boolean, or whether an integer is non-zero.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is neither a boolean nor an integer.
+    pub fn as_bool(&self) -> Result<bool, Error> {
+        match *self {
+            Self::Bool(value) => Ok(value),
+            Self::Float32(_) | Self::Float64(_) => Err(self.mismatch("a boolean")),
+            _ => self
+                .as_i64()
+                .map(|value| value != 0)
+                .map_err(|_err| self.mismatch("a boolean")),
+        }
+    }
+
+    /// Returns the contents of a string.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not a string.
+    pub fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            Self::String(value) => Ok(value),
+            _ => Err(self.mismatch("a string")),
+        }
+    }
+
+    /// Returns the contents of a `uint8`, `byte` or `char` array.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not a byte array.
+    pub fn as_bytes(&self) -> Result<&[u8], Error> {
+        match self {
+            Self::Bytes(value) => Ok(value),
+            _ => Err(self.mismatch("a byte array")),
+        }
+    }
+
+    /// Returns the elements of an array.
+    ///
+    /// Byte arrays are not included, use [`Value::as_bytes`] or [`Value::elements`] for them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not an array.
+    pub fn as_array(&self) -> Result<&[Self], Error> {
+        match self {
+            Self::Array(elements) => Ok(elements),
+            _ => Err(self.mismatch("an array")),
+        }
+    }
+
+    /// Returns an iterator over the elements of an array, including byte arrays.
+    ///
+    /// The elements of byte arrays are yielded as [`Value::Uint8`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not an array.
+    pub fn elements(&self) -> Result<Elements<'_>, Error> {
+        let inner = match self {
+            Self::Array(elements) => ElementsInner::Values(elements.iter()),
+            Self::Bytes(bytes) => ElementsInner::Bytes(bytes.iter()),
+            _ => return Err(self.mismatch("an array")),
+        };
+        Ok(Elements { inner })
+    }
+
+    /// Converts every element of a numeric array, including byte arrays, into an `f64`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not an array, or if an element is
+    /// not numeric.
+    pub fn to_f64_vec(&self) -> Result<Vec<f64>, Error> {
+        match self {
+            Self::Bytes(bytes) => Ok(bytes.iter().copied().map(f64::from).collect()),
+            _ => self
+                .elements()?
+                .enumerate()
+                .map(|(index, element)| {
+                    element
+                        .as_f64()
+                        .map_err(|err| anyhow!("Element {index}: {err}"))
+                })
+                .collect(),
+        }
+    }
+
+    /// Looks up a nested value by its path and converts it into an `f64`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not numeric.
+    pub fn get_f64(&self, path: &str) -> Result<f64, Error> {
+        self.get(path)?.as_f64().map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested value by its path and converts it into an `i64`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not an integer.
+    pub fn get_i64(&self, path: &str) -> Result<i64, Error> {
+        self.get(path)?.as_i64().map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested value by its path and converts it into a `u64`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not a non-negative integer.
+    pub fn get_u64(&self, path: &str) -> Result<u64, Error> {
+        self.get(path)?.as_u64().map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested value by its path and converts it into a `bool`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not a boolean.
+    pub fn get_bool(&self, path: &str) -> Result<bool, Error> {
+        self.get(path)?.as_bool().map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested string by its path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not a string.
+    pub fn get_str(&self, path: &str) -> Result<&str, Error> {
+        self.get(path)?.as_str().map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested byte array by its path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not a byte array.
+    pub fn get_bytes(&self, path: &str) -> Result<&[u8], Error> {
+        self.get(path)?
+            .as_bytes()
+            .map_err(|err| at_path(path, &err))
+    }
+
+    /// Looks up a nested array by its path.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error, naming the path, if the value does not exist or is
+    /// not an array.
+    pub fn get_array(&self, path: &str) -> Result<&[Self], Error> {
+        self.get(path)?
+            .as_array()
+            .map_err(|err| at_path(path, &err))
+    }
+
+    fn mismatch(&self, expected: &str) -> Error {
+        anyhow!("expected {expected}, found {}", self.kind())
+    }
+}
+
+/// An iterator over the elements of an array, returned by [`Value::elements`].
+#[derive(Debug, Clone)]
+pub struct Elements<'a> {
+    inner: ElementsInner<'a>,
+}
+
+#[derive(Debug, Clone)]
+enum ElementsInner<'a> {
+    Values(std::slice::Iter<'a, Value>),
+    Bytes(std::slice::Iter<'a, u8>),
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            ElementsInner::Values(values) => values.next(),
+            ElementsInner::Bytes(bytes) => bytes.next().copied().map(byte_value),
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match &mut self.inner {
+            ElementsInner::Values(values) => values.nth(n),
+            ElementsInner::Bytes(bytes) => bytes.nth(n).copied().map(byte_value),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.inner {
+            ElementsInner::Values(values) => values.size_hint(),
+            ElementsInner::Bytes(bytes) => bytes.size_hint(),
+        }
+    }
+}
+
+impl ExactSizeIterator for Elements<'_> {}
+
+/// Returns a shared `Value::Uint8` of a byte, so that the elements of byte arrays can be
+/// borrowed like those of other arrays.
+fn byte_value(byte: u8) -> &'static Value {
+    static VALUES: OnceLock<Vec<Value>> = OnceLock::new();
+
+    &VALUES.get_or_init(|| (0..=u8::MAX).map(Value::Uint8).collect())[usize::from(byte)]
+}
+
+/// Prefixes an error with the path of the value it is about.
+fn at_path(path: &str, err: &Error) -> Error {
+    anyhow!("Field `{path}`: {err}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(fields: Vec<(&str, Value)>) -> Value {
+        Value::Message(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.to_owned(), value))
+                .collect(),
+        )
+    }
+
+    fn odometry() -> Value {
+        message(vec![
+            (
+                "header",
+                message(vec![
+                    (
+                        "stamp",
+                        message(vec![
+                            ("sec", Value::Int32(12)),
+                            ("nanosec", Value::Uint32(500_000_000)),
+                        ]),
+                    ),
+                    ("frame_id", Value::String("odom".to_owned())),
+                ]),
+            ),
+            (
+                "poses",
+                Value::Array(vec![
+                    message(vec![("x", Value::Float64(1.5))]),
+                    message(vec![("x", Value::Float64(-2.0))]),
+                ]),
+            ),
+            ("data", Value::Bytes(vec![1, 2, 3])),
+            ("big", Value::Uint64(u64::MAX)),
+            ("valid", Value::Bool(true)),
+        ])
+    }
+
+    #[test]
+    fn test_get() {
+        let msg = odometry();
+
+        assert_eq!(msg.get("header.stamp.sec").unwrap(), &Value::Int32(12));
+        assert_eq!(msg.get("poses[1].x").unwrap(), &Value::Float64(-2.0));
+        assert_eq!(msg.get_array("poses").unwrap().len(), 2);
+        assert_eq!(
+            msg.get("poses[0]").unwrap().get("x").unwrap(),
+            &Value::Float64(1.5)
+        );
+    }
+
+    #[test]
+    fn test_get_errors() {
+        let msg = odometry();
+
+        assert_eq!(
+            msg.get("header.stmp.sec").unwrap_err().to_string(),
+            "Field `header.stmp.sec`: no field named `stmp`"
+        );
+        assert_eq!(
+            msg.get("poses[2].x").unwrap_err().to_string(),
+            "Field `poses[2].x`: index 2 out of bounds for 2 element(s)"
+        );
+        assert_eq!(
+            msg.get("header.frame_id.x").unwrap_err().to_string(),
+            "Field `header.frame_id.x`: cannot look up `x` in a string"
+        );
+        assert_eq!(
+            msg.get("header[0]").unwrap_err().to_string(),
+            "Field `header[0]`: cannot index into a message"
+        );
+        assert!(msg.get("poses[x]").is_err());
+        assert!(msg.get("poses[0").is_err());
+
+        for path in ["", "header.", ".header", "header..stamp"] {
+            assert_eq!(
+                msg.get(path).unwrap_err().to_string(),
+                format!("Field `{path}`: empty field name")
+            );
+        }
+        assert_eq!(
+            msg.get("poses").unwrap().get("[0].x").unwrap(),
+            &Value::Float64(1.5)
+        );
+    }
+
+    #[test]
+    fn test_byte_arrays() {
+        let msg = odometry();
+
+        assert_eq!(msg.get("data[2]").unwrap(), &Value::Uint8(3));
+        assert_eq!(msg.get_u64("data[0]").unwrap(), 1);
+        assert_eq!(
+            msg.get("data[3]").unwrap_err().to_string(),
+            "Field `data[3]`: index 3 out of bounds for 3 element(s)"
+        );
+
+        let elements = msg.get("data").unwrap().elements().unwrap();
+        assert_eq!(elements.len(), 3);
+        assert_eq!(
+            elements.cloned().collect::<Vec<_>>(),
+            [Value::Uint8(1), Value::Uint8(2), Value::Uint8(3)]
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let msg = odometry();
+
+        assert_eq!(msg.get_f64("header.stamp.sec").unwrap(), 12.0);
+        assert_eq!(msg.get_i64("header.stamp.nanosec").unwrap(), 500_000_000);
+        assert_eq!(msg.get_u64("big").unwrap(), u64::MAX);
+        assert_eq!(msg.get_i64("poses[1].x").unwrap(), -2);
+        assert_eq!(msg.get_str("header.frame_id").unwrap(), "odom");
+        assert_eq!(msg.get_bytes("data").unwrap(), &[1, 2, 3]);
+        assert!(msg.get_bool("valid").unwrap());
+        assert_eq!(
+            msg.get("data").unwrap().to_f64_vec().unwrap(),
+            [1.0, 2.0, 3.0]
+        );
+
+        let xs: Vec<f64> = msg
+            .get("poses")
+            .unwrap()
+            .elements()
+            .unwrap()
+            .map(|pose| pose.get_f64("x").unwrap())
+            .collect();
+        assert_eq!(xs, [1.5, -2.0]);
+    }
+
+    #[test]
+    fn test_conversion_errors() {
+        let msg = odometry();
+
+        assert_eq!(
+            msg.get_f64("header.frame_id").unwrap_err().to_string(),
+            "Field `header.frame_id`: expected a number, found string"
+        );
+        assert_eq!(
+            msg.get_i64("big").unwrap_err().to_string(),
+            "Field `big`: 18446744073709551615 does not fit in an i64"
+        );
+        assert_eq!(
+            msg.get_i64("poses[0].x").unwrap_err().to_string(),
+            "Field `poses[0].x`: 1.5 is not representable as an i64"
+        );
+        assert_eq!(
+            msg.get_u64("poses[1].x").unwrap_err().to_string(),
+            "Field `poses[1].x`: -2 is negative"
+        );
+        assert_eq!(
+            msg.get_str("header.stamp").unwrap_err().to_string(),
+            "Field `header.stamp`: expected a string, found message"
+        );
+        assert!(msg.get_bytes("poses").is_err());
+        assert!(msg.get("valid").unwrap().elements().is_err());
+    }
+}
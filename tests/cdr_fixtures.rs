@@ -0,0 +1,97 @@
+//! Decodes the CDR fixtures in `tests/fixtures/cdr`, serialized messages along with their full
+//! definition and expected value, without a running ROS graph.
+//!
+//! The fixtures are generated with `scripts/generate_cdr_fixtures.py`, which serializes them with
+//! `rclpy` and records the ROS 2 distribution and the RMW implementation it ran with. Fixtures
+//! that were not serialized by ROS 2 are rejected, since they would only check the decoder
+//! against another reading of the layout.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rerun_ros::cdr::{
+    decode_with_limits, encode_message, CdrReader, CdrWriter, DecodeLimits, DecodePlan, WcharSize,
+};
+use rerun_ros::render::{render, RenderOptions, TextFormat};
+use rerun_ros::ros_introspection::{Dialect, MsgSpec};
+
+/// Returns the size of the `wstring` characters written by an RMW implementation.
+fn wchar_size(name: &str, distro: &str, rmw: &str) -> WcharSize {
+    assert!(
+        rmw.starts_with("rmw_fastrtps"),
+        "{name}: the wstring characters of {rmw} are unknown, generate the fixtures with Fast DDS"
+    );
+    // Fast DDS moved from Fast-CDR 1 to Fast-CDR 2 in Jazzy
+    if matches!(distro, "humble" | "iron") {
+        WcharSize::Four
+    } else {
+        WcharSize::Two
+    }
+}
+
+#[test]
+fn test_decode_fixtures() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cdr");
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+    assert!(paths.len() >= 14, "Missing fixtures: {paths:?}");
+
+    for path in paths {
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let fixture: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        let topic_type = fixture["type"].as_str().unwrap();
+        assert_eq!(
+            fixture["serializer"], "rclpy",
+            "{name}: not serialized by ROS 2, regenerate the fixtures with \
+             `python3 scripts/generate_cdr_fixtures.py` in a sourced ROS 2 environment"
+        );
+        let (Some(distro), Some(rmw)) = (fixture["distro"].as_str(), fixture["rmw"].as_str())
+        else {
+            panic!("{name}: the ROS 2 distribution or the RMW implementation is not recorded");
+        };
+        let wchar_size = wchar_size(&name, distro, rmw);
+        let limits = DecodeLimits::default().with_wchar_size(wchar_size);
+        let definitions = fs::read_to_string(path.with_extension("msg")).unwrap();
+        let buffer = fs::read(path.with_extension("cdr")).unwrap();
+
+        let spec = MsgSpec::new_from_definitions(topic_type, &definitions, Dialect::Ros2)
+            .unwrap_or_else(|err| panic!("{name}: {err:#}"));
+        let value = decode_with_limits(&spec, &buffer, limits)
+            .unwrap_or_else(|err| panic!("{name}: {err:#}"));
+        let planned = DecodePlan::new(&spec)
+            .unwrap()
+            .decode_with_limits(&buffer, limits)
+            .unwrap();
+        assert_eq!(planned, value, "{name}: the plan decodes differently");
+
+        let rendered = render(&spec, &value, &RenderOptions::new(TextFormat::Json, None)).unwrap();
+        let decoded: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(decoded, fixture["value"], "{name}: unexpected value");
+
+        // Serializers may pad the end of the buffer to a multiple of 4 bytes
+        let encapsulation = CdrReader::new(&buffer).unwrap().encapsulation();
+        let mut writer = CdrWriter::new(encapsulation)
+            .unwrap()
+            .with_wchar_size(wchar_size);
+        encode_message(&mut writer, &spec, &value).unwrap();
+        let encoded = writer.finish();
+        assert!(
+            encoded.len() <= buffer.len() && buffer.len() - encoded.len() < 4,
+            "{name}: encoded to {} byte(s) instead of {}",
+            encoded.len(),
+            buffer.len()
+        );
+        assert_eq!(
+            decode_with_limits(&spec, &encoded, limits).unwrap(),
+            value,
+            "{name}"
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Property-based round trips through the message definition parser, the full definition
+//! export, and the CDR encoder and decoders, on random definitions and matching values.
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use proptest::string::string_regex;
+
+use rerun_ros::cdr::{decode, encode, DecodePlan};
+use rerun_ros::ros_introspection::{parse_message_definitions, Dialect, Layout, MsgSpec, Type};
+use rerun_ros::value::Value;
+
+const PACKAGE: &str = "prop_msgs";
+
+/// The characters of unbounded strings and wstrings, which span 1 to 4 bytes in UTF-8.
+const TEXT: &str = "[ -~äöüß€ハローワールド🦀]";
+
+const PRIMITIVES: [&str; 13] = [
+    "bool", "byte", "char", "float32", "float64", "int8", "uint8", "int16", "uint16", "int32",
+    "uint32", "int64", "uint64",
+];
+
+#[derive(Debug, Clone)]
+enum Element {
+    Primitive(&'static str),
+    String {
+        wide: bool,
+        bound: Option<usize>,
+    },
+    /// A nested message, by its index among the generated messages.
+    Nested(usize),
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Single,
+    Fixed(usize),
+    Bounded(usize),
+    Unbounded,
+}
+
+#[derive(Debug, Clone)]
+enum Line {
+    Field {
+        element: Element,
+        shape: Shape,
+        default: Option<u8>,
+    },
+    Constant(i32),
+}
+
+/// A generated message, which may only nest the messages that follow it, so that the
+/// definitions are never recursive.
+#[derive(Debug, Clone)]
+struct MessageDef {
+    lines: Vec<Line>,
+}
+
+fn type_name(index: usize) -> String {
+    format!("{PACKAGE}/Message{index}")
+}
+
+fn element_strategy(first_nested: usize, count: usize) -> BoxedStrategy<Element> {
+    let mut elements = vec![
+        proptest::sample::select(PRIMITIVES.to_vec())
+            .prop_map(Element::Primitive)
+            .boxed(),
+        (any::<bool>(), proptest::option::of(1usize..8))
+            .prop_map(|(wide, bound)| Element::String { wide, bound })
+            .boxed(),
+    ];
+    if first_nested < count {
+        elements.push((first_nested..count).prop_map(Element::Nested).boxed());
+    }
+    Union::new(elements).boxed()
+}
+
+fn line_strategy(first_nested: usize, count: usize) -> BoxedStrategy<Line> {
+    // Fixed-size arrays of no element are not valid in ROS 2 interfaces
+    let shape = prop_oneof![
+        3 => Just(Shape::Single),
+        1 => (1usize..4).prop_map(Shape::Fixed),
+        1 => (1usize..4).prop_map(Shape::Bounded),
+        1 => Just(Shape::Unbounded),
+    ];
+    let field = (
+        element_strategy(first_nested, count),
+        shape,
+        proptest::option::of(0u8..100),
+    )
+        .prop_map(|(element, shape, default)| Line::Field {
+            element,
+            shape,
+            default,
+        });
+    prop_oneof![1 => any::<i32>().prop_map(Line::Constant), 8 => field].boxed()
+}
+
+fn messages_strategy() -> impl Strategy<Value = Vec<MessageDef>> {
+    (1usize..=4).prop_flat_map(|count| {
+        (0..count)
+            .map(|index| {
+                vec(line_strategy(index + 1, count), 0..6).prop_map(|lines| MessageDef { lines })
+            })
+            .collect::<Vec<_>>()
+    })
+}
+
+/// Writes the definition of a message, in the canonical form of `Field::definition`.
+fn message_definition(message: &MessageDef) -> String {
+    let mut definition = String::new();
+    for (index, line) in message.lines.iter().enumerate() {
+        let (element, shape, default) = match line {
+            Line::Constant(value) => {
+                definition.push_str(&format!("int32 CONSTANT_{index}={value}\n"));
+                continue;
+            }
+            Line::Field {
+                element,
+                shape,
+                default,
+            } => (element, shape, default),
+        };
+        match element {
+            Element::Primitive(primitive) => definition.push_str(primitive),
+            Element::String { wide, bound } => {
+                definition.push_str(if *wide { "wstring" } else { "string" });
+                if let Some(bound) = bound {
+                    definition.push_str(&format!("<={bound}"));
+                }
+            }
+            Element::Nested(nested) => definition.push_str(&type_name(*nested)),
+        }
+        match shape {
+            Shape::Single => {}
+            Shape::Fixed(size) => definition.push_str(&format!("[{size}]")),
+            Shape::Bounded(bound) => definition.push_str(&format!("[<={bound}]")),
+            Shape::Unbounded => definition.push_str("[]"),
+        }
+        definition.push_str(&format!(" field_{index}"));
+        match (element, shape, default) {
+            (Element::Primitive(primitive), Shape::Single, Some(default))
+                if *primitive != "bool" =>
+            {
+                definition.push_str(&format!(" {default}"));
+            }
+            _ => {}
+        }
+        definition.push('\n');
+    }
+    definition
+}
+
+/// Writes the full definition of the first message, with the messages it depends on in
+/// depth-first order, which is the order of `MsgSpec::full_definition`.
+fn full_definition(messages: &[MessageDef]) -> (String, usize) {
+    let mut definitions = message_definition(&messages[0]);
+    let mut exported = vec![0];
+    let mut pending = nested_indices(&messages[0]);
+    pending.reverse();
+
+    while let Some(index) = pending.pop() {
+        if exported.contains(&index) {
+            continue;
+        }
+        definitions.push_str(&"=".repeat(80));
+        definitions.push('\n');
+        definitions.push_str(&format!("MSG: {}\n", type_name(index)));
+        definitions.push_str(&message_definition(&messages[index]));
+        exported.push(index);
+        pending.extend(nested_indices(&messages[index]).into_iter().rev());
+    }
+
+    (definitions, exported.len())
+}
+
+fn nested_indices(message: &MessageDef) -> Vec<usize> {
+    message
+        .lines
+        .iter()
+        .filter_map(|line| match line {
+            Line::Field {
+                element: Element::Nested(index),
+                ..
+            } => Some(*index),
+            _ => None,
+        })
+        .collect()
+}
+
+fn primitive_strategy(primitive: &str) -> BoxedStrategy<Value> {
+    use proptest::num::{f32, f64};
+
+    match primitive {
+        "bool" => any::<bool>().prop_map(Value::Bool).boxed(),
+        "int8" => any::<i8>().prop_map(Value::Int8).boxed(),
+        "int16" => any::<i16>().prop_map(Value::Int16).boxed(),
+        "uint16" => any::<u16>().prop_map(Value::Uint16).boxed(),
+        "int32" => any::<i32>().prop_map(Value::Int32).boxed(),
+        "uint32" => any::<u32>().prop_map(Value::Uint32).boxed(),
+        "int64" => any::<i64>().prop_map(Value::Int64).boxed(),
+        "uint64" => any::<u64>().prop_map(Value::Uint64).boxed(),
+        // NaN is left out, as it never compares equal to itself
+        "float32" => (f32::POSITIVE | f32::NEGATIVE | f32::NORMAL | f32::SUBNORMAL | f32::ZERO)
+            .prop_map(Value::Float32)
+            .boxed(),
+        "float64" => (f64::POSITIVE | f64::NEGATIVE | f64::NORMAL | f64::SUBNORMAL | f64::ZERO)
+            .prop_map(Value::Float64)
+            .boxed(),
+        _ => any::<u8>().prop_map(Value::Uint8).boxed(),
+    }
+}
+
+fn element_value_strategy(
+    element: &Element,
+    nested: &[Option<BoxedStrategy<Value>>],
+) -> BoxedStrategy<Value> {
+    match element {
+        Element::Primitive(primitive) => primitive_strategy(primitive),
+        // Bounds count bytes for strings, hence ASCII, and characters for wstrings
+        Element::String {
+            wide: false,
+            bound: Some(bound),
+        } => string_regex(&format!("[ -~]{{0,{bound}}}"))
+            .expect("The pattern of bounded strings is valid")
+            .prop_map(Value::String)
+            .boxed(),
+        Element::String {
+            wide: true,
+            bound: Some(bound),
+        } => string_regex(&format!("{TEXT}{{0,{bound}}}"))
+            .expect("The pattern of bounded wstrings is valid")
+            .prop_map(Value::String)
+            .boxed(),
+        Element::String { bound: None, .. } => string_regex(&format!("{TEXT}{{0,12}}"))
+            .expect("The pattern of strings is valid")
+            .prop_map(Value::String)
+            .boxed(),
+        Element::Nested(index) => nested[*index]
+            .clone()
+            .expect("Nested messages follow the messages nesting them"),
+    }
+}
+
+fn field_value_strategy(
+    element: &Element,
+    shape: &Shape,
+    nested: &[Option<BoxedStrategy<Value>>],
+) -> BoxedStrategy<Value> {
+    let len = match shape {
+        Shape::Single => return element_value_strategy(element, nested),
+        Shape::Fixed(size) => *size..=*size,
+        Shape::Bounded(bound) => 0..=*bound,
+        Shape::Unbounded => 0..=4,
+    };
+    match element {
+        Element::Primitive("byte" | "char" | "uint8") => {
+            vec(any::<u8>(), len).prop_map(Value::Bytes).boxed()
+        }
+        _ => vec(element_value_strategy(element, nested), len)
+            .prop_map(Value::Array)
+            .boxed(),
+    }
+}
+
+/// Generates values of the first message, building the strategies of the nested messages first.
+fn value_strategy(messages: &[MessageDef]) -> BoxedStrategy<Value> {
+    let mut strategies: Vec<Option<BoxedStrategy<Value>>> = vec![None; messages.len()];
+    for (index, message) in messages.iter().enumerate().rev() {
+        let fields: Vec<BoxedStrategy<(String, Value)>> = message
+            .lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| match line {
+                Line::Field { element, shape, .. } => Some(
+                    field_value_strategy(element, shape, &strategies)
+                        .prop_map(move |value| (format!("field_{index}"), value))
+                        .boxed(),
+                ),
+                Line::Constant(_) => None,
+            })
+            .collect();
+        strategies[index] = Some(fields.prop_map(Value::Message).boxed());
+    }
+    strategies[0].take().expect("There is at least one message")
+}
+
+fn case_strategy() -> impl Strategy<Value = (Vec<MessageDef>, Value)> {
+    messages_strategy().prop_flat_map(|messages| {
+        let value = value_strategy(&messages);
+        (Just(messages), value)
+    })
+}
+
+proptest! {
+    // Parsing definitions is slow in debug builds, as each field compiles its regexes
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_definition_and_cdr_roundtrip((messages, value) in case_strategy()) {
+        let root = format!("{PACKAGE}/msg/Message0");
+        let (definitions, count) = full_definition(&messages);
+
+        let parsed = parse_message_definitions(&definitions, &Type::new(&root).unwrap()).unwrap();
+        prop_assert_eq!(parsed.len(), count);
+
+        let spec = MsgSpec::new_from_definitions(&root, &definitions, Dialect::Ros2).unwrap();
+        let exported = spec.full_definition();
+        prop_assert_eq!(&exported, &definitions);
+
+        let reparsed = MsgSpec::new_from_definitions(&root, &exported, Dialect::Ros2).unwrap();
+        let encoded = encode(&reparsed, &value).unwrap();
+        prop_assert_eq!(&decode(&spec, &encoded).unwrap(), &value);
+        prop_assert_eq!(&DecodePlan::new(&spec).unwrap().decode(&encoded).unwrap(), &value);
+
+        // The encapsulation header is not part of the layout
+        let payload_size = encoded.len() - 4;
+        match spec.layout() {
+            Layout::Fixed { size, .. } => prop_assert_eq!(payload_size, size),
+            Layout::Bounded { max_size } => prop_assert!(payload_size <= max_size),
+            Layout::Unbounded => {}
+        }
+    }
+}
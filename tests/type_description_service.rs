@@ -0,0 +1,70 @@
+//! Fetches a type description from a real `~/get_type_description` service, the one of a
+//! `ros2 topic pub` process, and checks it against the definition installed locally.
+//!
+//! Needs a sourced ROS 2 Jazzy installation, as the type-description feature does.
+#![cfg(feature = "type-description")]
+
+use std::env;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rerun_ros::ros_introspection::MsgSpec;
+use rerun_ros::type_description_client::fetch_msg_spec;
+
+const TOPIC: &str = "/rerun_ros_type_description_test";
+const ROS_TYPE: &str = "geometry_msgs/msg/PoseStamped";
+
+/// Stops the publisher when the test ends, even if it fails.
+struct Publisher(Child);
+
+impl Drop for Publisher {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+/// Lists the serialized fields of a type and of its nested types, with their types.
+fn layout(spec: &MsgSpec, prefix: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    for (field, child) in spec.serialized_fields() {
+        let path = format!("{prefix}{}", field.name());
+        fields.push(format!(
+            "{path}: {} {}",
+            field.type_().name(),
+            field.array_size()
+        ));
+        if let Some(child) = child {
+            fields.extend(layout(child, &format!("{path}.")));
+        }
+    }
+    fields
+}
+
+#[test]
+fn test_fetch_msg_spec() {
+    let _publisher = Publisher(
+        Command::new("ros2")
+            .args(["topic", "pub", "--rate", "10", TOPIC, ROS_TYPE, "{}"])
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("Failed to start `ros2 topic pub`, is ROS 2 sourced?"),
+    );
+    let context = rclrs::Context::new(env::args()).unwrap();
+    let node = rclrs::create_node(&context, "rerun_ros_type_description_test").unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while node.count_publishers(TOPIC).unwrap() == 0 {
+        assert!(
+            Instant::now() < deadline,
+            "The publisher was not discovered"
+        );
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    // The service rejects requests without the type hash of the publisher
+    let fetched = fetch_msg_spec(&node, TOPIC, ROS_TYPE, Duration::from_secs(10)).unwrap();
+    let installed = MsgSpec::new(ROS_TYPE).unwrap();
+    assert_eq!(layout(&fetched, ""), layout(&installed, ""));
+}